@@ -1,71 +1,206 @@
 //! CLI tool for animalese text-to-speech
 //!
-//! Interactive mode: animalese
-//! With text: animalese "hello world"
-//! Piped: echo "hello" | animalese
-//! With flags: animalese --voice m1 --pitch=-5.0
+//! Speak text: animalese speak "hello world"
+//! Render to a file: animalese render "hello world" hello.wav
+//! List voices: animalese voices
+//! Interactive mode: animalese interactive
+//!
+//! The flat forms from before subcommands still work: animalese "hello world",
+//! echo "hello" | animalese, animalese --voice m1 --pitch=-5.0, and no
+//! arguments at all enters interactive mode.
 
-use animalese::{Animalese, VoiceProfile, VoiceType};
-use clap::Parser;
+use animalese::{Animalese, PlannedSound, SoundSource, StreamBackend, VoiceProfile, VoiceType, bundled_assets_path};
+use clap::{Args, Parser, Subcommand};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     terminal::{disable_raw_mode, enable_raw_mode},
 };
-use std::io::{self, Read};
+use kira::manager::{AudioManager, AudioManagerSettings};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
 #[derive(Parser, Debug)]
 #[command(name = "animalese-cli")]
 #[command(about = "Real-time animalese typing sounds", long_about = None)]
-struct Args {
-    /// Text to speak (if not provided, enters interactive mode)
-    text: Option<String>,
-
-    /// Voice type: f1, f2, f3, f4, m1, m2, m3, m4
-    #[arg(short, long, default_value = "f1")]
-    voice: String,
-
-    /// Pitch shift in semitones (-12.0 to 12.0)
-    #[arg(short, long, default_value = "0.0")]
-    pitch: f32,
-
-    /// Random pitch variation (0.0 to 2.0)
-    #[arg(short = 'r', long, default_value = "0.8")]
-    variation: f32,
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// Volume (0.0 to 1.0)
-    #[arg(short = 'V', long, default_value = "0.65")]
-    volume: f32,
-
-    /// Intonation: pitch glide over sentence (-1.0 falling to 1.0 rising)
-    #[arg(short = 'i', long, default_value = "0.0")]
-    intonation: f32,
+    /// Text to speak (shorthand for `speak`; enters interactive mode if omitted)
+    text: Option<String>,
 
-    /// Path to audio assets directory (defaults to bundled assets)
-    #[arg(short, long)]
-    assets: Option<String>,
+    #[command(flatten)]
+    voice: VoiceArgs,
 
-    /// List available voices and exit
+    /// List available voices and exit (same as `voices`)
     #[arg(short, long)]
     list: bool,
 
     /// Play test phrase with current settings
     #[arg(short = 't', long)]
     test: bool,
+
+    #[command(flatten)]
+    dry_run: DryRunArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Speak text, or piped input when no text is given
+    Speak {
+        /// Text to speak
+        text: Option<String>,
+
+        #[command(flatten)]
+        voice: VoiceArgs,
+
+        #[command(flatten)]
+        dry_run: DryRunArgs,
+    },
+    /// Render text to a WAV file without playing it
+    Render {
+        /// Text to render
+        text: String,
+
+        /// Output WAV file
+        output: PathBuf,
+
+        #[command(flatten)]
+        voice: VoiceArgs,
+    },
+    /// List available voices
+    Voices,
+    /// Type to hear sounds as you go
+    Interactive {
+        #[command(flatten)]
+        voice: VoiceArgs,
+
+        /// Keep the output device awake so the first key after a pause isn't clipped
+        #[arg(long)]
+        keepalive: bool,
+
+        #[command(flatten)]
+        rhythm: RhythmArgs,
+    },
+}
+
+/// Printing the sprite plan instead of playing it
+#[derive(Args, Debug, Clone)]
+struct DryRunArgs {
+    /// Print the planned sounds and total duration without opening an audio device
+    #[arg(long)]
+    dry_run: bool,
+
+    /// With --dry-run, also write one subtitle per line of text to an SRT file
+    #[arg(long, value_name = "PATH", requires = "dry_run")]
+    srt: Option<PathBuf>,
+}
+
+/// How typing speed shapes interactive playback
+#[derive(Args, Debug, Clone, Copy)]
+struct RhythmArgs {
+    /// Keys closer together than this (in ms) play shorter and higher
+    #[arg(long, visible_alias = "fast-threshold-ms", value_name = "MS", default_value_t = 100)]
+    fast_threshold: u64,
+
+    /// Shortest a letter gets when keys are pressed almost together (in ms)
+    #[arg(long, visible_alias = "fast-cutoff-ms", value_name = "MS", default_value_t = 30)]
+    min_duration: u64,
+}
+
+impl Default for RhythmArgs {
+    fn default() -> Self {
+        Self { fast_threshold: 100, min_duration: 30 }
+    }
 }
 
-fn parse_voice_type(s: &str) -> Result<VoiceType, String> {
-    match s.to_lowercase().as_str() {
-        "f1" => Ok(VoiceType::F1),
-        "f2" => Ok(VoiceType::F2),
-        "f3" => Ok(VoiceType::F3),
-        "f4" => Ok(VoiceType::F4),
-        "m1" => Ok(VoiceType::M1),
-        "m2" => Ok(VoiceType::M2),
-        "m3" => Ok(VoiceType::M3),
-        "m4" => Ok(VoiceType::M4),
-        _ => Err(format!("Invalid voice type: {}", s)),
+/// Length of a letter sprite, the most a keystroke can play
+const FULL_LETTER: Duration = Duration::from_millis(animalese::LETTER_SLOT_MS);
+
+/// Pitch lift in semitones for keys pressed almost together
+const FAST_PITCH_LIFT: f32 = 1.0;
+
+/// How fast a key pressed `interval` after the last was, from 0.0 (at or
+/// past the threshold) to 1.0 (pressed together)
+fn typing_speed(interval: Duration, threshold: Duration) -> f32 {
+    if interval >= threshold {
+        return 0.0;
     }
+    1.0 - interval.as_secs_f32() / threshold.as_secs_f32()
+}
+
+/// Sprite cut-off for a key pressed `interval` after the last
+///
+/// At or past `threshold` the whole sprite plays (`None`). Below it, the
+/// cut-off shrinks linearly from the full 200ms sprite down to `cutoff` as
+/// the interval approaches zero. Short cut-offs keep fast bursts from
+/// overlapping into clicks.
+fn duration_for_interval(interval: Duration, threshold: Duration, cutoff: Duration) -> Option<Duration> {
+    if interval >= threshold {
+        return None;
+    }
+    let speed = typing_speed(interval, threshold);
+    let full = FULL_LETTER.as_millis() as f32;
+    let min = (cutoff.as_millis() as f32).min(full);
+    Some(Duration::from_millis((full - (full - min) * speed).round() as u64))
+}
+
+/// Sprite cut-off and pitch lift for a key pressed `interval` after the last
+///
+/// The cut-off is [`duration_for_interval`] with the rhythm flags; the pitch
+/// rises linearly alongside it, up to +1 semitone as the interval
+/// approaches zero.
+fn keystroke_playback(interval: Duration, rhythm: RhythmArgs) -> (Option<Duration>, f32) {
+    let threshold = Duration::from_millis(rhythm.fast_threshold);
+    let cutoff = Duration::from_millis(rhythm.min_duration);
+    let duration = duration_for_interval(interval, threshold, cutoff);
+    (duration, typing_speed(interval, threshold) * FAST_PITCH_LIFT)
+}
+
+/// Voice settings shared by every command that makes sound
+///
+/// Flags left unset fall back to the loaded profile, then to the defaults.
+#[derive(Args, Debug)]
+struct VoiceArgs {
+    /// Voice type: f1, f2, f3, f4, m1, m2, m3, m4 [default: f1]
+    #[arg(short, long)]
+    voice: Option<String>,
+
+    /// Pitch shift in semitones (-12.0 to 12.0) [default: 0.0]
+    #[arg(short, long)]
+    pitch: Option<f32>,
+
+    /// Random pitch variation (0.0 to 2.0) [default: 0.8]
+    #[arg(short = 'r', long)]
+    variation: Option<f32>,
+
+    /// Volume (0.0 to 1.0) [default: 0.65]
+    #[arg(short = 'V', long)]
+    volume: Option<f32>,
+
+    /// Intonation: pitch glide over sentence (-1.0 falling to 1.0 rising) [default: 0.0]
+    #[arg(short = 'i', long)]
+    intonation: Option<f32>,
+
+    /// Seed the random pitch variation so runs sound the same [default: random]
+    #[arg(long, value_name = "U64")]
+    seed: Option<u64>,
+
+    /// Path to audio assets directory (defaults to bundled assets)
+    #[arg(short, long)]
+    assets: Option<String>,
+
+    /// Start from a profile saved with --save-profile (flags still override it)
+    #[arg(long, value_name = "PATH")]
+    load_profile: Option<PathBuf>,
+
+    /// Save the resulting voice settings to a TOML profile
+    #[arg(long, value_name = "PATH")]
+    save_profile: Option<PathBuf>,
 }
 
 fn list_voices() {
@@ -74,13 +209,13 @@ fn list_voices() {
     println!("  m1, m2, m3, m4  - Male voices");
 }
 
-fn interactive_mode(engine: &Animalese, args: &Args) -> Result<(), Box<dyn std::error::Error>> {
-    let assets_info = args.assets.as_ref()
-        .map(|s| s.as_str())
+fn interactive_mode(engine: &Animalese, args: &VoiceArgs, rhythm: RhythmArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let assets_info = args.assets.as_deref()
         .unwrap_or("bundled");
+    let profile = engine.profile();
     println!("🎮 Animalese Interactive Mode");
     println!("   Voice: {}, Pitch: {}, Variation: {}, Intonation: {}, Assets: {}",
-             args.voice, args.pitch, args.variation, args.intonation, assets_info);
+             profile.voice_type.name(), profile.pitch_shift, profile.pitch_variation, profile.intonation, assets_info);
     println!("   Type to hear sounds. Press Esc or Ctrl-C to exit.\n");
 
     enable_raw_mode()?;
@@ -89,51 +224,45 @@ fn interactive_mode(engine: &Animalese, args: &Args) -> Result<(), Box<dyn std::
         let mut last_keystroke = Instant::now();
 
         loop {
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(KeyEvent { code, modifiers, .. }) = event::read()? {
-                    match code {
-                        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
-                            break;
-                        }
-                        KeyCode::Esc => {
-                            break;
-                        }
-                        KeyCode::Char(c) => {
-                            if c.is_ascii_alphabetic() {
-                                // Detect typing speed
-                                let now = Instant::now();
-                                let time_since_last = now.duration_since(last_keystroke);
-                                last_keystroke = now;
-
-                                // If typing fast (< 100ms between keys), use shorter duration
-                                let max_duration = if time_since_last < Duration::from_millis(100) {
-                                    Some(Duration::from_millis(30)) // Cut off early to prevent clicks
-                                } else {
-                                    None // Play full duration
-                                };
-
-                                engine.play_letter_with_duration(c, max_duration)?;
-                            }
-                            // Print any printable character (including spaces)
-                            if !c.is_control() {
-                                print!("{}", c);
-                                io::Write::flush(&mut io::stdout())?;
-                            }
-                        }
-                        KeyCode::Enter => {
-                            engine.play_sfx("enter")?;
-                            println!();
+            if event::poll(Duration::from_millis(100))?
+                && let Event::Key(KeyEvent { code, modifiers, .. }) = event::read()?
+            {
+                match code {
+                    KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                        break;
+                    }
+                    KeyCode::Esc => {
+                        break;
+                    }
+                    KeyCode::Char(c) => {
+                        if c.is_ascii_alphabetic() {
+                            // Faster typing plays shorter, slightly higher letters
+                            let now = Instant::now();
+                            let time_since_last = now.duration_since(last_keystroke);
+                            last_keystroke = now;
+
+                            let (max_duration, pitch) = keystroke_playback(time_since_last, rhythm);
+                            engine.play_letter_with(c, max_duration, pitch)?;
                         }
-                        KeyCode::Backspace => {
-                            engine.play_sfx("backspace")?;
-                            print!("\x08 \x08"); // Move back, print space, move back again
+                        // Print any printable character (including spaces)
+                        if !c.is_control() {
+                            print!("{}", c);
                             io::Write::flush(&mut io::stdout())?;
                         }
-                        KeyCode::Tab => {
-                            engine.play_sfx("tab")?;
-                        }
-                        _ => {}
                     }
+                    KeyCode::Enter => {
+                        engine.play_sfx("enter")?;
+                        println!();
+                    }
+                    KeyCode::Backspace => {
+                        engine.play_sfx("backspace")?;
+                        print!("\x08 \x08"); // Move back, print space, move back again
+                        io::Write::flush(&mut io::stdout())?;
+                    }
+                    KeyCode::Tab => {
+                        engine.play_sfx("tab")?;
+                    }
+                    _ => {}
                 }
             }
         }
@@ -146,45 +275,63 @@ fn interactive_mode(engine: &Animalese, args: &Args) -> Result<(), Box<dyn std::
     result
 }
 
+/// Extra pause the CLI has always left for a space, unless a profile is loaded
+const WORD_PAUSE: Duration = Duration::from_millis(100);
+
+/// Extra pause the CLI has always left for a line break, unless a profile is loaded
+const LINE_PAUSE: Duration = Duration::from_millis(200);
+
 fn play_text(engine: &Animalese, text: &str) -> Result<(), Box<dyn std::error::Error>> {
-    for c in text.chars() {
-        if c.is_ascii_alphabetic() {
-            engine.play_letter(c)?;
-            std::thread::sleep(Duration::from_millis(50));
-        } else if c == ' ' {
-            std::thread::sleep(Duration::from_millis(100));
-        } else if c == '\n' {
-            std::thread::sleep(Duration::from_millis(200));
-        }
-    }
-    std::thread::sleep(Duration::from_millis(300));
-    Ok(())
+    // The engine handles cadence, pauses, intonation and the word length cap.
+    // It waits until the last sprite has finished, which replaces the fixed
+    // 300ms the old letter loop slept for it to ring out.
+    engine.speak_blocking(text)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
+/// Build a voice profile from the voice settings
+///
+/// Starts from --load-profile if given, applies any explicit flags, then
+/// writes the result to --save-profile if given.
+fn voice_profile(args: &VoiceArgs) -> Result<VoiceProfile, Box<dyn std::error::Error>> {
+    let mut profile = match &args.load_profile {
+        Some(path) => VoiceProfile::load(path)
+            .map_err(|e| format!("Failed to load profile '{}': {}", path.display(), e))?,
+        None => VoiceProfile { word_pause: WORD_PAUSE, line_pause: LINE_PAUSE, ..Default::default() },
+    };
 
-    // Handle list flag
-    if args.list {
-        list_voices();
-        return Ok(());
+    if let Some(voice) = &args.voice {
+        profile.voice_type = voice.parse::<VoiceType>()
+            .map_err(|e| format!("{}\nUse `animalese voices` to see available voices", e))?;
+    }
+    if let Some(pitch) = args.pitch {
+        profile.pitch_shift = pitch;
+    }
+    if let Some(variation) = args.variation {
+        profile.pitch_variation = variation;
+    }
+    if let Some(volume) = args.volume {
+        profile.volume = volume;
+    }
+    if let Some(intonation) = args.intonation {
+        profile.intonation = intonation;
     }
 
-    // Parse voice type
-    let voice_type = parse_voice_type(&args.voice)
-        .map_err(|e| format!("{}\nUse --list to see available voices", e))?;
+    // Save what the engine will play, and nothing from_toml would reject, like NaN
+    let profile = profile.clamped();
+    if let Some(path) = &args.save_profile {
+        profile.save(path)
+            .map_err(|e| format!("Failed to save profile '{}': {}", path.display(), e))?;
+        println!("Saved profile to {}", path.display());
+    }
+    Ok(profile)
+}
 
-    // Create voice profile
-    let profile = VoiceProfile {
-        voice_type,
-        pitch_shift: args.pitch,
-        pitch_variation: args.variation,
-        volume: args.volume,
-        intonation: args.intonation,
-    };
+/// Build an engine on the default output device with the voice settings applied
+fn create_engine(args: &VoiceArgs) -> Result<Animalese, Box<dyn std::error::Error>> {
+    let profile = voice_profile(args)?;
 
     // Initialize engine with bundled assets or custom path
-    let mut engine = if let Some(custom_path) = &args.assets {
+    let engine = if let Some(custom_path) = &args.assets {
         Animalese::with_custom_assets(custom_path)
             .map_err(|e| format!("Failed to load audio files from '{}': {}", custom_path, e))?
     } else {
@@ -193,31 +340,280 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     engine.set_profile(profile);
+    if let Some(seed) = args.seed {
+        engine.set_seed(seed);
+    }
+    Ok(engine)
+}
+
+/// Speak text, or piped input if there is no text
+fn speak(args: &VoiceArgs, text: Option<String>, dry_run: &DryRunArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if dry_run.dry_run {
+        let text = match text {
+            Some(text) => text,
+            None => io::read_to_string(io::stdin())?,
+        };
+        return print_plan(args, &text, dry_run.srt.as_deref());
+    }
+
+    let engine = Arc::new(create_engine(args)?);
+    let interrupted = stop_on_interrupt(&engine)?;
+
+    match text {
+        Some(text) => play_text(&engine, &text)?,
+        // Start speaking each line as it arrives rather than waiting for EOF
+        None => engine.speak_reader(io::stdin().lock())?,
+    }
+    exit_if_interrupted(&interrupted);
+    Ok(())
+}
 
-    // Handle test flag
-    if args.test {
-        println!("🎮 Testing voice: {} (pitch: {}, variation: {}, volume: {}, intonation: {})",
-                 args.voice, args.pitch, args.variation, args.volume, args.intonation);
-        println!("Speaking: 'hello world'");
-        play_text(&engine, "hello world")?;
-        return Ok(());
-    }
-
-    // Determine mode based on input
-    if let Some(text) = args.text {
-        // Text provided as argument
-        play_text(&engine, &text)?;
-    } else if atty::isnt(atty::Stream::Stdin) {
-        // Piped input
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        if !buffer.trim().is_empty() {
-            play_text(&engine, &buffer)?;
+/// Stop playback instead of dying mid-sound on Ctrl-C (or SIGTERM)
+///
+/// The signal only records its number; a watcher thread notices it, calls
+/// `engine.stop()`, lets the fade finish and exits with 128 + the signal
+/// number (130 for SIGINT, 143 for SIGTERM). The exit happens there because
+/// signal-hook restarts interrupted reads, so a `speak_reader` blocked on
+/// stdin would never return. Returns the signal number for
+/// [`exit_if_interrupted`].
+fn stop_on_interrupt(engine: &Arc<Animalese>) -> Result<Arc<AtomicUsize>, Box<dyn std::error::Error>> {
+    let interrupted = Arc::new(AtomicUsize::new(0));
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        signal_hook::flag::register_usize(signal, interrupted.clone(), signal as usize)?;
+    }
+
+    let engine = engine.clone();
+    let signal = interrupted.clone();
+    std::thread::spawn(move || {
+        let signo = loop {
+            match signal.load(Ordering::SeqCst) {
+                0 => std::thread::sleep(Duration::from_millis(20)),
+                signo => break signo,
+            }
+        };
+        engine.stop();
+        // Let the stop fade finish before the device closes
+        std::thread::sleep(Duration::from_millis(50));
+        println!();
+        std::process::exit(128 + signo as i32);
+    });
+    Ok(interrupted)
+}
+
+/// Wait for the watcher to exit if playback ended because of a signal
+///
+/// Returning from `main` would exit with status 0 before the watcher gets
+/// to exit with the signal's status.
+fn exit_if_interrupted(interrupted: &AtomicUsize) {
+    if interrupted.load(Ordering::SeqCst) != 0 {
+        loop {
+            std::thread::park();
+        }
+    }
+}
+
+/// Build an engine with the voice settings that renders without an output device
+fn offline_engine(args: &VoiceArgs) -> Result<Animalese<StreamBackend>, Box<dyn std::error::Error>> {
+    let profile = voice_profile(args)?;
+    let assets = args.assets.clone()
+        .unwrap_or_else(|| bundled_assets_path().to_string_lossy().to_string());
+
+    let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings::default())?;
+    let engine = Animalese::with_audio_manager(assets.as_str(), manager)
+        .map_err(|e| format!("Failed to load audio files from '{}': {}", assets, e))?;
+    engine.set_profile(profile);
+    if let Some(seed) = args.seed {
+        engine.set_seed(seed);
+    }
+    Ok(engine)
+}
+
+/// Render text to a WAV file without opening an output device
+fn render(args: &VoiceArgs, text: &str, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = offline_engine(args)?;
+    engine.render_to_wav(text, output)?;
+    println!("Wrote {}", output.display());
+    Ok(())
+}
+
+/// Print the sounds `speak` would play, optionally writing subtitles too
+fn print_plan(args: &VoiceArgs, text: &str, srt: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let engine = offline_engine(args)?;
+    let plan = engine.plan(text);
+
+    for sound in &plan {
+        let what = match &sound.source {
+            SoundSource::Letter(c) => format!("letter  {}", c),
+            SoundSource::Special(name) => format!("special [{}]", name),
+            SoundSource::Sfx(name) => format!("sfx     {}", name),
+        };
+        let pitch = sound.intonation_shift + sound.pitch_offset;
+        println!("{:>6}ms  {:<20} {:+.2} st", sound.offset.as_millis(), what, pitch);
+    }
+    println!("{} sounds, about {:.2}s", plan.len(), engine.estimated_duration(text).as_secs_f32());
+
+    if let Some(path) = srt {
+        let cues = line_cues(text, &plan, |line| engine.plan(line).len());
+        std::fs::write(path, to_srt(&cues))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Subtitle timing for each line of text that makes a sound
+///
+/// `sounds_in` counts the sounds a line plays on its own, which is how the
+/// plan for the whole text is split back into lines.
+fn line_cues(text: &str, plan: &[PlannedSound], sounds_in: impl Fn(&str) -> usize) -> Vec<(Duration, Duration, String)> {
+    let mut cues = Vec::new();
+    let mut sounds = plan.iter();
+    for line in text.lines() {
+        let line_sounds: Vec<_> = sounds.by_ref().take(sounds_in(line)).collect();
+        if let (Some(first), Some(last)) = (line_sounds.first(), line_sounds.last()) {
+            cues.push((first.offset, last.offset + last.length, line.trim().to_string()));
+        }
+    }
+    cues
+}
+
+/// Format cues as an SRT subtitle file
+fn to_srt(cues: &[(Duration, Duration, String)]) -> String {
+    let timestamp = |t: &Duration| {
+        let ms = t.as_millis();
+        format!("{:02}:{:02}:{:02},{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+    };
+    cues.iter()
+        .enumerate()
+        .map(|(i, (start, end, line))| format!("{}\n{} --> {}\n{}\n\n", i + 1, timestamp(start), timestamp(end), line))
+        .collect()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    let command = match cli.command {
+        Some(command) => command,
+        // The flat argument set from before subcommands
+        None if cli.list => Command::Voices,
+        None if cli.test => {
+            let engine = Arc::new(create_engine(&cli.voice)?);
+            let profile = engine.profile();
+            println!("🎮 Testing voice: {} (pitch: {}, variation: {}, volume: {}, intonation: {})",
+                     profile.voice_type.name(), profile.pitch_shift, profile.pitch_variation, profile.volume, profile.intonation);
+            println!("Speaking: 'hello world'");
+            let interrupted = stop_on_interrupt(&engine)?;
+            play_text(&engine, "hello world")?;
+            exit_if_interrupted(&interrupted);
+            return Ok(());
+        }
+        None if cli.text.is_some() || cli.dry_run.dry_run || atty::isnt(atty::Stream::Stdin) => Command::Speak {
+            text: cli.text,
+            voice: cli.voice,
+            dry_run: cli.dry_run,
+        },
+        None => Command::Interactive { voice: cli.voice, keepalive: false, rhythm: RhythmArgs::default() },
+    };
+
+    match command {
+        Command::Speak { text, voice, dry_run } => speak(&voice, text, &dry_run)?,
+        Command::Render { text, output, voice } => render(&voice, &text, &output)?,
+        Command::Voices => list_voices(),
+        Command::Interactive { voice, keepalive, rhythm } => {
+            let engine = create_engine(&voice)?;
+            engine.set_keepalive(keepalive)?;
+            interactive_mode(&engine, &voice, rhythm)?;
         }
-    } else {
-        // Interactive mode
-        interactive_mode(&engine, &args)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystroke_playback() {
+        let rhythm = RhythmArgs::default();
+        let at = |ms: u64| keystroke_playback(Duration::from_millis(ms), rhythm);
+
+        // Slow typing plays whole letters at the profile pitch
+        assert_eq!(at(100), (None, 0.0));
+        assert_eq!(at(800), (None, 0.0));
+
+        // Faster keys get shorter and higher, continuously
+        let (duration, pitch) = at(50);
+        assert_eq!(duration, Some(Duration::from_millis(115)));
+        assert!((pitch - 0.5).abs() < 1e-6);
+        let (duration, pitch) = at(0);
+        assert_eq!(duration, Some(Duration::from_millis(30)));
+        assert!((pitch - 1.0).abs() < 1e-6);
+
+        let samples: Vec<_> = (0..100).step_by(10).map(at).collect();
+        assert!(samples.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn test_seed_flag_is_reproducible() {
+        let args = Cli::parse_from(["animalese", "--seed", "42", "hello"]).voice;
+        assert_eq!(args.seed, Some(42));
+        let render = || offline_engine(&args).unwrap().render_to_samples("hello").unwrap();
+        assert_eq!(render(), render());
+    }
+
+    #[test]
+    fn test_saved_profile_loads_back() {
+        let path = std::env::temp_dir().join(format!("animalese-cli-profile-{}.toml", std::process::id()));
+        let path_arg = path.to_string_lossy().to_string();
+        let args = Cli::parse_from(["animalese", "--pitch", "NaN", "--volume", "5", "--save-profile", &path_arg, "hi"]).voice;
+        let saved = voice_profile(&args).unwrap();
+        let loaded = VoiceProfile::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), saved);
+        assert_eq!((saved.pitch_shift, saved.volume), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_srt_cues() {
+        let args = Cli::parse_from(["animalese"]).voice;
+        let engine = offline_engine(&args).unwrap();
+        let text = "hi\n\n... \nyo [ok]";
+        let plan = engine.plan(text);
+        let cues = line_cues(text, &plan, |line| engine.plan(line).len());
+
+        assert_eq!(cues.len(), 2, "silent lines get no cue");
+        assert_eq!(cues[0], (Duration::ZERO, Duration::from_millis(250), "hi".to_string()));
+        assert_eq!(cues[1].2, "yo [ok]");
+        assert_eq!(cues[1].1, plan.last().map(|s| s.offset + s.length).unwrap());
+
+        let srt = to_srt(&[(Duration::from_millis(1500), Duration::from_millis(3_723_004), "hi".to_string())]);
+        assert_eq!(srt, "1\n00:00:01,500 --> 01:02:03,004\nhi\n\n");
+    }
+
+    #[test]
+    fn test_keystroke_playback_flags() {
+        let rhythm = RhythmArgs { fast_threshold: 200, min_duration: 80 };
+        assert_eq!(keystroke_playback(Duration::from_millis(150), rhythm).0, Some(Duration::from_millis(170)));
+        assert_eq!(keystroke_playback(Duration::from_millis(250), rhythm), (None, 0.0));
+
+        // A zero threshold turns the effect off
+        let off = RhythmArgs { fast_threshold: 0, min_duration: 30 };
+        assert_eq!(keystroke_playback(Duration::ZERO, off), (None, 0.0));
+
+        let cli = Cli::parse_from(["animalese", "interactive", "--fast-threshold-ms", "150", "--fast-cutoff-ms", "50"]);
+        let Some(Command::Interactive { rhythm, .. }) = cli.command else { panic!("expected interactive") };
+        assert_eq!((rhythm.fast_threshold, rhythm.min_duration), (150, 50));
+    }
+
+    #[test]
+    fn test_duration_for_interval() {
+        let ms = Duration::from_millis;
+        assert_eq!(duration_for_interval(ms(100), ms(100), ms(50)), None);
+        assert_eq!(duration_for_interval(ms(0), ms(100), ms(50)), Some(ms(50)));
+        assert_eq!(duration_for_interval(ms(50), ms(100), ms(50)), Some(ms(125)));
+
+        // A cutoff longer than the sprite never lengthens it
+        assert_eq!(duration_for_interval(ms(0), ms(100), ms(500)), Some(ms(200)));
+    }
+}
@@ -0,0 +1,345 @@
+//! Recording what the engine plays
+//!
+//! Sprites are routed through a mixer sub-track carrying a [`CaptureTap`]
+//! effect. While capturing is switched on, the tap copies every frame it sees
+//! into a ring buffer on the audio thread; the engine drains it into a
+//! [`Capture`] from the speaking thread. The tap also watches the mixer's
+//! clock against the wall clock to count underruns.
+
+use kira::{
+    clock::clock_info::ClockInfoProvider,
+    modulator::value_provider::ModulatorValueProvider,
+    effect::{Effect, EffectBuilder},
+    Frame,
+};
+use ringbuf::{HeapConsumer, HeapProducer, HeapRb};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Frames the tap can hold between drains (about 1.5s at 44.1kHz)
+const CAPTURE_CAPACITY: usize = 1 << 16;
+
+/// How far past its usual swing the mixer may fall behind the wall clock
+/// before it's an underrun
+///
+/// The mixer renders a whole device buffer at once and then waits, so its
+/// clock runs up to one buffer ahead of the wall clock and back. The
+/// detector measures that swing and only counts shortfalls beyond it by
+/// more than this margin.
+pub(crate) const UNDERRUN_MARGIN: Duration = Duration::from_millis(50);
+
+/// How fast the baseline may creep up to follow the device clock running
+/// slower than the wall clock, as a fraction of the time passed
+///
+/// Device clocks drift by around 100 ppm; this allows ten times that, which
+/// still takes 50s to hide a 50ms dropout.
+const MAX_CLOCK_DRIFT: f64 = 0.001;
+
+/// Frames between wall clock checks
+const UNDERRUN_CHECK_FRAMES: u32 = 64;
+
+/// Audio recorded by [`Animalese::speak_capturing`](crate::Animalese::speak_capturing)
+/// or rendered by [`Animalese::render_to_samples`](crate::Animalese::render_to_samples)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    /// Sample rate of the engine's mixer
+    pub sample_rate: u32,
+    /// Interleaved stereo samples
+    pub samples: Vec<i16>,
+}
+
+impl Capture {
+    /// Number of channels in `samples` (always 2)
+    pub fn channels(&self) -> u16 {
+        2
+    }
+
+    /// Length of the recorded audio
+    pub fn duration(&self) -> Duration {
+        if self.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+        let frames = self.samples.len() / self.channels() as usize;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    /// Encode as a 16-bit PCM WAV file
+    pub fn to_wav_bytes(&self) -> Vec<u8> {
+        let channels = self.channels();
+        let block_align = channels * 2;
+        let data_len = (self.samples.len() * 2) as u32;
+
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+
+    /// Write as a 16-bit PCM WAV file
+    pub fn write_wav(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_wav_bytes())
+    }
+}
+
+/// State shared between the tap and the engine
+#[derive(Debug, Default)]
+pub(crate) struct CaptureState {
+    pub capturing: AtomicBool,
+    pub sample_rate: AtomicU32,
+    pub underruns: AtomicU64,
+}
+
+/// Counts underruns by comparing audio rendered with time passed
+///
+/// When the device asks for audio the mixer doesn't deliver in time, it plays
+/// silence, and from then on less audio has been rendered than time has
+/// passed. Each time that shortfall grows past the baseline by more than
+/// the largest render burst seen plus [`UNDERRUN_MARGIN`], one underrun is
+/// counted and the shortfall becomes the new baseline. The baseline also
+/// creeps up by at most [`MAX_CLOCK_DRIFT`] to follow clock drift.
+#[derive(Debug, Default)]
+pub(crate) struct UnderrunDetector {
+    started: Option<Instant>,
+    /// Seconds of audio rendered since `started`
+    rendered: f64,
+    /// Smallest shortfall of rendered audio against elapsed time seen lately
+    baseline: f64,
+    /// Shortfall and elapsed seconds at the last check
+    last: (f64, f64),
+    /// Shortfall where the current run of falling shortfalls began
+    burst_start: f64,
+    /// Most audio seen rendered in one burst, in seconds
+    burst: f64,
+    frames: u32,
+}
+
+impl UnderrunDetector {
+    /// Account for one frame of `dt` seconds, returning whether an underrun
+    /// was found
+    ///
+    /// The wall clock is only read every [`UNDERRUN_CHECK_FRAMES`] frames.
+    pub fn frame(&mut self, dt: f64, now: impl FnOnce() -> Instant) -> bool {
+        self.rendered += dt;
+        self.frames += 1;
+        if self.frames < UNDERRUN_CHECK_FRAMES {
+            return false;
+        }
+        self.frames = 0;
+
+        let now = now();
+        let Some(started) = self.started else {
+            self.started = Some(now);
+            self.rendered = 0.0;
+            return false;
+        };
+        let elapsed = now.duration_since(started).as_secs_f64();
+        let shortfall = elapsed - self.rendered;
+        let (last_shortfall, last_elapsed) = std::mem::replace(&mut self.last, (shortfall, elapsed));
+
+        // Rendering a buffer in one go runs the shortfall down; how far is the burst
+        if shortfall < last_shortfall {
+            self.burst = self.burst.max(self.burst_start - shortfall);
+        } else {
+            self.burst_start = shortfall;
+        }
+
+        if shortfall < self.baseline {
+            self.baseline = shortfall;
+            false
+        } else if shortfall - self.baseline > self.burst + UNDERRUN_MARGIN.as_secs_f64() {
+            self.baseline = shortfall;
+            true
+        } else {
+            self.baseline = shortfall.min(self.baseline + (elapsed - last_elapsed) * MAX_CLOCK_DRIFT);
+            false
+        }
+    }
+}
+
+/// Builds a [`CaptureTap`] for a mixer track
+pub(crate) struct CaptureTapBuilder {
+    pub state: Arc<CaptureState>,
+    pub producer: HeapProducer<Frame>,
+}
+
+/// Create a tap builder and the consumer end of its buffer
+pub(crate) fn capture_tap(state: Arc<CaptureState>) -> (CaptureTapBuilder, HeapConsumer<Frame>) {
+    let (producer, consumer) = HeapRb::new(CAPTURE_CAPACITY).split();
+    (CaptureTapBuilder { state, producer }, consumer)
+}
+
+impl EffectBuilder for CaptureTapBuilder {
+    type Handle = ();
+
+    fn build(self) -> (Box<dyn Effect>, Self::Handle) {
+        let tap = CaptureTap {
+            state: self.state,
+            producer: self.producer,
+            underruns: UnderrunDetector::default(),
+        };
+        (Box::new(tap), ())
+    }
+}
+
+/// Pass-through effect that copies frames out while capturing
+///
+/// Frames that don't fit because the buffer wasn't drained in time are
+/// dropped rather than blocking the audio thread. Underruns are only counted
+/// here; the engine logs them from its own thread.
+pub(crate) struct CaptureTap {
+    state: Arc<CaptureState>,
+    producer: HeapProducer<Frame>,
+    underruns: UnderrunDetector,
+}
+
+impl Effect for CaptureTap {
+    fn init(&mut self, sample_rate: u32) {
+        self.state.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn on_change_sample_rate(&mut self, sample_rate: u32) {
+        self.state.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    fn process(
+        &mut self,
+        input: Frame,
+        dt: f64,
+        _clock_info_provider: &ClockInfoProvider,
+        _modulator_value_provider: &ModulatorValueProvider,
+    ) -> Frame {
+        if self.state.capturing.load(Ordering::Relaxed) {
+            let _ = self.producer.push(input);
+        }
+        if self.underruns.frame(dt, Instant::now) {
+            self.state.underruns.fetch_add(1, Ordering::Relaxed);
+        }
+        input
+    }
+}
+
+/// Convert a float sample to 16-bit PCM
+pub(crate) fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_duration() {
+        let capture = Capture { sample_rate: 100, samples: vec![0; 300] };
+        assert_eq!(capture.duration(), Duration::from_millis(1500));
+        assert_eq!(Capture { sample_rate: 0, samples: vec![0; 4] }.duration(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_wav_header() {
+        let capture = Capture { sample_rate: 44_100, samples: vec![1, -1, 2, -2] };
+        let wav = capture.to_wav_bytes();
+
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 44);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes(wav[22..24].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 44_100);
+        assert_eq!(u32::from_le_bytes(wav[28..32].try_into().unwrap()), 176_400);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 8);
+        assert_eq!(&wav[44..46], &1i16.to_le_bytes());
+    }
+
+    /// Render `seconds` of audio in step with a wall clock at `wall`,
+    /// returning the underruns found
+    fn render(detector: &mut UnderrunDetector, start: Instant, wall: &mut f64, seconds: f64) -> usize {
+        render_bursts(detector, start, wall, seconds, 1, 1.0)
+    }
+
+    /// Render like a device asking for `burst` frames at a time, all at once
+    /// and then nothing until it's played them, with the device clock
+    /// running at `clock_rate` times the wall clock
+    fn render_bursts(detector: &mut UnderrunDetector, start: Instant, wall: &mut f64, seconds: f64, burst: usize, clock_rate: f64) -> usize {
+        let dt = 1.0 / 44_100.0;
+        let mut found = 0;
+        for _ in 0..(seconds / dt) as usize / burst {
+            for _ in 0..burst {
+                found += detector.frame(dt, || start + Duration::from_secs_f64(*wall)) as usize;
+            }
+            *wall += burst as f64 * dt / clock_rate;
+        }
+        found
+    }
+
+    #[test]
+    fn test_underrun_detector() {
+        let start = Instant::now();
+        let mut detector = UnderrunDetector::default();
+        let mut wall = 0.0;
+
+        // Rendering in step with the clock, or running ahead of it, is fine
+        assert_eq!(render(&mut detector, start, &mut wall, 1.0), 0);
+        wall -= 0.03;
+        assert_eq!(render(&mut detector, start, &mut wall, 1.0), 0);
+
+        // A 100ms stall is one underrun, and the clock is trusted again after
+        wall += 0.1;
+        assert_eq!(render(&mut detector, start, &mut wall, 1.0), 1);
+        assert_eq!(render(&mut detector, start, &mut wall, 1.0), 0);
+
+        // A stall shorter than the margin isn't
+        wall += 0.02;
+        assert_eq!(render(&mut detector, start, &mut wall, 1.0), 0);
+    }
+
+    #[test]
+    fn test_underrun_detector_large_buffers() {
+        let start = Instant::now();
+        let mut detector = UnderrunDetector::default();
+        let mut wall = 0.0;
+
+        // 4096 frames is 93ms, more than the margin on its own
+        assert_eq!(render_bursts(&mut detector, start, &mut wall, 5.0, 4096, 1.0), 0);
+
+        // A stall on top of the buffer's swing still counts
+        wall += 0.2;
+        assert_eq!(render_bursts(&mut detector, start, &mut wall, 1.0, 4096, 1.0), 1);
+    }
+
+    #[test]
+    fn test_underrun_detector_clock_drift() {
+        let start = Instant::now();
+        let mut detector = UnderrunDetector::default();
+        let mut wall = 0.0;
+
+        // A device clock 100 ppm slow falls 60ms behind over ten minutes
+        assert_eq!(render_bursts(&mut detector, start, &mut wall, 600.0, 512, 0.9999), 0);
+    }
+
+    #[test]
+    fn test_to_i16() {
+        assert_eq!(to_i16(0.0), 0);
+        assert_eq!(to_i16(1.0), i16::MAX);
+        assert_eq!(to_i16(-2.0), -i16::MAX);
+        assert_eq!(to_i16(0.5), 16384);
+    }
+}
@@ -0,0 +1,98 @@
+//! Output device discovery
+//!
+//! Thin wrappers over cpal's host enumeration, used by
+//! [`AnimaleseBuilder::device`](crate::AnimaleseBuilder::device) to route
+//! playback somewhere other than the system default, such as a virtual
+//! cable feeding OBS.
+
+use cpal::SupportedBufferSize;
+use cpal::traits::{DeviceTrait, HostTrait};
+
+/// An output device that animalese can play through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Name to pass to [`AnimaleseBuilder::device`](crate::AnimaleseBuilder::device)
+    pub name: String,
+    /// Whether this is the system default output
+    pub is_default: bool,
+}
+
+/// List the output devices on the default audio host
+///
+/// Returns an empty list if the host can't enumerate devices. Devices whose
+/// name can't be read are skipped.
+///
+/// # Example
+/// ```no_run
+/// for device in animalese::list_output_devices() {
+///     println!("{}{}", device.name, if device.is_default { " (default)" } else { "" });
+/// }
+/// ```
+pub fn list_output_devices() -> Vec<DeviceInfo> {
+    let host = cpal::default_host();
+    let default_name = host.default_output_device().and_then(|d| d.name().ok());
+
+    let Ok(devices) = host.output_devices() else {
+        return Vec::new();
+    };
+    devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| DeviceInfo {
+            is_default: Some(&name) == default_name.as_ref(),
+            name,
+        })
+        .collect()
+}
+
+/// Find an output device on the default host by exact name
+pub(crate) fn find_output_device(name: &str) -> Option<cpal::Device> {
+    cpal::default_host()
+        .output_devices()
+        .ok()?
+        .find(|device| device.name().is_ok_and(|n| n == name))
+}
+
+/// The buffer size a device will actually run at when asked for `frames`
+///
+/// Requests outside the range the device reports are pulled into it, since
+/// cpal refuses to open a stream with an unsupported fixed size. Devices that
+/// don't report a range get the request as-is.
+pub(crate) fn supported_buffer_size(device: Option<&cpal::Device>, frames: u32) -> u32 {
+    let default_device;
+    let device = match device {
+        Some(device) => device,
+        None => match cpal::default_host().default_output_device() {
+            Some(device) => {
+                default_device = device;
+                &default_device
+            }
+            None => return frames,
+        },
+    };
+    match device.default_output_config() {
+        Ok(config) => fit_buffer_size(frames, config.buffer_size()),
+        Err(_) => frames,
+    }
+}
+
+/// Pull a requested buffer size into a device's supported range
+fn fit_buffer_size(frames: u32, supported: &SupportedBufferSize) -> u32 {
+    match supported {
+        SupportedBufferSize::Range { min, max } if min <= max => frames.clamp(*min, *max),
+        _ => frames,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_buffer_size() {
+        let range = SupportedBufferSize::Range { min: 64, max: 4096 };
+        assert_eq!(fit_buffer_size(256, &range), 256);
+        assert_eq!(fit_buffer_size(16, &range), 64);
+        assert_eq!(fit_buffer_size(10_000, &range), 4096);
+        assert_eq!(fit_buffer_size(16, &SupportedBufferSize::Unknown), 16);
+    }
+}
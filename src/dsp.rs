@@ -0,0 +1,332 @@
+//! Offline processing applied to sprite frames before playback
+
+use kira::Frame;
+
+/// Supported range for `VoiceProfile::time_stretch`
+pub(crate) const MIN_TIME_STRETCH: f32 = 0.25;
+pub(crate) const MAX_TIME_STRETCH: f32 = 4.0;
+
+/// Change the length of a sound without changing its pitch
+///
+/// A basic WSOLA (waveform similarity overlap-add): 20ms Hann-windowed grains
+/// are read from the input at a rate set by `factor` and overlap-added at a
+/// fixed hop, with each grain nudged by up to 5ms to line up with the waveform
+/// of the previous one. `factor` is the output length relative to the input,
+/// so 0.6 turns a 200ms sprite into 120ms.
+///
+/// Works well for the voiced, fairly steady sprites in animalese packs. Fast
+/// transients get smeared slightly and extreme factors can sound phasey or
+/// stuttery; it's no substitute for a proper phase vocoder.
+pub(crate) fn time_stretch(input: &[Frame], sample_rate: u32, factor: f32) -> Vec<Frame> {
+    let factor = factor.clamp(MIN_TIME_STRETCH, MAX_TIME_STRETCH);
+    let window = ((sample_rate as usize / 50) & !1).max(2);
+    if input.len() <= window || factor == 1.0 {
+        return input.to_vec();
+    }
+
+    let synthesis_hop = window / 2;
+    let analysis_hop = synthesis_hop as f32 / factor;
+    let tolerance = window / 4;
+    let out_len = (input.len() as f32 * factor).round() as usize;
+    let last_start = input.len() - window;
+
+    let hann: Vec<f32> = (0..window)
+        .map(|n| 0.5 - 0.5 * (std::f32::consts::TAU * n as f32 / window as f32).cos())
+        .collect();
+
+    let mut output = vec![Frame::ZERO; out_len + window];
+    let mut weights = vec![0.0f32; out_len + window];
+    let mut previous: Option<usize> = None;
+
+    let mut k = 0;
+    while k * synthesis_hop < out_len {
+        let nominal = ((k as f32 * analysis_hop) as usize).min(last_start);
+
+        // Pick the grain that best continues the previous one
+        let start = match previous {
+            None => nominal,
+            Some(prev) => {
+                let natural = (prev + synthesis_hop).min(last_start);
+                let lo = nominal.saturating_sub(tolerance);
+                let hi = (nominal + tolerance).min(last_start);
+                (lo..=hi)
+                    .max_by(|a, b| {
+                        correlation(input, natural, *a, window)
+                            .total_cmp(&correlation(input, natural, *b, window))
+                    })
+                    .unwrap_or(nominal)
+            }
+        };
+
+        let at = k * synthesis_hop;
+        for n in 0..window {
+            output[at + n] += input[start + n] * hann[n];
+            weights[at + n] += hann[n];
+        }
+
+        previous = Some(start);
+        k += 1;
+    }
+
+    output.truncate(out_len);
+    for (frame, weight) in output.iter_mut().zip(weights) {
+        if weight > 1e-3 {
+            *frame /= weight;
+        }
+    }
+    output
+}
+
+/// Pitch range [`estimate_period`] searches, in Hz
+const MIN_PITCH_HZ: f32 = 60.0;
+const MAX_PITCH_HZ: f32 = 600.0;
+
+/// Normalized autocorrelation below which a sound counts as unvoiced
+const VOICING_THRESHOLD: f32 = 0.5;
+
+/// Fraction of the best autocorrelation a shorter lag needs to be picked
+const OCTAVE_TOLERANCE: f32 = 0.9;
+
+/// The pitch period of a sound in frames, or `None` if it isn't voiced
+///
+/// Picks the shortest lag whose normalized autocorrelation between 60 and
+/// 600 Hz peaks close to the strongest, over the whole sound, so it suits
+/// short, steady sprites rather than gliding speech.
+pub(crate) fn estimate_period(input: &[Frame], sample_rate: u32) -> Option<usize> {
+    let min_lag = (sample_rate as f32 / MAX_PITCH_HZ) as usize;
+    let max_lag = (sample_rate as f32 / MIN_PITCH_HZ) as usize;
+    if min_lag == 0 || input.len() < max_lag * 2 {
+        return None;
+    }
+
+    let mono: Vec<f32> = input.iter().map(|frame| frame.left + frame.right).collect();
+    let span = mono.len() - max_lag;
+    let energy: f32 = mono[..span].iter().step_by(2).map(|x| x * x).sum();
+    if energy <= 1e-6 {
+        return None;
+    }
+
+    let score = |lag: usize| {
+        let cross: f32 = (0..span).step_by(2).map(|n| mono[n] * mono[n + lag]).sum();
+        let lagged: f32 = (0..span).step_by(2).map(|n| mono[n + lag] * mono[n + lag]).sum();
+        2.0 * cross / (energy + lagged).max(1e-6)
+    };
+    let scores: Vec<f32> = (min_lag..=max_lag).map(score).collect();
+    let best = scores.iter().copied().fold(f32::MIN, f32::max);
+    if best < VOICING_THRESHOLD {
+        return None;
+    }
+    // Multiples of the period score about as well, so take the first peak
+    let peak = (1..scores.len() - 1)
+        .find(|&i| scores[i] >= best * OCTAVE_TOLERANCE && scores[i] >= scores[i - 1] && scores[i] >= scores[i + 1])?;
+    Some(min_lag + peak)
+}
+
+/// Shift the pitch of a sound by `rate` while keeping its formants and length
+///
+/// A basic TD-PSOLA: the sound is cut into Hann-windowed grains two pitch
+/// periods long, one per period, and the grains are overlap-added one period
+/// divided by `rate` apart, reusing or skipping grains to keep the length.
+/// Each grain keeps its own spectral envelope, so the vowel colour stays put
+/// while the pitch moves, instead of the chipmunk effect of playing faster.
+///
+/// Approximate: the period is estimated once per sound (see
+/// [`estimate_period`]), so gliding pitch and breathy sounds come out rough,
+/// and large shifts buzz. Unvoiced sounds are returned unchanged.
+pub(crate) fn pitch_shift_formants(input: &[Frame], sample_rate: u32, rate: f32) -> Vec<Frame> {
+    if rate <= 0.0 || rate == 1.0 {
+        return input.to_vec();
+    }
+    let Some(period) = estimate_period(input, sample_rate) else {
+        return input.to_vec();
+    };
+
+    let window = period * 2;
+    let hann: Vec<f32> = (0..window)
+        .map(|n| 0.5 - 0.5 * (std::f32::consts::TAU * n as f32 / window as f32).cos())
+        .collect();
+    let last_grain = input.len().saturating_sub(window);
+    let hop = period as f32 / rate;
+
+    let mut output = vec![Frame::ZERO; input.len() + window];
+    let mut weights = vec![0.0f32; input.len() + window];
+    let mut mark = 0.0f32;
+    while (mark as usize) < input.len() {
+        // The grain whose analysis mark is nearest this synthesis mark
+        let grain = ((mark / period as f32).round() as usize * period).min(last_grain);
+        let at = mark as usize;
+        for n in 0..window.min(input.len() - grain) {
+            output[at + n] += input[grain + n] * hann[n];
+            weights[at + n] += hann[n];
+        }
+        mark += hop;
+    }
+
+    output.truncate(input.len());
+    for (frame, weight) in output.iter_mut().zip(weights) {
+        if weight > 1e-3 {
+            *frame /= weight;
+        }
+    }
+    output
+}
+
+/// Supported range for a filter cutoff, in Hz
+pub(crate) const MIN_CUTOFF_HZ: f32 = 20.0;
+pub(crate) const MAX_CUTOFF_HZ: f32 = 20_000.0;
+
+/// Feedback coefficient of a one-pole low-pass at `cutoff_hz`
+///
+/// `a = e^(-2π fc / fs)`: near 1.0 for a low cutoff (heavy smoothing), near
+/// 0.0 as the cutoff approaches the sample rate.
+pub(crate) fn one_pole_coefficient(cutoff_hz: f32, sample_rate: u32) -> f32 {
+    let nyquist = sample_rate as f32 / 2.0;
+    let cutoff = cutoff_hz.clamp(1.0, nyquist.max(1.0));
+    (-std::f32::consts::TAU * cutoff / sample_rate as f32).exp()
+}
+
+/// One-pole low-pass: `y[n] = (1 - a) x[n] + a y[n-1]`
+///
+/// Rolls off at 6 dB per octave above the cutoff and passes DC unchanged.
+/// Gentle, but plenty to muffle a voice.
+pub(crate) fn lowpass(input: &[Frame], sample_rate: u32, cutoff_hz: f32) -> Vec<Frame> {
+    let a = one_pole_coefficient(cutoff_hz, sample_rate);
+    let mut y = Frame::ZERO;
+    input
+        .iter()
+        .map(|x| {
+            y = *x * (1.0 - a) + y * a;
+            y
+        })
+        .collect()
+}
+
+/// One-pole high-pass: the input minus its low-pass
+///
+/// The complement of [`lowpass`], so it removes DC entirely.
+pub(crate) fn highpass(input: &[Frame], sample_rate: u32, cutoff_hz: f32) -> Vec<Frame> {
+    input
+        .iter()
+        .zip(lowpass(input, sample_rate, cutoff_hz))
+        .map(|(x, low)| *x - low)
+        .collect()
+}
+
+/// Similarity of two windows of a signal (mono, every 4th sample)
+fn correlation(input: &[Frame], a: usize, b: usize, len: usize) -> f32 {
+    (0..len)
+        .step_by(4)
+        .map(|n| (input[a + n].left + input[a + n].right) * (input[b + n].left + input[b + n].right))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, sample_rate: u32, len: usize) -> Vec<Frame> {
+        (0..len)
+            .map(|n| Frame::from_mono((std::f32::consts::TAU * freq * n as f32 / sample_rate as f32).sin()))
+            .collect()
+    }
+
+    /// Rising zero crossings per second
+    fn crossing_rate(frames: &[Frame], sample_rate: u32) -> f32 {
+        let crossings = frames.windows(2).filter(|w| w[0].left < 0.0 && w[1].left >= 0.0).count();
+        crossings as f32 * sample_rate as f32 / frames.len() as f32
+    }
+
+    /// Peak level after the first 10ms of settling
+    fn settled_peak(frames: &[Frame]) -> f32 {
+        frames[441..].iter().fold(0.0, |peak, frame| peak.max(frame.left.abs()))
+    }
+
+    #[test]
+    fn test_one_pole_coefficient() {
+        let a = one_pole_coefficient(1000.0, 44_100);
+        assert!((a - (-std::f32::consts::TAU * 1000.0 / 44_100.0).exp()).abs() < 1e-6);
+        assert!((a - 0.8673).abs() < 1e-3);
+
+        // Lower cutoffs smooth more; cutoffs past Nyquist are limited to it
+        assert!(one_pole_coefficient(100.0, 44_100) > a);
+        assert_eq!(one_pole_coefficient(50_000.0, 44_100), one_pole_coefficient(22_050.0, 44_100));
+    }
+
+    #[test]
+    fn test_filters_dc() {
+        let dc = vec![Frame::from_mono(0.5); 4410];
+
+        let low = lowpass(&dc, 44_100, 500.0);
+        assert!((low.last().unwrap().left - 0.5).abs() < 1e-4, "low-pass keeps DC");
+
+        let high = highpass(&dc, 44_100, 500.0);
+        assert!(high.last().unwrap().left.abs() < 1e-4, "high-pass removes DC");
+    }
+
+    #[test]
+    fn test_filters_frequency_response() {
+        let low_tone = sine(100.0, 44_100, 4410);
+        let high_tone = sine(8000.0, 44_100, 4410);
+
+        // Well inside the passband, close to unity
+        assert!(settled_peak(&lowpass(&low_tone, 44_100, 1000.0)) > 0.95);
+        assert!(settled_peak(&highpass(&high_tone, 44_100, 1000.0)) > 0.9);
+
+        // Three octaves into the stopband, at least 15 dB down
+        assert!(settled_peak(&lowpass(&high_tone, 44_100, 1000.0)) < 0.18);
+        assert!(settled_peak(&highpass(&low_tone, 44_100, 1000.0)) < 0.18);
+    }
+
+    #[test]
+    fn test_time_stretch_length() {
+        let input = sine(220.0, 44_100, 8820);
+        assert_eq!(time_stretch(&input, 44_100, 0.6).len(), 5292);
+        assert_eq!(time_stretch(&input, 44_100, 2.0).len(), 17_640);
+        assert_eq!(time_stretch(&input, 44_100, 1.0), input);
+
+        // Too short to window, left alone
+        let short = sine(220.0, 44_100, 100);
+        assert_eq!(time_stretch(&short, 44_100, 0.5), short);
+    }
+
+    #[test]
+    fn test_estimate_period() {
+        assert_eq!(estimate_period(&sine(220.0, 44_100, 8820), 44_100), Some(200));
+        assert_eq!(estimate_period(&vec![Frame::ZERO; 8820], 44_100), None);
+        assert_eq!(estimate_period(&sine(220.0, 44_100, 500), 44_100), None);
+    }
+
+    #[test]
+    fn test_pitch_shift_formants() {
+        // A 220 Hz pulse train through a 2 kHz resonance, like a vowel
+        let period = 200;
+        let vowel: Vec<Frame> = (0..8820)
+            .map(|n| {
+                let t = (n % period) as f32 / 44_100.0;
+                Frame::from_mono((std::f32::consts::TAU * 2000.0 * t).sin() * (-t * 800.0).exp())
+            })
+            .collect();
+
+        let shifted = pitch_shift_formants(&vowel, 44_100, 1.5);
+        assert_eq!(shifted.len(), vowel.len());
+        let period = estimate_period(&shifted, 44_100).unwrap() as f32;
+        assert!((44_100.0 / period - 330.0).abs() < 20.0, "shifted to {} Hz", 44_100.0 / period);
+        // The resonance rings at the same rate, where resampling would move it
+        let ring = crossing_rate(&shifted[..4410], 44_100);
+        assert!((ring - crossing_rate(&vowel[..4410], 44_100)).abs() < 300.0, "resonance moved to {ring} Hz");
+
+        assert_eq!(pitch_shift_formants(&vowel, 44_100, 1.0), vowel);
+        let silence = vec![Frame::ZERO; 8820];
+        assert_eq!(pitch_shift_formants(&silence, 44_100, 1.5), silence);
+    }
+
+    #[test]
+    fn test_time_stretch_preserves_pitch() {
+        let input = sine(220.0, 44_100, 8820);
+        for factor in [0.6, 1.5] {
+            let output = time_stretch(&input, 44_100, factor);
+            let rate = crossing_rate(&output, 44_100);
+            assert!((rate - 220.0).abs() < 15.0, "factor {factor} gave {rate} Hz");
+        }
+    }
+}
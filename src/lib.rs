@@ -26,17 +26,57 @@
 //! engine.speak_excited("Amazing!")?;
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
+//!
+//! ## Logging
+//!
+//! Enable the `log` feature to have the engine emit events through the
+//! [`log`](https://docs.rs/log) crate. Filter by target:
+//!
+//! - `animalese::speech`: utterance start and end (info/debug), each sprite
+//!   queued and lookahead waits (trace)
+//! - `animalese::decode`: audio files that failed to decode (warn)
+//! - `animalese::playback`: `stop()` and keepalive changes (debug)
+//!
+//! Without the feature there are no events and no runtime cost.
 
+#[macro_use]
+mod logging;
+mod capture;
+mod device;
+mod dsp;
+mod manifest;
+mod markup;
+mod profile_file;
+mod sfx_bank;
+mod stream;
+mod timeline;
+
+pub use capture::Capture;
+pub use device::{DeviceInfo, list_output_devices};
+pub use sfx_bank::SpriteLayout;
+pub use stream::{SampleStream, StreamBackend, StreamBackendSettings};
+
+use capture::CaptureState;
 use kira::{
-    manager::{AudioManager, AudioManagerSettings, backend::DefaultBackend},
-    sound::static_sound::{StaticSoundData, StaticSoundHandle},
+    manager::{AudioManager, AudioManagerSettings, backend::{Backend, DefaultBackend, cpal::CpalBackendSettings}},
+    sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings},
+    track::{TrackBuilder, TrackHandle},
+    Frame,
+    StartTime,
     tween::Tween,
     Volume,
 };
+use markup::{MarkupItem, SpanStyle};
+use sfx_bank::SfxBank;
+use ringbuf::HeapConsumer;
+use std::collections::HashMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use rand::Rng;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 /// Returns the path to bundled voice assets
 ///
@@ -49,36 +89,438 @@ pub fn bundled_assets_path() -> PathBuf {
         .join("voice")
 }
 
+/// The bundled pack's manifest, compiled into the crate
+fn bundled_manifest() -> Result<manifest::Manifest, Box<dyn std::error::Error>> {
+    let base = bundled_assets_path().parent().ok_or("Invalid assets path")?.to_path_buf();
+    Ok(manifest::Manifest::parse(manifest::BUNDLED_MANIFEST, &base)?)
+}
+
+/// Errors reported by the engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnimaleseError {
+    /// A required asset file or directory doesn't exist
+    AssetNotFound(PathBuf),
+    /// No output device has the requested name
+    DeviceNotFound(String),
+    /// A profile file couldn't be parsed
+    InvalidProfile {
+        /// 1-based line number, or 0 if the problem isn't on one line
+        line: usize,
+        /// What was wrong
+        message: String,
+    },
+    /// A voice pack manifest couldn't be parsed (see [`Animalese::from_manifest`])
+    InvalidManifest {
+        /// 1-based line number, or 0 if the problem isn't on one line
+        line: usize,
+        /// What was wrong
+        message: String,
+    },
+    /// A sprite starts past the end of its audio file (see [`SpriteOverflow`])
+    SpriteOutOfRange {
+        /// Where the sprite was meant to start
+        start: Duration,
+        /// Length of the decoded file
+        file_length: Duration,
+    },
+}
+
+impl fmt::Display for AnimaleseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimaleseError::AssetNotFound(path) => write!(f, "Asset not found: {}", path.display()),
+            AnimaleseError::DeviceNotFound(name) => {
+                write!(f, "Output device not found: {} (see list_output_devices())", name)
+            }
+            AnimaleseError::InvalidProfile { line: 0, message } => write!(f, "Invalid profile: {}", message),
+            AnimaleseError::InvalidProfile { line, message } => write!(f, "Invalid profile (line {}): {}", line, message),
+            AnimaleseError::InvalidManifest { line: 0, message } => write!(f, "Invalid manifest: {}", message),
+            AnimaleseError::InvalidManifest { line, message } => write!(f, "Invalid manifest (line {}): {}", line, message),
+            AnimaleseError::SpriteOutOfRange { start, file_length } => write!(
+                f,
+                "Sprite at {:?} is past the end of the {:?} audio file; is the voice pack truncated?",
+                start, file_length
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AnimaleseError {}
+
 /// Voice types available
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum VoiceType {
     F1, F2, F3, F4,
     M1, M2, M3, M4,
 }
 
 impl VoiceType {
-    fn filename(&self) -> &'static str {
+    /// Every voice type, in order
+    pub const ALL: [VoiceType; 8] = [
+        VoiceType::F1, VoiceType::F2, VoiceType::F3, VoiceType::F4,
+        VoiceType::M1, VoiceType::M2, VoiceType::M3, VoiceType::M4,
+    ];
+
+    /// Pick a voice type at random
+    ///
+    /// Pass a seeded RNG such as `rand::rngs::StdRng` for a repeatable pick.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> VoiceType {
+        Self::ALL[rng.gen_range(0..Self::ALL.len())]
+    }
+
+    /// Candidate filenames for this voice, in priority order
+    ///
+    /// Voice packs may ship any of these formats, and can mix formats
+    /// between voices.
+    pub fn filenames(&self) -> &'static [&'static str] {
+        match self {
+            VoiceType::F1 => &["f1.ogg", "f1.wav", "f1.mp3", "f1.flac"],
+            VoiceType::F2 => &["f2.ogg", "f2.wav", "f2.mp3", "f2.flac"],
+            VoiceType::F3 => &["f3.ogg", "f3.wav", "f3.mp3", "f3.flac"],
+            VoiceType::F4 => &["f4.ogg", "f4.wav", "f4.mp3", "f4.flac"],
+            VoiceType::M1 => &["m1.ogg", "m1.wav", "m1.mp3", "m1.flac"],
+            VoiceType::M2 => &["m2.ogg", "m2.wav", "m2.mp3", "m2.flac"],
+            VoiceType::M3 => &["m3.ogg", "m3.wav", "m3.mp3", "m3.flac"],
+            VoiceType::M4 => &["m4.ogg", "m4.wav", "m4.mp3", "m4.flac"],
+        }
+    }
+
+    /// Built-in pitch variation for this voice, in semitones
+    ///
+    /// Used instead of `VoiceProfile::pitch_variation` when
+    /// `VoiceProfile::use_voice_defaults` is on, to give each voice a bit of
+    /// personality: f2 is the bounciest and m3 the steadiest.
+    ///
+    /// | Voice | f1  | f2  | f3  | f4  | m1  | m2  | m3  | m4  |
+    /// |-------|-----|-----|-----|-----|-----|-----|-----|-----|
+    /// | Variation | 0.8 | 1.1 | 0.9 | 0.7 | 0.8 | 0.6 | 0.4 | 0.7 |
+    pub fn default_pitch_variation(&self) -> f32 {
+        match self {
+            VoiceType::F1 => 0.8,
+            VoiceType::F2 => 1.1,
+            VoiceType::F3 => 0.9,
+            VoiceType::F4 => 0.7,
+            VoiceType::M1 => 0.8,
+            VoiceType::M2 => 0.6,
+            VoiceType::M3 => 0.4,
+            VoiceType::M4 => 0.7,
+        }
+    }
+
+    /// Typical fundamental pitch of this voice's bundled recordings, in Hz
+    ///
+    /// Measured from the bundled assets: the median over the 26 letter
+    /// sprites of each sprite's fundamental, estimated by normalized
+    /// autocorrelation of the middle 100ms (40ms to 140ms in) searching
+    /// 60–1000Hz. Letters within a voice agree to a few percent. Custom packs
+    /// will differ.
+    ///
+    /// Useful for pitch-matching voices: the `pitch_shift` that brings a
+    /// voice to a target frequency is `12.0 * (target / reference).log2()`.
+    ///
+    /// # Example
+    /// ```
+    /// use animalese::VoiceType;
+    ///
+    /// // Shift f2 down to sound at m3's pitch
+    /// let shift = 12.0 * (VoiceType::M3.reference_pitch_hz() / VoiceType::F2.reference_pitch_hz()).log2();
+    /// assert!(shift < -7.0 && shift > -8.0);
+    /// ```
+    pub fn reference_pitch_hz(&self) -> f32 {
         match self {
-            VoiceType::F1 => "f1.ogg",
-            VoiceType::F2 => "f2.ogg",
-            VoiceType::F3 => "f3.ogg",
-            VoiceType::F4 => "f4.ogg",
-            VoiceType::M1 => "m1.ogg",
-            VoiceType::M2 => "m2.ogg",
-            VoiceType::M3 => "m3.ogg",
-            VoiceType::M4 => "m4.ogg",
+            VoiceType::F1 => 397.0,
+            VoiceType::F2 => 485.0,
+            VoiceType::F3 => 345.0,
+            VoiceType::F4 => 333.0,
+            VoiceType::M1 => 381.0,
+            VoiceType::M2 => 350.0,
+            VoiceType::M3 => 308.0,
+            VoiceType::M4 => 100.0,
+        }
+    }
+
+    /// Short name of the voice, e.g. "f1"
+    pub fn name(&self) -> &'static str {
+        match self {
+            VoiceType::F1 => "f1",
+            VoiceType::F2 => "f2",
+            VoiceType::F3 => "f3",
+            VoiceType::F4 => "f4",
+            VoiceType::M1 => "m1",
+            VoiceType::M2 => "m2",
+            VoiceType::M3 => "m3",
+            VoiceType::M4 => "m4",
         }
     }
 }
 
-/// Voice profile configuration
+impl std::str::FromStr for VoiceType {
+    type Err = String;
+
+    /// Parse a short voice name like "f1" or "M3" (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "f1" => Ok(VoiceType::F1),
+            "f2" => Ok(VoiceType::F2),
+            "f3" => Ok(VoiceType::F3),
+            "f4" => Ok(VoiceType::F4),
+            "m1" => Ok(VoiceType::M1),
+            "m2" => Ok(VoiceType::M2),
+            "m3" => Ok(VoiceType::M3),
+            "m4" => Ok(VoiceType::M4),
+            _ => Err(format!("Invalid voice type: {}", s)),
+        }
+    }
+}
+
+/// Existing files for a voice in a voice directory, in priority order
+fn voice_files(voice_dir: &Path, voice_type: VoiceType) -> Vec<PathBuf> {
+    voice_type.filenames()
+        .iter()
+        .map(|name| voice_dir.join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Where a voice pack's sheets are
 #[derive(Debug, Clone)]
+struct AssetPack {
+    voice_path: String,
+    sfx_path: String,
+    /// Files named by a manifest, used instead of a voice's built-in names
+    voice_file_names: HashMap<VoiceType, String>,
+    /// Per-voice trims in decibels for a pack from [`AssetPack::load`]
+    ///
+    /// `None` for the engine's own pack, whose trims are the engine's
+    /// `set_voice_trim()` settings.
+    voice_trims: Option<HashMap<VoiceType, f32>>,
+}
+
+impl AssetPack {
+    /// A voice directory, with `sfx.ogg` in its parent
+    ///
+    /// Returns [`AnimaleseError::AssetNotFound`] if either is missing.
+    fn open(voice_path: String) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(&voice_path).is_dir() {
+            return Err(AnimaleseError::AssetNotFound(PathBuf::from(&voice_path)).into());
+        }
+
+        // SFX file is in parent directory of voice
+        let sfx_file = Path::new(&voice_path)
+            .parent()
+            .ok_or("Invalid assets path")?
+            .join("sfx.ogg");
+        if !sfx_file.is_file() {
+            return Err(AnimaleseError::AssetNotFound(sfx_file).into());
+        }
+        let sfx_path = sfx_file.to_string_lossy().to_string();
+        Ok(AssetPack { voice_path, sfx_path, voice_file_names: HashMap::new(), voice_trims: None })
+    }
+
+    /// A voice directory, or the pack a `voices.toml` manifest describes
+    ///
+    /// The pack carries its own trims, from the manifest or none at all, so
+    /// the engine's don't apply to it.
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.is_file() {
+            let pack = Self::open(path.to_string_lossy().to_string())?;
+            return Ok(AssetPack { voice_trims: Some(HashMap::new()), ..pack });
+        }
+        let toml = std::fs::read_to_string(path)?;
+        let manifest = manifest::Manifest::parse(&toml, path.parent().unwrap_or(Path::new("")))?;
+        let mut voice_file_names = HashMap::new();
+        let mut voice_trims = HashMap::new();
+        for (voice, entry) in manifest.voices {
+            if let Some(file) = entry.file {
+                voice_file_names.insert(voice, file);
+            }
+            voice_trims.insert(voice, entry.trim_db.clamp(MIN_VOICE_TRIM_DB, MAX_VOICE_TRIM_DB));
+        }
+        let pack = Self::open(manifest.voice_dir.to_string_lossy().to_string())?;
+        Ok(AssetPack { voice_file_names, voice_trims: Some(voice_trims), ..pack })
+    }
+
+    /// Existing files for a voice, honouring the manifest's file names
+    fn voice_files(&self, voice: VoiceType) -> Vec<PathBuf> {
+        match self.voice_file_names.get(&voice) {
+            Some(_) => Some(self.voice_file(voice)).filter(|path| path.is_file()).into_iter().collect(),
+            None => voice_files(Path::new(&self.voice_path), voice),
+        }
+    }
+
+    /// The file a voice is looked for in first
+    fn voice_file(&self, voice: VoiceType) -> PathBuf {
+        let name = self.voice_file_names.get(&voice).map_or(voice.filenames()[0], String::as_str);
+        Path::new(&self.voice_path).join(name)
+    }
+}
+
+/// The sheet a sprite is cut from
+#[derive(Debug, Clone, Copy)]
+enum Sheet<'a> {
+    /// The profile voice's sheet in a pack
+    Voice(&'a AssetPack),
+    /// A sound effect sheet at a path
+    Sfx(&'a str),
+}
+
+/// A problem with one file of a voice pack, from [`Animalese::validate_assets`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetIssue {
+    /// The file with the problem (or where it was expected)
+    pub path: PathBuf,
+    /// What's wrong with it
+    pub problem: AssetProblem,
+}
+
+/// What's wrong with a voice pack file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetProblem {
+    /// No file in any supported format
+    Missing,
+    /// The file exists but couldn't be decoded; holds the decoder's error
+    Undecodable(String),
+    /// The file decodes but ends before its last sprite does
+    TooShort {
+        /// Length of the decoded file
+        length: Duration,
+        /// Length needed to hold every sprite
+        needed: Duration,
+    },
+}
+
+impl fmt::Display for AssetIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.problem {
+            AssetProblem::Missing => write!(f, "{}: missing", path),
+            AssetProblem::Undecodable(error) => write!(f, "{}: can't be decoded: {}", path, error),
+            AssetProblem::TooShort { length, needed } => {
+                write!(f, "{}: too short ({:?}, needs {:?})", path, length, needed)
+            }
+        }
+    }
+}
+
+/// Results of [`Animalese::self_test`], for pasting into bug reports
+///
+/// Its `Display` prints one line per check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Type name of the kira backend the engine plays through
+    pub backend: String,
+    /// Problems found by [`Animalese::validate_assets`]; empty if none
+    pub asset_issues: Vec<AssetIssue>,
+    /// Output devices on the default audio host, from [`list_output_devices`]
+    ///
+    /// Listed whatever the backend, so an engine on a [`StreamBackend`]
+    /// reports the machine's devices even though it doesn't use them. Empty
+    /// if the host has none or can't enumerate them.
+    pub output_devices: Vec<DeviceInfo>,
+    /// The mixer's sample rate, or `None` if it hasn't reported one yet
+    ///
+    /// A [`StreamBackend`] only reports it once its stream has been pulled.
+    pub sample_rate: Option<u32>,
+    /// Whether a short silent test tone could be started, or why not
+    pub test_tone: Result<(), String>,
+}
+
+impl SelfTestReport {
+    /// Whether the assets are sound and the test tone started
+    ///
+    /// A missing output device doesn't count against this, since engines on
+    /// a [`StreamBackend`] don't need one.
+    pub fn is_ok(&self) -> bool {
+        self.asset_issues.is_empty() && self.test_tone.is_ok()
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "animalese {} on {}", env!("CARGO_PKG_VERSION"), self.backend)?;
+        if self.asset_issues.is_empty() {
+            writeln!(f, "assets: ok")?;
+        }
+        for issue in &self.asset_issues {
+            writeln!(f, "assets: {}", issue)?;
+        }
+        let default = self.output_devices.iter().find(|device| device.is_default);
+        match default {
+            Some(device) => writeln!(f, "output devices: {} (default: {})", self.output_devices.len(), device.name)?,
+            None => writeln!(f, "output devices: {} (no default)", self.output_devices.len())?,
+        }
+        match self.sample_rate {
+            Some(rate) => writeln!(f, "sample rate: {} Hz", rate)?,
+            None => writeln!(f, "sample rate: not reported yet")?,
+        }
+        match &self.test_tone {
+            Ok(()) => write!(f, "test tone: ok"),
+            Err(error) => write!(f, "test tone: failed: {}", error),
+        }
+    }
+}
+
+/// Length of the silent tone played by [`Animalese::self_test`]
+const SELF_TEST_TONE: Duration = Duration::from_millis(50);
+
+/// Length a voice file needs to hold every letter and special sprite
+fn voice_sheet_length() -> Duration {
+    let letters = LETTER_SLOT * 26;
+    let specials = SPECIAL_MAP.iter().map(|(_, start)| *start + SFX_SLOT).max();
+    specials.map_or(letters, |end| end.max(letters))
+}
+
+/// Decode a file and check it's at least `needed` long
+fn check_asset(path: PathBuf, needed: Duration) -> Option<AssetIssue> {
+    let problem = match StaticSoundData::from_file(&path) {
+        Err(e) => AssetProblem::Undecodable(e.to_string()),
+        Ok(data) if data.duration() < needed => AssetProblem::TooShort { length: data.duration(), needed },
+        Ok(_) => return None,
+    };
+    Some(AssetIssue { path, problem })
+}
+
+/// Voice profile configuration
+#[derive(Debug, Clone, PartialEq)]
 pub struct VoiceProfile {
     pub voice_type: VoiceType,
     pub pitch_shift: f32,      // Fixed pitch shift in semitones
     pub pitch_variation: f32,  // Random variation range in semitones
     pub volume: f32,           // Volume multiplier (0.0 to 1.0)
     pub intonation: f32,       // Pitch glide over sentence: -1.0 (falling) to 1.0 (rising)
+    pub tempo: f32,            // Delivery speed multiplier, independent of pitch (0.25 to 4.0)
+    pub sprite_trim_start: Duration, // Leading silence to skip in each letter sprite
+    pub time_stretch: f32,     // Sprite length multiplier at the same pitch (1.0 = off, 0.25 to 4.0)
+    pub acronym_handling: bool, // Spell out unpronounceable acronyms like "FBI" letter by letter
+    pub auto_question_intonation: Option<f32>, // Glide for a trailing '?' when intonation is 0.0 (None = off)
+    pub emoji_sfx: HashMap<char, String>, // SFX to play for emoji in speak(), e.g. '❓' => "question" or "ui:bell"
+    pub max_word_length: usize, // Letters voiced per unbroken run; the rest are skipped (0 = no limit)
+    pub letter_gap: Duration,  // Time from one letter starting to the next, before tempo scaling
+    pub word_pause: Duration,  // Extra pause for each space, on top of letter_gap (ZERO = none)
+    pub line_pause: Duration,  // Extra pause for each line break (ZERO = none)
+    pub reverse_sprites: bool, // Play each voice sprite backwards
+    pub sprite_overflow: SpriteOverflow, // What to do when a sprite is past the end of a short voice file
+    pub speed_read: bool,      // Abbreviate long words for quick blips (lossy, see `speak()`)
+    pub use_voice_defaults: bool, // Use the voice's built-in pitch variation instead of `pitch_variation`
+    pub question_shape: QuestionShape, // Pitch curve for speak_question() and the automatic '?' rise
+    pub unmapped: UnmappedPolicy, // What speak() plays for digits and symbols with no sprite
+    pub intonation_range_semitones: f32, // Glide reached at intonation ±1.0 (default 3.0, up to 12.0)
+    pub filter: Option<FilterSettings>, // Low- or high-pass on every sprite, e.g. muffled or tinny (None = off)
+    pub auto_terminal_intonation: bool, // Also glide for a trailing '!' (rise) or '.' (fall) when intonation is 0.0
+    pub intonation_basis: IntonationBasis, // Whether the glide follows letter count or time, pauses included
+    pub volume_variation: f32, // Random per-letter volume change, as a fraction of volume (0.0 to 1.0)
+    pub markdown_emphasis: bool, // Speak *word* with mild and **word** with strong emphasis, asterisks silent
+    pub declination: f32,      // Semitones the pitch drifts down over the whole text, on top of intonation (0.0 to 12.0)
+    pub min_retrigger_gap: Duration, // play_letter() drops a repeat of the same letter this soon after it (ZERO = off)
+    pub pan: f32,              // Stereo position: -1.0 (left) to 1.0 (right)
+    pub pitch_floor: f32,      // Lowest final pitch in semitones after every modifier (default -18.0)
+    pub pitch_ceiling: f32,    // Highest final pitch in semitones after every modifier (default 18.0)
+    pub fade_in: Duration,     // Fade at the start of every sprite against clicks (default 5ms, ZERO = none)
+    pub adaptive_cadence: bool, // Shorten letter_gap for long texts, down to 40% of it at 500 letters
+    pub breath_sfx: Option<String>, // Built-in SFX speak_varied() plays quietly between sentences (None = off)
+    pub voice_change_fade: Duration, // Crossfade where speech switches voice mid-utterance (ZERO = hard switch)
+    pub detect_question_words: bool, // Rise like a '?' for text opening with "what", "is" etc. (heuristic, see speak())
+    pub preserve_formants: bool, // Pitch voice sprites with approximate PSOLA instead of speeding them up (less chipmunk)
 }
 
 impl Default for VoiceProfile {
@@ -89,441 +531,6503 @@ impl Default for VoiceProfile {
             pitch_variation: 0.8,
             volume: 0.65,
             intonation: 0.0,
+            tempo: 1.0,
+            sprite_trim_start: Duration::ZERO,
+            time_stretch: 1.0,
+            acronym_handling: false,
+            auto_question_intonation: Some(0.5),
+            emoji_sfx: HashMap::new(),
+            max_word_length: DEFAULT_MAX_WORD_LENGTH,
+            letter_gap: Duration::from_millis(50),
+            word_pause: Duration::ZERO,
+            line_pause: Duration::ZERO,
+            reverse_sprites: false,
+            sprite_overflow: SpriteOverflow::Error,
+            speed_read: false,
+            use_voice_defaults: false,
+            question_shape: QuestionShape::Rising,
+            unmapped: UnmappedPolicy::Skip,
+            intonation_range_semitones: DEFAULT_INTONATION_RANGE,
+            filter: None,
+            auto_terminal_intonation: false,
+            intonation_basis: IntonationBasis::LetterCount,
+            volume_variation: 0.0,
+            markdown_emphasis: false,
+            declination: 0.0,
+            min_retrigger_gap: Duration::ZERO,
+            pan: 0.0,
+            pitch_floor: DEFAULT_PITCH_FLOOR,
+            pitch_ceiling: DEFAULT_PITCH_CEILING,
+            fade_in: DEFAULT_FADE_IN,
+            adaptive_cadence: false,
+            breath_sfx: None,
+            voice_change_fade: Duration::ZERO,
+            detect_question_words: false,
+            preserve_formants: false,
         }
     }
 }
 
-/// Maps letters to their sprite positions in the audio file
-/// Each letter gets 200ms starting at letter_index * 200ms
-fn letter_to_sprite_time(c: char) -> Option<Duration> {
-    let c = c.to_ascii_lowercase();
-    if !c.is_ascii_lowercase() {
-        return None;
+impl VoiceProfile {
+    /// Copy of this profile with every field pulled into its supported range
+    ///
+    /// Pitch shift is limited to ±12 semitones, variation to 0.0..=2.0,
+    /// volume and volume variation to 0.0..=1.0, intonation and pan to -1.0..=1.0, intonation range
+    /// and declination to 0.0..=12.0 semitones, tempo and time stretch to 0.25..=4.0, and a
+    /// filter cutoff to 20..=20000 Hz. NaN falls back to the default (no
+    /// filtering for a cutoff, off for the question glide).
+    pub fn clamped(&self) -> Self {
+        let default = Self::default();
+        Self {
+            pitch_shift: clamp_finite(self.pitch_shift, -MAX_PITCH_SHIFT, MAX_PITCH_SHIFT, default.pitch_shift),
+            pitch_variation: clamp_finite(self.pitch_variation, 0.0, MAX_PITCH_VARIATION, default.pitch_variation),
+            volume: clamp_finite(self.volume, 0.0, 1.0, default.volume),
+            volume_variation: clamp_finite(self.volume_variation, 0.0, 1.0, default.volume_variation),
+            intonation: clamp_finite(self.intonation, -1.0, 1.0, default.intonation),
+            intonation_range_semitones: clamp_finite(
+                self.intonation_range_semitones, 0.0, MAX_INTONATION_RANGE, default.intonation_range_semitones,
+            ),
+            declination: clamp_finite(self.declination, 0.0, MAX_DECLINATION, default.declination),
+            pan: clamp_finite(self.pan, -1.0, 1.0, default.pan),
+            tempo: clamp_finite(self.tempo, MIN_TEMPO, MAX_TEMPO, default.tempo),
+            time_stretch: clamp_finite(
+                self.time_stretch, dsp::MIN_TIME_STRETCH, dsp::MAX_TIME_STRETCH, default.time_stretch,
+            ),
+            auto_question_intonation: self.auto_question_intonation
+                .filter(|glide| !glide.is_nan())
+                .map(|glide| glide.clamp(-1.0, 1.0)),
+            filter: self.filter.map(|filter| FilterSettings {
+                cutoff_hz: clamp_finite(filter.cutoff_hz, dsp::MIN_CUTOFF_HZ, dsp::MAX_CUTOFF_HZ, dsp::MAX_CUTOFF_HZ),
+                ..filter
+            }),
+            ..self.clone()
+        }
     }
 
-    let index = (c as u32 - 'a' as u32) as u64;
-    Some(Duration::from_millis(index * 200))
+    /// Pitch variation sprites are played with
+    ///
+    /// The voice's [`VoiceType::default_pitch_variation`] when
+    /// `use_voice_defaults` is on, otherwise `pitch_variation`.
+    pub fn effective_pitch_variation(&self) -> f32 {
+        if self.use_voice_defaults {
+            self.voice_type.default_pitch_variation()
+        } else {
+            self.pitch_variation
+        }
+    }
 }
 
-/// Special sprite times for non-letter sounds
-fn special_to_sprite_time(name: &str) -> Option<Duration> {
-    match name {
-        "ok" => Some(Duration::from_millis(5200)),
-        "gwah" => Some(Duration::from_millis(5800)),
-        "deska" => Some(Duration::from_millis(6400)),
-        _ => None,
-    }
+/// `value` clamped to `min..=max`, or `fallback` if it's NaN
+fn clamp_finite(value: f32, min: f32, max: f32, fallback: f32) -> f32 {
+    if value.is_nan() { fallback } else { value.clamp(min, max) }
 }
 
-/// SFX sprite times (600ms each)
-fn sfx_to_sprite_time(name: &str) -> Option<Duration> {
-    let index = match name {
-        "backspace" => 0,
-        "enter" => 1,
-        "tab" => 2,
-        "question" => 3,
-        "exclamation" => 4,
-        "at" => 5,
-        "pound" => 6,
-        "dollar" => 7,
-        "caret" => 8,
-        "ampersand" => 9,
-        "asterisk" => 10,
-        "parenthesis_open" => 11,
-        "parenthesis_closed" => 12,
-        "bracket_open" => 13,
-        "bracket_closed" => 14,
-        "brace_open" => 15,
-        "brace_closed" => 16,
-        "tilde" => 17,
-        "default" => 18,
-        "arrow_left" => 19,
-        "arrow_up" => 20,
-        "arrow_right" => 21,
-        "arrow_down" => 22,
-        "slash_forward" => 23,
-        "slash_back" => 24,
-        "percent" => 25,
-        _ => return None,
-    };
-    Some(Duration::from_millis(index * 600))
+/// Quietest effective volume `is_audible()` counts as heard, -60 dB
+const AUDIBLE_VOLUME: f32 = 0.001;
+
+/// Quietest trim `set_voice_trim()` accepts, in decibels
+const MIN_VOICE_TRIM_DB: f32 = -60.0;
+
+/// Loudest trim `set_voice_trim()` accepts, in decibels
+const MAX_VOICE_TRIM_DB: f32 = 12.0;
+
+/// A volume with a trim in decibels applied
+fn trimmed_volume(volume: f32, trim_db: f32) -> f32 {
+    volume * 10.0_f32.powf(trim_db / 20.0)
 }
 
-/// Calculate playback rate from pitch shift in semitones
-/// rate = 2^(semitones / 12)
-fn semitones_to_rate(semitones: f32) -> f32 {
-    2.0_f32.powf(semitones / 12.0)
+/// Supported range for `VoiceProfile::pitch_shift`, in semitones either way
+const MAX_PITCH_SHIFT: f32 = 12.0;
+
+/// Supported maximum for `VoiceProfile::pitch_variation`, in semitones
+const MAX_PITCH_VARIATION: f32 = 2.0;
+
+/// Default cap on letters voiced in a row, against pathological input
+const DEFAULT_MAX_WORD_LENGTH: usize = 200;
+
+/// What to do when a sprite starts past the end of its audio file
+///
+/// Happens with truncated or custom voice packs that don't cover the whole
+/// alphabet, where late letters like 'z' (at 5000ms) would otherwise be
+/// silent with no explanation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpriteOverflow {
+    /// Fail with [`AnimaleseError::SpriteOutOfRange`]
+    #[default]
+    Error,
+    /// Play the last sprite-length stretch of the file instead
+    Clamp,
+    /// Wrap around to the start of the file, so a pack with 10 letters'
+    /// worth of 200ms slots reuses them for the rest of the alphabet
+    Wrap,
 }
 
-/// Animalese sound engine with kira-based playback
-pub struct Animalese {
-    manager: Arc<Mutex<AudioManager>>,
-    voice_path: String,
-    sfx_path: String,
-    profile: Arc<Mutex<VoiceProfile>>,
-    active_sounds: Arc<Mutex<Vec<StaticSoundHandle>>>,
+/// Where a sprite should start in a file of `file_length`
+///
+/// Sprites that start inside the file are left alone, even if they run past
+/// the end (the tail is just shorter).
+fn resolve_sprite_start(start: Duration, length: Duration, file_length: Duration, overflow: SpriteOverflow) -> Result<Duration, AnimaleseError> {
+    if start < file_length {
+        return Ok(start);
+    }
+    match overflow {
+        SpriteOverflow::Error => Err(AnimaleseError::SpriteOutOfRange { start, file_length }),
+        SpriteOverflow::Clamp => Ok(file_length.saturating_sub(length)),
+        SpriteOverflow::Wrap if file_length.is_zero() => Ok(Duration::ZERO),
+        SpriteOverflow::Wrap => Ok(Duration::from_nanos((start.as_nanos() % file_length.as_nanos()) as u64)),
+    }
 }
 
-impl Animalese {
-    /// Create a new Animalese engine with bundled assets
-    ///
-    /// # Example
-    /// ```no_run
-    /// use animalese::Animalese;
-    ///
-    /// let engine = Animalese::new().unwrap();
-    /// engine.speak("hello world").unwrap();
-    /// ```
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        Self::with_custom_assets(bundled_assets_path().to_string_lossy().to_string())
+/// Default for `VoiceProfile::intonation_range_semitones`: the glide in
+/// semitones reached by the end of an utterance at intonation 1.0
+const DEFAULT_INTONATION_RANGE: f32 = 3.0;
+
+/// Default for `VoiceProfile::fade_in`
+const DEFAULT_FADE_IN: Duration = Duration::from_millis(5);
+
+/// Fade a sound in over `fade`, or leave its onset untouched for zero
+///
+/// Zero skips the fade altogether rather than asking kira for a zero-length
+/// one, so the sprite starts sample for sample as in the file, at the risk
+/// of a click if it doesn't start at silence.
+fn with_fade_in(sound_data: StaticSoundData, fade: Duration) -> StaticSoundData {
+    if fade.is_zero() {
+        return sound_data;
     }
+    sound_data.fade_in_tween(Tween {
+        duration: fade,
+        ..Default::default()
+    })
+}
 
-    /// Create an Animalese engine with custom audio assets
-    ///
-    /// Only use this if you have custom audio files that match the expected
-    /// format (sprite sheets with 200ms letter sounds, etc).
-    ///
-    /// # Arguments
-    /// * `assets_path` - Path to your custom assets/audio/voice directory
-    ///
-    /// # Example
-    /// ```no_run
-    /// use animalese::Animalese;
-    ///
-    /// let engine = Animalese::with_custom_assets("./my_assets/voice").unwrap();
-    /// ```
-    pub fn with_custom_assets(assets_path: impl Into<String>) -> Result<Self, Box<dyn std::error::Error>> {
-        let voice_path = assets_path.into();
+/// Default for `VoiceProfile::pitch_floor`: an octave and a half down
+const DEFAULT_PITCH_FLOOR: f32 = -18.0;
 
-        // SFX file is in parent directory of voice
-        let sfx_path = Path::new(&voice_path)
-            .parent()
-            .ok_or("Invalid assets path")?
-            .join("sfx.ogg")
-            .to_string_lossy()
-            .to_string();
+/// Default for `VoiceProfile::pitch_ceiling`: an octave and a half up
+const DEFAULT_PITCH_CEILING: f32 = 18.0;
 
-        // Initialize kira audio manager
-        let manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())?;
+/// Pitch a sprite plays at, in semitones, kept within the profile's bounds
+///
+/// `pitch` is everything stacked up (profile shift, markup, intonation,
+/// boosts) and `variation` the random part. A floor above the ceiling
+/// gives the ceiling.
+fn final_pitch(pitch: f32, variation: f32, floor: f32, ceiling: f32) -> f32 {
+    (pitch + variation).max(floor).min(ceiling)
+}
 
-        let profile = Arc::new(Mutex::new(VoiceProfile::default()));
-        let active_sounds = Arc::new(Mutex::new(Vec::new()));
+/// Supported maximum for `VoiceProfile::declination`, in semitones
+const MAX_DECLINATION: f32 = 12.0;
 
-        Ok(Self {
-            manager: Arc::new(Mutex::new(manager)),
-            voice_path,
-            sfx_path,
-            profile,
-            active_sounds,
-        })
-    }
+/// Supported maximum for `VoiceProfile::intonation_range_semitones`
+const MAX_INTONATION_RANGE: f32 = 12.0;
 
-    /// Set the voice profile
-    pub fn set_profile(&mut self, new_profile: VoiceProfile) {
-        if let Ok(mut profile) = self.profile.lock() {
-            *profile = new_profile;
-        }
-    }
+/// Intonation presets for [`Animalese::speak_with_intonation`]
+///
+/// Each preset is a glide from -1.0 (falling) to 1.0 (rising), scaled by
+/// `VoiceProfile::intonation_range_semitones` so that by default 1.0 ends the
+/// utterance 3 semitones above where it started, plus an optional constant
+/// pitch boost. The semitone figures below are for that default range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Intonation {
+    /// No glide
+    Flat,
+    /// Rising glide for questions, ending 1.8 semitones up (or peaking there
+    /// and falling back, with [`QuestionShape::RisingFalling`])
+    Question,
+    /// Falling glide for statements, ending 0.9 semitones down
+    Statement,
+    /// Raised 2 semitones, then rising to 1.2 semitones above that
+    Excited,
+    /// Custom glide from -1.0 (falling) to 1.0 (rising), with no boost
+    Custom(f32),
+}
 
-    /// Get a copy of the current voice profile
-    pub fn profile(&self) -> VoiceProfile {
-        self.profile.lock().unwrap().clone()
+impl Intonation {
+    /// Glide amount from -1.0 (falling) to 1.0 (rising)
+    pub fn glide(&self) -> f32 {
+        match self {
+            Intonation::Flat => 0.0,
+            Intonation::Question => 0.6,
+            Intonation::Statement => -0.3,
+            Intonation::Excited => 0.4,
+            Intonation::Custom(glide) => *glide,
+        }
     }
 
-    /// Play a letter sound with the current voice profile
-    pub fn play_letter(&self, c: char) -> Result<(), Box<dyn std::error::Error>> {
-        self.play_letter_with_duration(c, None)
+    /// Constant pitch boost in semitones applied for the whole utterance
+    pub fn pitch_boost(&self) -> f32 {
+        match self {
+            Intonation::Excited => 2.0,
+            _ => 0.0,
+        }
     }
 
-    /// Play a letter sound with optional max duration (for fast typing)
-    pub fn play_letter_with_duration(&self, c: char, max_duration: Option<Duration>) -> Result<(), Box<dyn std::error::Error>> {
-        self.play_letter_with_options(c, max_duration, 0.0)
+    /// Pitch offset in semitones reached at the end of an utterance, with the
+    /// default 3-semitone intonation range
+    pub fn final_semitones(&self) -> f32 {
+        self.pitch_boost() + self.glide() * DEFAULT_INTONATION_RANGE
     }
+}
 
-    /// Play a letter sound with optional duration and intonation adjustment
-    fn play_letter_with_options(&self, c: char, max_duration: Option<Duration>, intonation_shift: f32) -> Result<(), Box<dyn std::error::Error>> {
-        let sprite_time = letter_to_sprite_time(c)
-            .ok_or("Not a valid letter")?;
+/// Pitch curve for question intonation
+///
+/// Used by `speak_question()` and the automatic rise for text ending in `?`,
+/// via `VoiceProfile::question_shape`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuestionShape {
+    /// A steady rise over the whole utterance, typical of yes/no questions
+    #[default]
+    Rising,
+    /// Rise to the full glide 80% of the way through, then fall back to the
+    /// starting pitch by the last sound, typical of wh-questions
+    RisingFalling,
+}
 
-        self.play_sprite(&self.voice_path, sprite_time, Duration::from_millis(200), true, max_duration, intonation_shift)
-    }
+/// Which side of the cutoff a [`FilterSettings`] keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    /// Keep the lows, for a muffled voice behind a wall
+    Lowpass,
+    /// Keep the highs, for a thin, tinny radio voice
+    Highpass,
+}
 
-    /// Play a special sound (ok, gwah, deska)
-    pub fn play_special(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let sprite_time = special_to_sprite_time(name)
-            .ok_or("Unknown special sound")?;
+/// A simple one-pole filter for shaping a voice's timbre
+///
+/// Rolls off gently (6 dB per octave) past `cutoff_hz`. The cutoff is where
+/// it's heard, whatever the pitch: sprites are filtered before being sped up
+/// or slowed down for pitch, with the cutoff moved to compensate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterSettings {
+    /// Low-pass or high-pass
+    pub kind: FilterKind,
+    /// Cutoff frequency in Hz (20 to 20000)
+    pub cutoff_hz: f32,
+}
 
-        self.play_sprite(&self.voice_path, sprite_time, Duration::from_millis(600), true, None, 0.0)
-    }
+/// What `speak()` plays for characters with no sprite of their own
+///
+/// Applies to digits, symbols like `#` or `%`, and emoji not mapped in
+/// `VoiceProfile::emoji_sfx`. Whitespace and everyday punctuation (`.,!?;:`,
+/// quotes, brackets, dashes) stay silent whatever the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnmappedPolicy {
+    /// Play nothing
+    #[default]
+    Skip,
+    /// Play the start of the `default` SFX, unpitched
+    DefaultSfx,
+    /// Play a short, quiet snippet of a voice sprite at the profile pitch
+    Blip,
+}
 
-    /// Play a sound effect (enter, backspace, etc)
-    pub fn play_sfx(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let sprite_time = sfx_to_sprite_time(name)
-            .ok_or("Unknown SFX sound")?;
+/// How long an unmapped character's placeholder plays, and how quiet a blip is
+const UNMAPPED_SFX_LENGTH: Duration = Duration::from_millis(200);
+const BLIP_LENGTH: Duration = Duration::from_millis(60);
+const BLIP_VOLUME: f32 = 0.4;
 
-        self.play_sprite(&self.sfx_path, sprite_time, Duration::from_millis(600), false, None, 0.0)
-    }
+/// Whether a character is punctuation that reads naturally as silence
+fn is_prose_punctuation(c: char) -> bool {
+    matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | '\'' | '"' | '‘' | '’' | '“' | '”'
+        | '(' | ')' | '[' | ']' | '{' | '}' | '-' | '–' | '—' | '…')
+}
 
-    /// Play text as animalese speech with intonation
-    pub fn speak(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let profile = self.profile.lock().unwrap();
-        let base_intonation = profile.intonation;
-        drop(profile);
-
-        // Check if text ends with question mark for automatic rising intonation
-        let has_question = text.trim_end().ends_with('?');
-        let intonation = if has_question && base_intonation == 0.0 {
-            0.5 // Apply gentle rising intonation for questions
-        } else {
-            base_intonation
-        };
+/// What an intonation glide is spread over
+///
+/// Either way the first voiced sound starts the glide and the last ends it;
+/// the basis decides where the sounds between fall on the curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntonationBasis {
+    /// Each voiced sound moves one step along the curve, however long the
+    /// pauses around it. Text with word, line or inline pauses bunches the
+    /// glide onto the letters rather than spreading it over the sentence.
+    #[default]
+    LetterCount,
+    /// Each sound sits on the curve by when it starts, so pitch keeps moving
+    /// through pauses as it would in speech. Better for long inputs.
+    Time,
+}
 
-        // Count letters for position calculation
-        let letters: Vec<char> = text.chars().filter(|c| c.is_ascii_alphabetic()).collect();
-        let total_letters = letters.len() as f32;
+/// Where a [`QuestionShape::RisingFalling`] contour peaks, as a fraction of the utterance
+const QUESTION_PEAK: f32 = 0.8;
 
-        if total_letters == 0.0 {
-            return Ok(());
+impl QuestionShape {
+    /// Fraction of the glide reached at voiced sound `index` of `total`
+    fn curve(&self, index: f32, total: f32) -> f32 {
+        match self {
+            QuestionShape::Rising => index / total,
+            QuestionShape::RisingFalling => {
+                let position = index / (total - 1.0).max(1.0);
+                if position <= QUESTION_PEAK {
+                    position / QUESTION_PEAK
+                } else {
+                    (1.0 - position) / (1.0 - QUESTION_PEAK)
+                }
+            }
         }
+    }
+}
 
-        let mut letter_index = 0.0;
-        for c in text.chars() {
-            if c.is_ascii_alphabetic() {
-                // Calculate position (0.0 to 1.0) in the sentence
-                let position = letter_index / total_letters;
+/// An intonation glide and the curve it follows over an utterance
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Contour {
+    glide: f32,
+    shape: QuestionShape,
+}
 
-                // Apply intonation curve
-                // Positive intonation = rising (pitch increases)
-                // Negative intonation = falling (pitch decreases)
-                let intonation_shift = intonation * position * 3.0; // Scale to ~3 semitones max
+impl From<f32> for Contour {
+    /// A plain glide, rising or falling steadily
+    fn from(glide: f32) -> Self {
+        Contour { glide, shape: QuestionShape::Rising }
+    }
+}
 
-                self.play_letter_with_options(c, None, intonation_shift)?;
-                letter_index += 1.0;
+/// Length of each letter's slot in a voice sprite sheet, in milliseconds
+///
+/// A voice file holds the letters a to z back to back, each starting at
+/// `letter_index * LETTER_SLOT_MS`, followed by the special sounds.
+pub const LETTER_SLOT_MS: u64 = 200;
 
-                // Small delay between letters to simulate speech cadence
-                std::thread::sleep(Duration::from_millis(50));
-            }
-        }
-        Ok(())
-    }
+/// Length of each special sound's slot in a voice sprite sheet, and of each
+/// sound effect's in an SFX sheet, in milliseconds
+///
+/// The specials (ok, gwah, deska) follow the 26 letters of a voice file in
+/// that order; `sfx.ogg` holds its sound effects back to back from the start.
+pub const SFX_SLOT_MS: u64 = 600;
 
-    /// Speak text with rising intonation (for questions)
-    ///
-    /// Automatically applies a rising pitch contour, perfect for questions
-    /// or uncertain statements.
+/// [`LETTER_SLOT_MS`] as a duration
+const LETTER_SLOT: Duration = Duration::from_millis(LETTER_SLOT_MS);
+
+/// [`SFX_SLOT_MS`] as a duration
+const SFX_SLOT: Duration = Duration::from_millis(SFX_SLOT_MS);
+
+/// Maps letters to their sprite positions in the audio file
+/// Each letter gets a [`LETTER_SLOT_MS`] slot starting at letter_index * LETTER_SLOT_MS
+fn letter_to_sprite_time(c: char) -> Option<Duration> {
+    let c = fold_compatibility_char(c).to_ascii_lowercase();
+    if !c.is_ascii_lowercase() {
+        return None;
+    }
+
+    let index = (c as u32 - 'a' as u32) as u64;
+    Some(Duration::from_millis(index * LETTER_SLOT_MS))
+}
+
+/// First code point of each run of ten decimal digits folded to `0`-`9`
+///
+/// Fullwidth digits are covered by the fullwidth block in
+/// [`fold_compatibility_char`].
+const DIGIT_ZEROS: [u32; 10] = [
+    0x0660,  // Arabic-Indic
+    0x06F0,  // Extended Arabic-Indic
+    0x0966,  // Devanagari
+    0x09E6,  // Bengali
+    0x0E50,  // Thai
+    0x1D7CE, // Mathematical bold
+    0x1D7D8, // Mathematical double-struck
+    0x1D7E2, // Mathematical sans-serif
+    0x1D7EC, // Mathematical sans-serif bold
+    0x1D7F6, // Mathematical monospace
+];
+
+/// Fold a compatibility form of an ASCII character to the ASCII character
+///
+/// Fullwidth forms (`Ｈ`, `ｅ`, `１`, `？`) become their ASCII
+/// counterparts, the ideographic space becomes a space, and the decimal
+/// digits of [`DIGIT_ZEROS`] become ASCII digits. Anything else is
+/// returned unchanged.
+fn fold_compatibility_char(c: char) -> char {
+    let code = c as u32;
+    let folded = match code {
+        0xFF01..=0xFF5E => code - 0xFEE0,
+        0x3000 => ' ' as u32,
+        _ => match DIGIT_ZEROS.iter().find(|&&zero| (zero..zero + 10).contains(&code)) {
+            Some(zero) => '0' as u32 + code - zero,
+            None => code,
+        },
+    };
+    char::from_u32(folded).unwrap_or(c)
+}
+
+/// Fold the compatibility forms in text to ASCII, see [`fold_compatibility_char`]
+fn fold_compatibility(text: &str) -> String {
+    text.chars().map(fold_compatibility_char).collect()
+}
+
+/// Skip leading silence in a sprite slot
+///
+/// Returns the trimmed start and the remaining slot length. The trim is clamped
+/// to the slot so playback never starts inside the next sprite.
+fn trim_sprite(start: Duration, slot: Duration, trim: Duration) -> (Duration, Duration) {
+    let trim = trim.min(slot);
+    (start + trim, slot - trim)
+}
+
+/// Special sounds, usable as `[name]` tokens in `speak()`, and where each
+/// starts in a voice's sprite sheet (one SFX slot each, after the letters)
+const SPECIAL_MAP: [(&str, Duration); 3] = [
+    ("ok", Duration::from_millis(26 * LETTER_SLOT_MS)),
+    ("gwah", Duration::from_millis(26 * LETTER_SLOT_MS + SFX_SLOT_MS)),
+    ("deska", Duration::from_millis(26 * LETTER_SLOT_MS + 2 * SFX_SLOT_MS)),
+];
+
+/// Special sprite times for non-letter sounds
+fn special_to_sprite_time(name: &str) -> Option<Duration> {
+    SPECIAL_MAP.iter().find(|(special, _)| *special == name).map(|(_, time)| *time)
+}
+
+/// The built-in special sounds and their offsets in each voice file
+///
+/// These are the names accepted by `play_special()` and as `[name]` in
+/// `speak()`. Each sprite is 600ms long.
+pub fn builtin_special_map() -> &'static [(&'static str, Duration)] {
+    &SPECIAL_MAP
+}
+
+/// A unit of text as seen by `speak()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// A letter with its own sprite
+    Letter(char),
+    /// A special sound embedded as `[ok]`, `[gwah]` or `[deska]`
+    Special(&'static str),
+    /// A switch to another voice, embedded as `[m1]`, `[f2]` etc.
+    Voice(VoiceType),
+    /// Anything else (spaces, punctuation, digits, ...)
+    Other(char),
+}
+
+impl Token {
+    /// Whether this token plays a sprite
+    fn is_voiced(&self) -> bool {
+        !matches!(self, Token::Other(_) | Token::Voice(_))
+    }
+}
+
+/// Split text into letters, special sound and voice tokens, and everything else
+///
+/// Bracketed names that aren't special sounds or voices are left as ordinary
+/// characters.
+/// Apostrophes between two letters (`'` or `’`) are dropped, so contractions
+/// like "don't" are one word, exactly as if written "dont".
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '['
+            && let Some(end) = rest.find(']')
+        {
+            let name = rest[1..end].to_ascii_lowercase();
+            if let Some((special, _)) = SPECIAL_MAP.iter().find(|(s, _)| *s == name) {
+                tokens.push(Token::Special(special));
+                rest = &rest[end + 1..];
+                continue;
+            }
+            if let Ok(voice) = name.parse::<VoiceType>() {
+                tokens.push(Token::Voice(voice));
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+
+        let after = &rest[c.len_utf8()..];
+        let in_word = matches!(tokens.last(), Some(Token::Letter(_)))
+            && after.starts_with(|next: char| next.is_ascii_alphabetic());
+        if !(matches!(c, '\'' | '’') && in_word) {
+            tokens.push(if c.is_ascii_alphabetic() { Token::Letter(c) } else { Token::Other(c) });
+        }
+        rest = after;
+    }
+
+    tokens
+}
+
+/// Common two-consonant word openings, for [`is_pronounceable`]
+const CONSONANT_ONSETS: [&str; 24] = [
+    "bl", "br", "ch", "cl", "cr", "dr", "fl", "fr", "gl", "gr", "pl", "pr",
+    "sc", "sh", "sk", "sl", "sm", "sn", "sp", "st", "sw", "th", "tr", "wh",
+];
+
+/// Whether an acronym reads naturally as a word
+///
+/// A simple phonotactic heuristic, not a dictionary: the word must be at least
+/// four letters, contain a vowel (a, e, i, o, u, or y after the first letter),
+/// never have three consonants in a row, and only open with two consonants if
+/// they're a common onset like "st" or "br". So "NASA", "NATO" and "SCUBA"
+/// are words while "FBI", "HTML" and "NYC" are spelled out. Short acronyms
+/// like "USA" are always spelled, even the odd one that's said as a word.
+fn is_pronounceable(word: &str) -> bool {
+    let word = word.to_ascii_lowercase();
+    let vowel: Vec<bool> = word.char_indices()
+        .map(|(i, c)| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u') || (c == 'y' && i > 0))
+        .collect();
+
+    if vowel.len() < 4 || !vowel.contains(&true) {
+        return false;
+    }
+    if vowel.windows(3).any(|w| w.iter().all(|v| !v)) {
+        return false;
+    }
+    vowel[0] || vowel[1] || CONSONANT_ONSETS.contains(&&word[..2])
+}
+
+/// Mark the letters of unpronounceable acronyms, which are spelled out
+///
+/// An acronym is a run of two or more letters that are all uppercase.
+fn spelled_letters(tokens: &[Token]) -> Vec<bool> {
+    let mut spelled = vec![false; tokens.len()];
+    let mut start = 0;
+    while start < tokens.len() {
+        let len = tokens[start..].iter()
+            .take_while(|t| matches!(t, Token::Letter(_)))
+            .count();
+        if len == 0 {
+            start += 1;
+            continue;
+        }
+
+        let word: String = tokens[start..start + len].iter()
+            .map(|t| match t {
+                Token::Letter(c) => *c,
+                _ => unreachable!("run is all letters"),
+            })
+            .collect();
+        if len >= 2 && word.chars().all(|c| c.is_ascii_uppercase()) && !is_pronounceable(&word) {
+            spelled[start..start + len].fill(true);
+        }
+        start += len;
+    }
+    spelled
+}
+
+/// Drop letters past the first `max` of every unbroken run of letters
+///
+/// Guards against adversarial input like a 100,000-letter "word", which would
+/// otherwise queue minutes of speech. Specials and punctuation end a run.
+/// A `max` of 0 keeps everything.
+fn cap_word_length(tokens: Vec<Token>, max: usize) -> Vec<Token> {
+    if max == 0 {
+        return tokens;
+    }
+    let mut run = 0;
+    tokens.into_iter()
+        .filter(|token| {
+            run = if matches!(token, Token::Letter(_)) { run + 1 } else { 0 };
+            run <= max
+        })
+        .collect()
+}
+
+/// Letters a word keeps under `VoiceProfile::speed_read`
+const SPEED_READ_LETTERS: usize = 4;
+
+/// Abbreviate long words for `VoiceProfile::speed_read`
+///
+/// Words (unbroken runs of letters) longer than [`SPEED_READ_LETTERS`] drop
+/// every vowel (a, e, i, o, u, either case) after their first letter, then
+/// keep only the first [`SPEED_READ_LETTERS`] letters that remain. So
+/// "notification" becomes "ntfc", "hello" becomes "hll", and "the" and
+/// "read" are left alone.
+fn speed_read_tokens(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result = Vec::with_capacity(tokens.len());
+    let mut word = Vec::new();
+    let flush = |word: &mut Vec<char>, result: &mut Vec<Token>| {
+        if word.len() > SPEED_READ_LETTERS {
+            let consonants = word.iter()
+                .enumerate()
+                .filter(|(i, c)| *i == 0 || !matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u'))
+                .map(|(_, c)| *c);
+            result.extend(consonants.take(SPEED_READ_LETTERS).map(Token::Letter));
+        } else {
+            result.extend(word.iter().copied().map(Token::Letter));
+        }
+        word.clear();
+    };
+
+    for token in tokens {
+        match token {
+            Token::Letter(c) => word.push(c),
+            other => {
+                flush(&mut word, &mut result);
+                result.push(other);
+            }
+        }
+    }
+    flush(&mut word, &mut result);
+    result
+}
+
+/// Tokens `speak()` voices for a run of text under a profile
+fn prepare_tokens(text: &str, profile: &VoiceProfile) -> Vec<Token> {
+    let tokens = cap_word_length(tokenize(text), profile.max_word_length);
+    if profile.speed_read {
+        speed_read_tokens(tokens)
+    } else {
+        tokens
+    }
+}
+
+/// Text after every transform, as planned by `speak()`
+#[derive(Debug, Clone, PartialEq)]
+enum NormalizedItem {
+    /// Tokens spoken with a style
+    Tokens(Vec<Token>, SpanStyle),
+    /// Silence before the next sound
+    Pause(Duration),
+}
+
+/// Apply a profile's text transforms, in the order `normalize_text` lists
+fn normalize(text: &str, profile: &VoiceProfile) -> Vec<NormalizedItem> {
+    let mut items = markup::parse(&fold_compatibility(text));
+    if profile.markdown_emphasis {
+        items = markup::emphasize(items);
+    }
+    items.into_iter()
+        .map(|item| match item {
+            MarkupItem::Text(text, style) => NormalizedItem::Tokens(prepare_tokens(&text, profile), style),
+            MarkupItem::Pause(pause) => NormalizedItem::Pause(pause),
+        })
+        .collect()
+}
+
+/// The text `speak()` voices under a profile, see `Animalese::normalize_text`
+fn normalize_text(text: &str, profile: &VoiceProfile) -> String {
+    let mut normalized = String::new();
+    for item in normalize(text, profile) {
+        let NormalizedItem::Tokens(tokens, _) = item else {
+            continue;
+        };
+        for token in tokens {
+            match token {
+                Token::Letter(c) | Token::Other(c) => normalized.push(c),
+                Token::Special(name) => normalized.push_str(&format!("[{}]", name)),
+                Token::Voice(voice) => normalized.push_str(&format!("[{}]", voice.name())),
+            }
+        }
+    }
+    normalized
+}
+
+/// Bytes of text `speak_reader()` holds before speaking a line with no newline
+const MAX_PENDING_TEXT: usize = 4096;
+
+/// Take the decodable UTF-8 from the front of `bytes`
+///
+/// Invalid sequences become U+FFFD. An incomplete sequence at the end is left
+/// in `bytes` so the rest of it can arrive with the next read.
+fn drain_utf8(bytes: &mut Vec<u8>) -> String {
+    let mut text = String::new();
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                text.push_str(valid);
+                bytes.clear();
+                return text;
+            }
+            Err(e) => {
+                let valid = e.valid_up_to();
+                text.push_str(std::str::from_utf8(&bytes[..valid]).expect("prefix is valid"));
+                let Some(invalid) = e.error_len() else {
+                    bytes.drain(..valid);
+                    return text;
+                };
+                text.push(char::REPLACEMENT_CHARACTER);
+                bytes.drain(..valid + invalid);
+            }
+        }
+    }
+}
+
+/// Names of the sound effects, in sprite order
+const SFX_NAMES: [&str; 26] = [
+    "backspace", "enter", "tab", "question", "exclamation", "at", "pound",
+    "dollar", "caret", "ampersand", "asterisk", "parenthesis_open",
+    "parenthesis_closed", "bracket_open", "bracket_closed", "brace_open",
+    "brace_closed", "tilde", "default", "arrow_left", "arrow_up",
+    "arrow_right", "arrow_down", "slash_forward", "slash_back", "percent",
+];
+
+/// Sound effects and where each starts in `sfx.ogg` (one SFX slot each, in order)
+const SFX_MAP: [(&str, Duration); SFX_NAMES.len()] = {
+    let mut map = [("", Duration::ZERO); SFX_NAMES.len()];
+    let mut index = 0;
+    while index < map.len() {
+        map[index] = (SFX_NAMES[index], Duration::from_millis(index as u64 * SFX_SLOT_MS));
+        index += 1;
+    }
+    map
+};
+
+/// SFX sprite times (600ms each)
+fn sfx_to_sprite_time(name: &str) -> Option<Duration> {
+    SFX_MAP.iter().find(|(sfx, _)| *sfx == name).map(|(_, time)| *time)
+}
+
+/// The built-in sound effects and their offsets in `sfx.ogg`
+///
+/// These are the names accepted by `play_sfx()` and `VoiceProfile::emoji_sfx`.
+/// Each sprite is 600ms long.
+///
+/// # Example
+/// ```
+/// for (name, offset) in animalese::builtin_sfx_map() {
+///     println!("{name}: {offset:?}");
+/// }
+/// ```
+pub fn builtin_sfx_map() -> &'static [(&'static str, Duration)] {
+    &SFX_MAP
+}
+
+/// Supported range for `VoiceProfile::tempo`
+const MIN_TEMPO: f32 = 0.25;
+const MAX_TEMPO: f32 = 4.0;
+
+/// Scale a sprite length or gap by tempo
+///
+/// Higher tempo means shorter durations; pitch is unaffected.
+fn scale_for_tempo(duration: Duration, tempo: f32) -> Duration {
+    duration.div_f64(clamp_finite(tempo, MIN_TEMPO, MAX_TEMPO, 1.0) as f64)
+}
+
+/// Which letter is sounding on each animation frame of a plan
+///
+/// Frame `i` covers the time from `i / fps` seconds and shows the letter
+/// sounding at that instant: the latest started letter that hasn't ended
+/// yet. A letter starting exactly on a frame boundary shows from that
+/// frame, and one ending there doesn't. There are enough frames to cover
+/// the whole plan, the last one partly past its end, and none at 0 fps.
+fn letter_frames(plan: &[PlannedSound], fps: u32) -> Vec<Option<char>> {
+    let end = plan.iter().map(|sound| sound.offset + sound.length).max().unwrap_or(Duration::ZERO);
+    let count = frames_covering(end, fps);
+    (0..count)
+        .map(|frame| {
+            let at = Duration::from_nanos((frame as u128 * 1_000_000_000 / fps as u128) as u64);
+            plan.iter()
+                .filter(|sound| sound.offset <= at && at < sound.offset + sound.length)
+                .filter_map(|sound| match sound.source {
+                    SoundSource::Letter(c) => Some((sound.offset, c)),
+                    SoundSource::Special(_) | SoundSource::Sfx(_) => None,
+                })
+                .max_by_key(|&(offset, _)| offset)
+                .map(|(_, c)| c)
+        })
+        .collect()
+}
+
+/// Frames at `fps` needed to cover `duration`, counting a partial last frame
+fn frames_covering(duration: Duration, fps: u32) -> usize {
+    (duration.as_nanos() * fps as u128).div_ceil(1_000_000_000) as usize
+}
+
+/// Letters in a standard word when measuring words per minute
+const WPM_WORD_LETTERS: u32 = 5;
+
+/// Tempo that speaks `words_per_minute` standard words with a profile's
+/// letter gap and word pause
+///
+/// A standard word is [`WPM_WORD_LETTERS`] letters and a space, taking
+/// `5 * letter_gap + word_pause` at tempo 1.0, so the tempo is
+/// `wpm * (5 * letter_gap + word_pause) / 60s`, within the supported tempos.
+fn tempo_for_wpm(words_per_minute: f32, profile: &VoiceProfile) -> f32 {
+    let word = profile.letter_gap * WPM_WORD_LETTERS + profile.word_pause;
+    (words_per_minute * word.as_secs_f32() / 60.0).clamp(MIN_TEMPO, MAX_TEMPO)
+}
+
+/// The frames a sprite's slice covers
+fn sprite_frames(data: &StaticSoundData) -> &[Frame] {
+    let (start, end) = data.slice.unwrap_or((0, data.frames.len()));
+    let end = end.min(data.frames.len());
+    let start = start.min(end);
+    &data.frames[start..end]
+}
+
+/// Replace a sliced sprite with a time-stretched copy of just that region
+///
+/// Keeps the sprite's settings; see `dsp::time_stretch` for quality notes.
+fn stretch_sprite(data: &StaticSoundData, factor: f32) -> StaticSoundData {
+    StaticSoundData {
+        sample_rate: data.sample_rate,
+        frames: dsp::time_stretch(sprite_frames(data), data.sample_rate, factor).into(),
+        settings: data.settings,
+        slice: None,
+    }
+}
+
+/// Replace a sliced sprite with a filtered copy of just that region
+///
+/// `rate` is the playback rate the sprite will be played at, which scales
+/// every frequency in it; the cutoff is divided by it so it lands in the
+/// right place once played.
+fn filter_sprite(data: &StaticSoundData, filter: FilterSettings, rate: f32) -> StaticSoundData {
+    let cutoff = filter.cutoff_hz / rate;
+    let frames = match filter.kind {
+        FilterKind::Lowpass => dsp::lowpass(sprite_frames(data), data.sample_rate, cutoff),
+        FilterKind::Highpass => dsp::highpass(sprite_frames(data), data.sample_rate, cutoff),
+    };
+    StaticSoundData {
+        sample_rate: data.sample_rate,
+        frames: frames.into(),
+        settings: data.settings,
+        slice: None,
+    }
+}
+
+/// Replace a sliced sprite with a pitch-shifted copy of just that region
+///
+/// Keeps the sprite's length and formants; see `dsp::pitch_shift_formants`
+/// for quality notes.
+fn formant_shift_sprite(data: &StaticSoundData, rate: f32) -> StaticSoundData {
+    StaticSoundData {
+        sample_rate: data.sample_rate,
+        frames: dsp::pitch_shift_formants(sprite_frames(data), data.sample_rate, rate).into(),
+        settings: data.settings,
+        slice: None,
+    }
+}
+
+/// Replace a sliced sprite with a backwards copy of just that region
+fn reverse_sprite(data: &StaticSoundData) -> StaticSoundData {
+    StaticSoundData {
+        sample_rate: data.sample_rate,
+        frames: sprite_frames(data).iter().rev().copied().collect(),
+        settings: data.settings,
+        slice: None,
+    }
+}
+
+/// Volume for one letter with random jitter
+///
+/// Scales `volume` by a factor drawn uniformly from `1.0 ± variation`, the
+/// loudness counterpart of `pitch_variation`, clamped to 0.0..=1.0.
+fn jittered_volume<R: Rng + ?Sized>(volume: f32, variation: f32, rng: &mut R) -> f32 {
+    if variation == 0.0 {
+        return volume;
+    }
+    (volume * (1.0 + rng.gen_range(-1.0..=1.0) * variation)).clamp(0.0, 1.0)
+}
+
+/// Calculate playback rate from pitch shift in semitones
+/// rate = 2^(semitones / 12)
+fn semitones_to_rate(semitones: f32) -> f32 {
+    2.0_f32.powf(semitones / 12.0)
+}
+
+/// A sprite to play: where it starts, how long it should sound, and how much
+/// of the sheet it may read before running into the next sprite
+#[derive(Debug, Clone, Copy)]
+struct SpriteWindow {
+    start: Duration,
+    length: Duration,
+    slot: Duration,
+}
+
+impl SpriteWindow {
+    /// A window whose audible length is its whole slot
+    fn whole(start: Duration, slot: Duration) -> Self {
+        SpriteWindow { start, length: slot, slot }
+    }
+
+    /// How much of the sheet to read when playing at `rate`
     ///
-    /// # Example
-    /// ```no_run
-    /// use animalese::Animalese;
+    /// Playing at `rate` turns a source window of `w` into `w / rate` of
+    /// audio, so sounding for `length` takes `length * rate` of source. That
+    /// is capped at the slot: a sprite pitched far up runs out of its own
+    /// recording and ends early rather than reading the next letter's.
+    fn source_length(&self, rate: f32) -> Duration {
+        self.length.mul_f64(rate as f64).min(self.slot)
+    }
+}
+
+/// Where the slot of a planned sound's sprite ends in its sheet
+fn sprite_slot_end(source: &SoundSource) -> Option<Duration> {
+    match *source {
+        SoundSource::Letter(c) => letter_to_sprite_time(c).map(|t| t + LETTER_SLOT),
+        SoundSource::Special(name) => special_to_sprite_time(name).map(|t| t + SFX_SLOT),
+        SoundSource::Sfx(ref name) => sfx_to_sprite_time(name).map(|t| t + SFX_SLOT),
+    }
+}
+
+/// What a planned sound plays
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SoundSource {
+    /// A letter sprite from the voice sheet
+    Letter(char),
+    /// A special sprite (ok, gwah, deska) from the voice sheet
+    Special(&'static str),
+    /// A sound effect standing in for an emoji, as named for `play_sfx()`
+    Sfx(String),
+}
+
+/// One sprite play that `speak()` would queue
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedSound {
+    /// What is being played
+    pub source: SoundSource,
+    /// When the sprite starts, relative to the start of the utterance
+    pub offset: Duration,
+    /// Where the sprite starts in its sprite sheet
     ///
-    /// let engine = Animalese::new().unwrap();
-    /// engine.speak_question("What's that").unwrap();
-    /// ```
-    pub fn speak_question(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Temporarily set intonation to rising
-        let original_intonation = {
-            let mut profile = self.profile.lock().unwrap();
-            let original = profile.intonation;
-            profile.intonation = 0.6; // Moderate rising intonation
-            original
+    /// Zero for a sound effect from a sound bank (`bank:sound`), which is
+    /// only looked up when played.
+    pub sprite_start: Duration,
+    /// How long the sprite sounds
+    ///
+    /// Pitched sprites read `length` times their playback rate from the
+    /// sheet so they sound for this long at any pitch, but never read past
+    /// the end of their own sprite.
+    pub length: Duration,
+    /// Intonation offset in semitones (before random variation)
+    pub intonation_shift: f32,
+    /// Extra pitch in semitones from inline markup
+    pub pitch_offset: f32,
+    /// Volume multiplier from inline markup
+    pub volume: f32,
+    /// Whether the profile pitch and variation apply
+    pub pitched: bool,
+    /// Voice from an inline `[m1]`-style token, instead of the profile's
+    pub voice: Option<VoiceType>,
+    /// Stereo position added to the profile's pan, from `speak_panning()`
+    pub pan: f32,
+}
+
+/// The placeholder an unmapped character plays under a policy, if any
+///
+/// Placeholders sit outside the intonation contour, like emoji SFX.
+fn unmapped_sound(policy: UnmappedPolicy, offset: Duration, pitch_offset: f32, volume: f32) -> Option<PlannedSound> {
+    let (source, sprite_start, length, volume, pitched) = match policy {
+        UnmappedPolicy::Skip => return None,
+        UnmappedPolicy::DefaultSfx => {
+            (SoundSource::Sfx("default".to_string()), sfx_to_sprite_time("default")?, UNMAPPED_SFX_LENGTH, volume, false)
+        }
+        UnmappedPolicy::Blip => {
+            (SoundSource::Letter('u'), letter_to_sprite_time('u')?, BLIP_LENGTH, volume * BLIP_VOLUME, true)
+        }
+    };
+    Some(PlannedSound {
+        source,
+        offset,
+        sprite_start,
+        length,
+        intonation_shift: 0.0,
+        pitch_offset,
+        volume,
+        pitched,
+        voice: None,
+        pan: 0.0,
+    })
+}
+
+/// Intonation `speak()` uses for text with the given profile
+///
+/// A trailing `?` gets the profile's `auto_question_intonation` (a gentle
+/// rise by default) when the profile has no intonation set. With
+/// `auto_terminal_intonation` on, a trailing `!` gets the rise of
+/// [`Intonation::Excited`] and a trailing `.` the fall of
+/// [`Intonation::Statement`] too. A nonzero profile intonation always wins.
+fn resolve_intonation(text: &str, profile: &VoiceProfile) -> f32 {
+    if profile.intonation != 0.0 {
+        return profile.intonation;
+    }
+    match text.trim_end().chars().last().map(fold_compatibility_char) {
+        Some('?') => profile.auto_question_intonation.unwrap_or(0.0),
+        Some(c) if c != '!' && c != '.' && profile.detect_question_words && starts_with_question_word(text) => {
+            profile.auto_question_intonation.unwrap_or(0.0)
+        }
+        Some('!') if profile.auto_terminal_intonation => Intonation::Excited.glide(),
+        Some('.') if profile.auto_terminal_intonation => Intonation::Statement.glide(),
+        _ => 0.0,
+    }
+}
+
+/// Words that open a question, for `VoiceProfile::detect_question_words`
+const QUESTION_WORDS: [&str; 28] = [
+    "what", "why", "how", "who", "whom", "whose", "where", "when", "which",
+    "is", "are", "am", "was", "were", "do", "does", "did", "can", "could",
+    "will", "would", "should", "shall", "may", "might", "have", "has", "had",
+];
+
+/// Whether text's first word is one of [`QUESTION_WORDS`], ignoring case
+fn starts_with_question_word(text: &str) -> bool {
+    let text = fold_compatibility(text);
+    let first = text.split(|c: char| !c.is_alphanumeric() && c != '\'').find(|word| !word.is_empty());
+    first.is_some_and(|word| QUESTION_WORDS.iter().any(|question| question.eq_ignore_ascii_case(word)))
+}
+
+/// Intonation contour `speak()` uses for text with the given profile
+///
+/// Like [`resolve_intonation`], with the automatic question rise following
+/// the profile's `question_shape`.
+fn resolve_contour(text: &str, profile: &VoiceProfile) -> Contour {
+    let glide = resolve_intonation(text, profile);
+    let last = text.trim_end().chars().last().map(fold_compatibility_char);
+    let auto_question = profile.intonation == 0.0
+        && glide != 0.0
+        && (last == Some('?') || (profile.detect_question_words && last != Some('!') && last != Some('.')));
+    Contour {
+        glide,
+        shape: if auto_question { profile.question_shape } else { QuestionShape::Rising },
+    }
+}
+
+/// Split text into sentences, each keeping its closing punctuation
+///
+/// A sentence ends at a run of `.`, `!`, `?` or `…` followed by whitespace or
+/// the end of the text. Blank sentences are dropped.
+fn split_sentences(text: &str) -> Vec<&str> {
+    let is_terminator = |c: char| matches!(c, '.' | '!' | '?' | '…');
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        if is_terminator(c) && next.is_none_or(char::is_whitespace) {
+            sentences.push(&text[start..i + c.len_utf8()]);
+            start = i + c.len_utf8();
+        }
+    }
+    sentences.push(&text[start..]);
+    sentences.into_iter().map(str::trim).filter(|s| !s.is_empty()).collect()
+}
+
+/// English words for 0 to 19
+const SMALL_NUMBERS: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+
+/// English words for the tens from twenty
+const TENS: [&str; 8] = ["twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+
+/// Names of the thousands groups, from the thousands up
+const NUMBER_GROUPS: [&str; 6] = ["thousand", "million", "billion", "trillion", "quadrillion", "quintillion"];
+
+/// Words for a number from 1 to 999
+fn hundreds_words(n: u64, words: &mut Vec<&'static str>) {
+    if n >= 100 {
+        words.extend([SMALL_NUMBERS[(n / 100) as usize], "hundred"]);
+    }
+    match n % 100 {
+        0 => {}
+        rest @ 1..20 => words.push(SMALL_NUMBERS[rest as usize]),
+        rest => {
+            words.push(TENS[(rest / 10 - 2) as usize]);
+            if rest % 10 != 0 {
+                words.push(SMALL_NUMBERS[(rest % 10) as usize]);
+            }
+        }
+    }
+}
+
+/// A number in English words, like "minus one thousand two hundred four"
+///
+/// Words are separated by spaces, with no "and" or hyphens, and thousands
+/// groups are named up to quintillions.
+fn number_words(n: i64) -> String {
+    if n == 0 {
+        return SMALL_NUMBERS[0].to_string();
+    }
+    let mut words = Vec::new();
+    if n < 0 {
+        words.push("minus");
+    }
+
+    let mut groups = Vec::new();
+    let mut rest = n.unsigned_abs();
+    while rest > 0 {
+        groups.push(rest % 1000);
+        rest /= 1000;
+    }
+    for (index, &group) in groups.iter().enumerate().rev() {
+        if group == 0 {
+            continue;
+        }
+        hundreds_words(group, &mut words);
+        if index > 0 {
+            words.push(NUMBER_GROUPS[index - 1]);
+        }
+    }
+    words.join(" ")
+}
+
+/// Volume of a breath between sentences, relative to the profile's volume
+const BREATH_VOLUME: f32 = 0.3;
+
+/// Pause a breath adds before the next sentence
+const BREATH_PAUSE: Duration = Duration::from_millis(300);
+
+/// Join per-sentence plans back to back, with a breath between sentences
+///
+/// Each sentence starts once the previous one has finished, and `breath`,
+/// a built-in SFX name, plays quietly at each boundary and delays the next
+/// sentence by [`BREATH_PAUSE`]. Unknown names mean no breaths. Returns the
+/// plan and, for each sound, the index of the sentence it belongs to; a
+/// breath belongs to the sentence before it.
+fn join_sentences(sentences: Vec<Vec<PlannedSound>>, breath: Option<&str>) -> (Vec<PlannedSound>, Vec<usize>) {
+    let breath = breath.and_then(|name| SFX_MAP.iter().find(|(sfx, _)| *sfx == name));
+    let count = sentences.len();
+    let mut plan = Vec::new();
+    let mut sentence_of = Vec::new();
+    let mut start = Duration::ZERO;
+    for (index, sentence_plan) in sentences.into_iter().enumerate() {
+        let end = sentence_plan.iter().map(|sound| sound.offset + sound.length).max().unwrap_or(Duration::ZERO);
+        plan.extend(sentence_plan.into_iter().map(|sound| PlannedSound { offset: start + sound.offset, ..sound }));
+        // The previous sentence finishes before the next begins
+        start += end;
+        if let Some(&(name, sprite_start)) = breath
+            && index + 1 < count
+        {
+            plan.push(PlannedSound {
+                source: SoundSource::Sfx(name.to_string()),
+                offset: start,
+                sprite_start,
+                length: SFX_SLOT,
+                intonation_shift: 0.0,
+                pitch_offset: 0.0,
+                volume: BREATH_VOLUME,
+                pitched: false,
+                voice: None,
+                pan: 0.0,
+            });
+            start += BREATH_PAUSE;
+        }
+        sentence_of.resize(plan.len(), index);
+    }
+    (plan, sentence_of)
+}
+
+/// Voices for `count` sentences from `available`, never the same twice running
+///
+/// Deterministic for a given seed. Falls back to `VoiceType::random` when no
+/// voices are available, and repeats when only one is.
+fn varied_voices(count: usize, available: &[VoiceType], seed: u64) -> Vec<VoiceType> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut voices: Vec<VoiceType> = Vec::with_capacity(count);
+    for _ in 0..count {
+        let candidates: Vec<VoiceType> = available.iter()
+            .copied()
+            .filter(|voice| available.len() < 2 || Some(voice) != voices.last())
+            .collect();
+        let voice = if candidates.is_empty() {
+            VoiceType::random(&mut rng)
+        } else {
+            candidates[rng.gen_range(0..candidates.len())]
+        };
+        voices.push(voice);
+    }
+    voices
+}
+
+/// Voiced letters up to which adaptive cadence keeps the full letter gap
+const ADAPTIVE_CADENCE_SHORT: usize = 50;
+
+/// Voiced letters from which adaptive cadence uses its shortest gap
+const ADAPTIVE_CADENCE_LONG: usize = 500;
+
+/// Fraction of the letter gap adaptive cadence shortens long texts to
+const ADAPTIVE_CADENCE_MIN_RATIO: f32 = 0.4;
+
+/// Letter gap for a text of `voiced` sounds with adaptive cadence on
+///
+/// The full `gap` up to [`ADAPTIVE_CADENCE_SHORT`] sounds, falling linearly
+/// to [`ADAPTIVE_CADENCE_MIN_RATIO`] of it at [`ADAPTIVE_CADENCE_LONG`] and
+/// staying there for anything longer.
+fn adaptive_letter_gap(gap: Duration, voiced: usize) -> Duration {
+    if voiced <= ADAPTIVE_CADENCE_SHORT {
+        return gap;
+    }
+    let progress = (voiced - ADAPTIVE_CADENCE_SHORT) as f32
+        / (ADAPTIVE_CADENCE_LONG - ADAPTIVE_CADENCE_SHORT) as f32;
+    gap.mul_f32(1.0 - (1.0 - ADAPTIVE_CADENCE_MIN_RATIO) * progress.min(1.0))
+}
+
+/// Compute the sprite plays for an utterance without playing anything
+fn plan_utterance(text: &str, profile: &VoiceProfile, intonation: impl Into<Contour>) -> Vec<PlannedSound> {
+    let intonation = intonation.into();
+    let items = normalize(text, profile);
+
+    // Count voiced tokens for position calculation
+    let total_voiced = items.iter()
+        .map(|item| match item {
+            NormalizedItem::Tokens(tokens, _) => tokens.iter().filter(|t| t.is_voiced()).count(),
+            NormalizedItem::Pause(_) => 0,
+        })
+        .sum::<usize>() as f32;
+    let letter_gap = if profile.adaptive_cadence {
+        adaptive_letter_gap(profile.letter_gap, total_voiced as usize)
+    } else {
+        profile.letter_gap
+    };
+
+    let mut plan = Vec::new();
+    let mut contour = Vec::new();
+    let mut offset = Duration::ZERO;
+    let mut voiced_index = 0.0;
+    let mut voice = None;
+    for item in items {
+        let (tokens, style) = match item {
+            NormalizedItem::Tokens(tokens, style) => (tokens, style),
+            NormalizedItem::Pause(pause) => {
+                offset += pause;
+                continue;
+            }
         };
+        let tempo = profile.tempo * style.rate;
+        let spelled = if profile.acronym_handling {
+            spelled_letters(&tokens)
+        } else {
+            vec![false; tokens.len()]
+        };
+
+        for (token, spelled) in tokens.into_iter().zip(spelled) {
+            // Mapped emoji play their SFX as-is, outside the intonation contour
+            if let Token::Other(c) = token
+                && let Some(sfx) = profile.emoji_sfx.get(&c)
+            {
+                plan.push(PlannedSound {
+                    source: SoundSource::Sfx(sfx.clone()),
+                    offset,
+                    // Bank sounds are looked up when played
+                    sprite_start: sfx_to_sprite_time(sfx).unwrap_or(Duration::ZERO),
+                    length: SFX_SLOT,
+                    intonation_shift: 0.0,
+                    pitch_offset: 0.0,
+                    volume: style.volume,
+                    pitched: false,
+                    voice: None,
+                    pan: 0.0,
+                });
+                offset += scale_for_tempo(Duration::from_millis(300), tempo);
+                continue;
+            }
+
+            if !token.is_voiced() {
+                match token {
+                    Token::Voice(next) => voice = Some(next),
+                    Token::Other(' ') => offset += scale_for_tempo(profile.word_pause, tempo),
+                    Token::Other('\n') => offset += scale_for_tempo(profile.line_pause, tempo),
+                    Token::Other(c) if !c.is_whitespace() && !is_prose_punctuation(c) => {
+                        if let Some(sound) = unmapped_sound(profile.unmapped, offset, style.pitch, style.volume) {
+                            plan.push(PlannedSound { voice, ..sound });
+                            offset += scale_for_tempo(letter_gap, tempo);
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            // Apply intonation curve by position in the sentence
+            // Positive intonation = rising (pitch increases)
+            // Negative intonation = falling (pitch decreases)
+            let position = intonation.shape.curve(voiced_index, total_voiced);
+            let intonation_shift = intonation.glide * position * profile.intonation_range_semitones;
+
+            let (source, sprite_start, length, gap) = match token {
+                Token::Letter(c) => {
+                    let sprite_time = letter_to_sprite_time(c).expect("letter tokens are ASCII letters");
+                    let (start, slot) = trim_sprite(sprite_time, LETTER_SLOT, profile.sprite_trim_start);
+                    let length = scale_for_tempo(LETTER_SLOT, tempo).min(slot);
+
+                    // Small delay between letters to simulate speech cadence,
+                    // or a letter and a breath when spelling out an acronym
+                    let gap = if spelled { Duration::from_millis(250) } else { letter_gap };
+                    (SoundSource::Letter(c), start, length, scale_for_tempo(gap, tempo))
+                }
+                Token::Special(name) => {
+                    let sprite_time = special_to_sprite_time(name).expect("special tokens are known sounds");
+                    let length = scale_for_tempo(SFX_SLOT, tempo);
+
+                    // Specials are whole words, so give them room before the next letter
+                    (SoundSource::Special(name), sprite_time, length, scale_for_tempo(Duration::from_millis(300), tempo))
+                }
+                Token::Voice(_) | Token::Other(_) => unreachable!("unvoiced tokens are skipped"),
+            };
+
+            contour.push(plan.len());
+            plan.push(PlannedSound {
+                source,
+                offset,
+                sprite_start,
+                length,
+                intonation_shift,
+                pitch_offset: style.pitch,
+                volume: style.volume,
+                pitched: true,
+                voice,
+                pan: 0.0,
+            });
+            offset += gap;
+            voiced_index += 1.0;
+        }
+    }
+
+    if profile.intonation_basis == IntonationBasis::Time {
+        retime_contour(&mut plan, &contour, intonation, profile.intonation_range_semitones);
+    }
+    apply_declination(&mut plan, &contour, profile.declination);
+    plan
+}
+
+/// Drift a plan's contour sounds down by `declination` semitones overall
+///
+/// The first voiced sound is untouched and the last is `declination` lower,
+/// with the ones between stepping down evenly by letter. The drift adds to
+/// whatever intonation curve the sounds already have.
+fn apply_declination(plan: &mut [PlannedSound], contour: &[usize], declination: f32) {
+    if declination == 0.0 || contour.len() < 2 {
+        return;
+    }
+    let steps = (contour.len() - 1) as f32;
+    for (n, &i) in contour.iter().enumerate() {
+        plan[i].intonation_shift -= declination * n as f32 / steps;
+    }
+}
+
+/// Plan letters with explicit durations, back to back
+///
+/// Each item starts when the previous one's duration is up. A letter sounds
+/// for its duration, clamped to its (trimmed) 200ms sprite so it never runs
+/// into the next letter's; the rest of the duration is silent. Anything but
+/// an ASCII letter is a silence of its duration.
+fn plan_timed(items: &[(char, Duration)], profile: &VoiceProfile) -> Vec<PlannedSound> {
+    let mut plan = Vec::new();
+    let mut offset = Duration::ZERO;
+    for &(c, duration) in items {
+        if let Some(sprite_time) = letter_to_sprite_time(c) {
+            let (start, slot) = trim_sprite(sprite_time, LETTER_SLOT, profile.sprite_trim_start);
+            plan.push(PlannedSound {
+                source: SoundSource::Letter(c),
+                offset,
+                sprite_start: start,
+                length: duration.min(slot),
+                intonation_shift: 0.0,
+                pitch_offset: 0.0,
+                volume: 1.0,
+                pitched: true,
+                voice: None,
+                pan: 0.0,
+            });
+        }
+        offset += duration;
+    }
+    plan
+}
+
+/// Re-spread the intonation of a plan's contour sounds by start time
+///
+/// `contour` indexes the sounds that follow the contour. The first and last
+/// keep the pitch they get by letter count; the ones between are placed by
+/// how far through the utterance they start, pauses included.
+fn retime_contour(plan: &mut [PlannedSound], contour: &[usize], intonation: Contour, range: f32) {
+    let (Some(&first), Some(&last)) = (contour.first(), contour.last()) else {
+        return;
+    };
+    let start = plan[first].offset;
+    let span = (plan[last].offset - start).as_secs_f32();
+    if span == 0.0 {
+        return;
+    }
+
+    let total = contour.len() as f32;
+    for &i in contour {
+        let fraction = (plan[i].offset - start).as_secs_f32() / span;
+        let position = intonation.shape.curve(fraction * (total - 1.0), total);
+        plan[i].intonation_shift = intonation.glide * position * range;
+    }
+}
+
+/// How a sound of a plan fades for a change of voice
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct VoiceFade {
+    /// Fade in over the crossfade, as the first sound in a new voice
+    fade_in: bool,
+    /// Fade out from this long after the sound starts, as a new voice comes in
+    fade_out_after: Option<Duration>,
+}
+
+/// Crossfades for the voice changes in a plan, one per sound
+///
+/// `voices` holds the voice each sound plays in, or `None` for sound effects,
+/// which don't take part. Where a sound's voice differs from the previous
+/// voiced sound's, it fades in, and every sound of another voice still
+/// ringing when it starts fades out from that moment.
+fn voice_fades(plan: &[PlannedSound], voices: &[Option<VoiceType>]) -> Vec<VoiceFade> {
+    let mut fades = vec![VoiceFade::default(); plan.len()];
+    let mut previous = None;
+    for (i, sound) in plan.iter().enumerate() {
+        let Some(voice) = voices[i] else {
+            continue;
+        };
+        if previous.is_some_and(|previous| previous != voice) {
+            fades[i].fade_in = true;
+            for k in 0..i {
+                let ringing = plan[k].offset + plan[k].length > sound.offset;
+                if ringing && voices[k].is_some_and(|other| other != voice) && fades[k].fade_out_after.is_none() {
+                    fades[k].fade_out_after = Some(sound.offset.saturating_sub(plan[k].offset));
+                }
+            }
+        }
+        previous = Some(voice);
+    }
+    fades
+}
+
+/// Sweep a plan's stereo position from `from` to `to`
+///
+/// The first sound sits at `from`, the last at `to` and the ones between
+/// step evenly by position, emoji SFX included.
+fn pan_plan(plan: &mut [PlannedSound], from: f32, to: f32) {
+    let steps = plan.len().saturating_sub(1).max(1) as f32;
+    for (i, sound) in plan.iter_mut().enumerate() {
+        sound.pan = from + (to - from) * i as f32 / steps;
+    }
+}
+
+/// Pitch the letters of a plan along a scale, cycling through it
+///
+/// The i-th letter's intonation shift becomes `scale[i % scale.len()]`
+/// semitones; other sounds are left alone. An empty scale changes nothing.
+fn melody_plan(plan: &mut [PlannedSound], scale: &[f32]) {
+    if scale.is_empty() {
+        return;
+    }
+    let letters = plan.iter_mut().filter(|sound| matches!(sound.source, SoundSource::Letter(_)));
+    for (sound, &semitones) in letters.zip(scale.iter().cycle()) {
+        sound.intonation_shift = semitones;
+    }
+}
+
+/// Convert a pan from -1.0 (left) to 1.0 (right) to kira's 0.0..=1.0
+///
+/// kira pans with an equal-power law, so a centred sound isn't quieter
+/// than one hard to a side.
+fn kira_panning(pan: f32) -> f64 {
+    ((pan.clamp(-1.0, 1.0) + 1.0) / 2.0) as f64
+}
+
+/// Play a plan back to front
+///
+/// Sounds come in the opposite order on a mirrored timeline, so gaps, pauses
+/// and the intonation contour are all reversed too.
+fn reverse_plan(plan: &[PlannedSound]) -> Vec<PlannedSound> {
+    let end = plan.last().map_or(Duration::ZERO, |sound| sound.offset);
+    plan.iter()
+        .rev()
+        .map(|sound| PlannedSound {
+            offset: end - sound.offset,
+            ..sound.clone()
+        })
+        .collect()
+}
+
+/// Latest a chorus voice may lag behind the first
+const CHORUS_MAX_DELAY: Duration = Duration::from_millis(30);
+
+/// Layer copies of a plan for several voices
+///
+/// Every voice after the first gets a fixed random delay of up to
+/// [`CHORUS_MAX_DELAY`] and a fixed random pitch offset within `±detune`
+/// semitones, and each copy is quietened by `1/√voices` so the layers don't
+/// clip. The result is sorted by offset, each sound paired with the index of
+/// its voice.
+fn chorus_plan(plan: &[PlannedSound], voices: &[VoiceType], detune: f32, rng: &mut impl Rng) -> Vec<(usize, PlannedSound)> {
+    let detune = detune.abs().min(MAX_PITCH_SHIFT);
+    let gain = 1.0 / (voices.len().max(1) as f32).sqrt();
+
+    let mut layered = Vec::with_capacity(plan.len() * voices.len());
+    for index in 0..voices.len() {
+        let (delay, pitch) = if index == 0 {
+            (Duration::ZERO, 0.0)
+        } else {
+            (CHORUS_MAX_DELAY.mul_f32(rng.r#gen()), rng.gen_range(-1.0..=1.0) * detune)
+        };
+        layered.extend(plan.iter().map(|sound| (index, PlannedSound {
+            offset: sound.offset + delay,
+            pitch_offset: sound.pitch_offset + pitch,
+            volume: sound.volume * gain,
+            ..sound.clone()
+        })));
+    }
+    layered.sort_by_key(|(_, sound)| sound.offset);
+    layered
+}
+
+/// Final pitch of each planned letter, before random variation
+fn pitch_track(plan: &[PlannedSound], profile: &VoiceProfile) -> Vec<(char, f32)> {
+    plan.iter()
+        .filter_map(|sound| match sound.source {
+            SoundSource::Letter(c) => Some((c, profile.pitch_shift + sound.pitch_offset + sound.intonation_shift)),
+            SoundSource::Special(_) | SoundSource::Sfx(_) => None,
+        })
+        .collect()
+}
+
+/// Default number of sprites `speak()` schedules ahead of playback
+const DEFAULT_LOOKAHEAD: usize = 8;
+
+/// Offset at which planned sound `index` may be handed to the mixer
+///
+/// Sounds are scheduled once the sound `lookahead` places earlier starts,
+/// so at most `lookahead` sounds are waiting in the mixer at any time.
+fn schedule_at(plan: &[PlannedSound], index: usize, lookahead: usize) -> Duration {
+    index.checked_sub(lookahead.max(1))
+        .map_or(Duration::ZERO, |earlier| plan[earlier].offset)
+}
+
+/// Timing of the utterance `speak()` is currently playing
+#[derive(Debug, Clone)]
+struct Utterance {
+    started: Instant,
+    offsets: Vec<Duration>,
+    /// Time jumped over by `skip_ahead()` so far
+    skipped: Duration,
+    /// Sprites `skip_ahead()` asked to drop that the speaking thread hasn't yet
+    pending_skip: usize,
+}
+
+/// The last utterance spoken, kept for `replay_last()`
+#[derive(Debug, Clone)]
+struct SpokenUtterance {
+    plan: Vec<PlannedSound>,
+    /// The distinct profiles the utterance was spoken with
+    profiles: Vec<VoiceProfile>,
+    /// Index into `profiles` for each planned sound
+    profile_of: Vec<usize>,
+}
+
+impl SpokenUtterance {
+    /// Remember a plan and the profile each of its sounds played with
+    fn new(plan: &[PlannedSound], profiles: &[&VoiceProfile]) -> Self {
+        let mut distinct: Vec<&VoiceProfile> = Vec::new();
+        let profile_of = profiles.iter()
+            .map(|&profile| {
+                // Callers pass the same few profiles over and over, so check identity first
+                distinct.iter()
+                    .position(|&seen| std::ptr::eq(seen, profile) || *seen == *profile)
+                    .unwrap_or_else(|| {
+                        distinct.push(profile);
+                        distinct.len() - 1
+                    })
+            })
+            .collect();
+        Self {
+            plan: plan.to_vec(),
+            profiles: distinct.into_iter().cloned().collect(),
+            profile_of,
+        }
+    }
+}
+
+impl Utterance {
+    /// Fraction of sprites that have started, by the wall clock
+    fn progress(&self) -> f32 {
+        utterance_progress(&self.offsets, self.started.elapsed() + self.skipped)
+    }
+}
+
+/// Why [`Animalese::wait_until`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wait {
+    Due,
+    Stopped,
+    Skip(usize),
+}
+
+/// A sound handed to the mixer
+struct ActiveSound {
+    handle: StaticSoundHandle,
+    /// Start of the utterance and index in its plan, for sprites from `speak()`
+    sprite: Option<(Instant, usize)>,
+    /// When the sound starts, later than now for sprites scheduled ahead
+    starts: Instant,
+}
+
+/// Whether an utterance is in progress or any sound hasn't finished
+///
+/// Forgets sounds that have stopped along the way.
+fn is_busy(utterance: &Mutex<Option<Utterance>>, active_sounds: &Mutex<Vec<ActiveSound>>) -> bool {
+    utterance.lock().unwrap().is_some() || {
+        let mut active = active_sounds.lock().unwrap();
+        active.retain(|sound| sound.handle.state() != kira::sound::PlaybackState::Stopped);
+        !active.is_empty()
+    }
+}
+
+/// Fraction of sprites with an offset at or before `elapsed`
+fn utterance_progress(offsets: &[Duration], elapsed: Duration) -> f32 {
+    if offsets.is_empty() {
+        return 0.0;
+    }
+    let played = offsets.partition_point(|offset| *offset <= elapsed);
+    played as f32 / offsets.len() as f32
+}
+
+/// Linearly interpolate a volume ramp
+///
+/// Returns `to` once `elapsed` reaches `duration` (including zero-length ramps).
+fn fade_value(from: f32, to: f32, elapsed: Duration, duration: Duration) -> f32 {
+    if elapsed >= duration {
+        return to;
+    }
+    let t = elapsed.as_secs_f32() / duration.as_secs_f32();
+    from + (to - from) * t
+}
+
+/// State of the master volume ramp, mirrored from the kira main track
+#[derive(Debug, Clone, Copy)]
+struct VolumeFade {
+    from: f32,
+    to: f32,
+    started: Instant,
+    duration: Duration,
+}
+
+impl VolumeFade {
+    fn steady(volume: f32) -> Self {
+        Self {
+            from: volume,
+            to: volume,
+            started: Instant::now(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    fn current(&self) -> f32 {
+        fade_value(self.from, self.to, self.started.elapsed(), self.duration)
+    }
+}
+
+/// A phrase synthesized ahead of time by [`Animalese::prepare`]
+///
+/// Cheap to clone; clones share the same audio.
+#[derive(Debug, Clone)]
+pub struct Clip {
+    data: StaticSoundData,
+}
+
+impl Clip {
+    /// Length of the clip
+    pub fn duration(&self) -> Duration {
+        self.data.duration()
+    }
+}
+
+/// Amplitude of the keepalive signal (about -90 dBFS)
+const KEEPALIVE_AMPLITUDE: f32 = 3e-5;
+
+/// A looping, inaudibly quiet signal that keeps the output device awake
+///
+/// Alternates sign every frame, so it sits at the Nyquist frequency and
+/// carries no audible tone even if it were loud enough to hear.
+fn keepalive_sound() -> StaticSoundData {
+    let frames: Vec<Frame> = (0..64)
+        .map(|n| Frame::from_mono(if n % 2 == 0 { KEEPALIVE_AMPLITUDE } else { -KEEPALIVE_AMPLITUDE }))
+        .collect();
+
+    StaticSoundData {
+        sample_rate: 44_100,
+        frames: frames.into(),
+        settings: StaticSoundSettings::new().loop_region(..),
+        slice: None,
+    }
+}
+
+/// Animalese sound engine with kira-based playback
+///
+/// Plays through the default output device unless built on another kira
+/// [`Backend`] with [`Animalese::with_audio_manager`].
+pub struct Animalese<B: Backend = DefaultBackend> {
+    manager: Arc<Mutex<AudioManager<B>>>,
+    voice_track: TrackHandle,
+    assets: Arc<AssetPack>,
+    sfx_banks: Arc<Mutex<HashMap<String, SfxBank>>>,
+    profile: Arc<Mutex<VoiceProfile>>,
+    active_sounds: Arc<Mutex<Vec<ActiveSound>>>,
+    master_volume: Arc<Mutex<VolumeFade>>,
+    lookahead: Arc<AtomicUsize>,
+    max_sounds: Arc<AtomicUsize>,
+    stop_generation: Arc<AtomicU64>,
+    enabled: Arc<AtomicBool>,
+    rng: Arc<Mutex<StdRng>>,
+    voice_trims: Arc<Mutex<HashMap<VoiceType, f32>>>,
+    last_letter: Arc<Mutex<Option<(char, Instant)>>>,
+    capture_state: Arc<CaptureState>,
+    captured: Arc<Mutex<(HeapConsumer<Frame>, Vec<Frame>)>>,
+    keepalive: Arc<Mutex<Option<StaticSoundHandle>>>,
+    reported_underruns: Arc<AtomicU64>,
+    sfx_loop: Arc<Mutex<Option<StaticSoundHandle>>>,
+    utterance: Arc<Mutex<Option<Utterance>>>,
+    last_spoken: Arc<Mutex<Option<Arc<SpokenUtterance>>>>,
+    on_idle: Arc<Mutex<Option<IdleCallback>>>,
+    idle_watcher_started: Arc<AtomicBool>,
+    buffer_size: Option<u32>,
+}
+
+/// Called when the engine goes from playing to idle
+type IdleCallback = Box<dyn Fn() + Send>;
+
+/// How often the idle watcher checks whether anything is still playing
+const IDLE_POLL: Duration = Duration::from_millis(10);
+
+impl Animalese {
+    /// Create a new Animalese engine with bundled assets
+    ///
+    /// # Errors
+    ///
+    /// Fails if the default output device can't be opened. The audio manager
+    /// and its mixer track are set up before this returns, with no playback
+    /// thread of the engine's own to fail later, so an `Ok` engine can play.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak("hello world").unwrap();
+    /// ```
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let manifest = bundled_manifest()?;
+        Ok(Self::with_custom_assets(manifest.voice_dir.to_string_lossy())?.configure(manifest))
+    }
+
+    /// Create an Animalese engine from a voice pack's `voices.toml` manifest
+    ///
+    /// The manifest names the pack's voice directory (relative to the
+    /// manifest, with `sfx.ogg` next to it), and optionally each voice's file
+    /// and volume trim in decibels:
+    ///
+    /// ```toml
+    /// voice_dir = "voice"
+    ///
+    /// [voices.m2]
+    /// file = "deep.wav"       # leave out to try m2.ogg, m2.wav, ...
+    /// trim_db = -3.0
+    /// ```
+    ///
+    /// Voices without a table keep their built-in file names and no trim,
+    /// so a manifest with only `voice_dir` behaves like
+    /// [`Animalese::with_custom_assets`]. Per-voice pitch defaults and custom
+    /// sprite layouts aren't supported yet: pitch variation stays
+    /// [`VoiceType::default_pitch_variation`], and packs must follow the
+    /// [`LETTER_SLOT_MS`] and [`SFX_SLOT_MS`] layout. The bundled pack
+    /// ships its manifest as `assets/audio/voices.toml`, and
+    /// [`Animalese::new`] uses a copy compiled into the crate.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the manifest can't be read, with
+    /// [`AnimaleseError::InvalidManifest`] if it can't be parsed, and like
+    /// [`Animalese::with_custom_assets`] otherwise.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::from_manifest("./my_pack/voices.toml").unwrap();
+    /// engine.speak("hello").unwrap();
+    /// ```
+    pub fn from_manifest(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path)?;
+        let manifest = manifest::Manifest::parse(&toml, path.parent().unwrap_or(Path::new("")))?;
+        Ok(Self::with_custom_assets(manifest.voice_dir.to_string_lossy())?.configure(manifest))
+    }
+
+    /// Create an Animalese engine with custom audio assets
+    ///
+    /// Only use this if you have custom audio files that match the expected
+    /// format (sprite sheets with 200ms letter sounds, etc).
+    ///
+    /// # Arguments
+    /// * `assets_path` - Path to your custom assets/audio/voice directory
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::with_custom_assets("./my_assets/voice").unwrap();
+    /// ```
+    pub fn with_custom_assets(assets_path: impl Into<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        // Initialize kira audio manager
+        let manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())?;
+
+        Self::with_audio_manager(assets_path, manager)
+    }
+}
+
+/// Configures and creates an [`Animalese`] engine on an output device
+///
+/// # Example
+/// ```no_run
+/// use animalese::AnimaleseBuilder;
+///
+/// let engine = AnimaleseBuilder::new()
+///     .device("CABLE Input (VB-Audio Virtual Cable)")
+///     .build()
+///     .unwrap();
+/// engine.speak("hello stream").unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AnimaleseBuilder {
+    assets_path: Option<String>,
+    device: Option<String>,
+    buffer_size: Option<u32>,
+}
+
+impl AnimaleseBuilder {
+    /// Start with bundled assets on the default output device
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use custom assets (see [`Animalese::with_custom_assets`])
+    pub fn assets(mut self, assets_path: impl Into<String>) -> Self {
+        self.assets_path = Some(assets_path.into());
+        self
+    }
+
+    /// Play through the output device with this exact name
+    ///
+    /// Names come from [`list_output_devices`].
+    pub fn device(mut self, name: impl Into<String>) -> Self {
+        self.device = Some(name.into());
+        self
+    }
+
+    /// Ask the output device for a fixed buffer of `frames` per callback
+    ///
+    /// By default the device picks its own buffer size, which on some systems
+    /// is large enough (tens of milliseconds) to make typing sounds lag
+    /// behind the keys. Smaller buffers cut that latency (256 frames is about
+    /// 6ms at 44.1kHz) at the cost of crackles from underruns if the system
+    /// can't keep up. Sizes outside what the device supports are pulled into
+    /// its range; [`Animalese::buffer_size`] reports what was used.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::AnimaleseBuilder;
+    ///
+    /// let engine = AnimaleseBuilder::new().buffer_size(256).build().unwrap();
+    /// println!("buffer: {:?} frames", engine.buffer_size());
+    /// ```
+    pub fn buffer_size(mut self, frames: u32) -> Self {
+        self.buffer_size = Some(frames);
+        self
+    }
+
+    /// Open the output device and create the engine
+    ///
+    /// Without [`AnimaleseBuilder::assets`], the bundled pack is used with
+    /// its manifest, as by [`Animalese::new`]. Returns
+    /// [`AnimaleseError::DeviceNotFound`] if a device was named but no
+    /// output device has that name.
+    pub fn build(self) -> Result<Animalese, Box<dyn std::error::Error>> {
+        let device = match self.device {
+            Some(name) => Some(device::find_output_device(&name).ok_or(AnimaleseError::DeviceNotFound(name))?),
+            None => None,
+        };
+        let buffer_size = self.buffer_size
+            .map(|frames| device::supported_buffer_size(device.as_ref(), frames));
+
+        let manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings {
+            backend_settings: CpalBackendSettings {
+                device,
+                buffer_size: buffer_size.map_or(cpal::BufferSize::Default, cpal::BufferSize::Fixed),
+            },
+            ..Default::default()
+        })?;
+
+        let mut engine = Animalese::with_assets_or_bundled(self.assets_path, manager)?;
+        engine.buffer_size = buffer_size;
+        Ok(engine)
+    }
+}
+
+impl Animalese<StreamBackend> {
+    /// Get a handle for pulling rendered samples into your own audio pipeline
+    ///
+    /// See [`StreamBackend`] for a full example.
+    pub fn sample_stream(&self) -> SampleStream {
+        self.manager.lock().unwrap().backend_mut().sample_stream()
+    }
+}
+
+impl<B: Backend> Animalese<B> {
+    /// Create an Animalese engine on an existing kira audio manager
+    ///
+    /// Use this to play through a backend other than the default output
+    /// device, such as [`StreamBackend`] for feeding your own mixer.
+    ///
+    /// # Arguments
+    /// * `assets_path` - Path to the assets/audio/voice directory
+    /// * `manager` - A kira audio manager on any backend
+    ///
+    /// Returns [`AnimaleseError::AssetNotFound`] if the voice directory or the
+    /// `sfx.ogg` next to it is missing.
+    pub fn with_audio_manager(assets_path: impl Into<String>, manager: AudioManager<B>) -> Result<Self, Box<dyn std::error::Error>> {
+        let assets = Arc::new(AssetPack::open(assets_path.into())?);
+
+        let profile = Arc::new(Mutex::new(VoiceProfile::default()));
+        let active_sounds = Arc::new(Mutex::new(Vec::new()));
+
+        // All sprites play through a sub-track so speak_capturing can tap them
+        let mut manager = manager;
+        let capture_state = Arc::new(CaptureState::default());
+        let (tap, consumer) = capture::capture_tap(capture_state.clone());
+        let voice_track = manager.add_sub_track(TrackBuilder::new().with_effect(tap))?;
+
+        Ok(Self {
+            manager: Arc::new(Mutex::new(manager)),
+            voice_track,
+            assets,
+            sfx_banks: Arc::new(Mutex::new(HashMap::new())),
+            profile,
+            active_sounds,
+            master_volume: Arc::new(Mutex::new(VolumeFade::steady(1.0))),
+            lookahead: Arc::new(AtomicUsize::new(DEFAULT_LOOKAHEAD)),
+            max_sounds: Arc::new(AtomicUsize::new(0)),
+            stop_generation: Arc::new(AtomicU64::new(0)),
+            enabled: Arc::new(AtomicBool::new(true)),
+            rng: Arc::new(Mutex::new(StdRng::from_entropy())),
+            voice_trims: Arc::new(Mutex::new(HashMap::new())),
+            last_letter: Arc::new(Mutex::new(None)),
+            capture_state,
+            captured: Arc::new(Mutex::new((consumer, Vec::new()))),
+            keepalive: Arc::new(Mutex::new(None)),
+            reported_underruns: Arc::new(AtomicU64::new(0)),
+            sfx_loop: Arc::new(Mutex::new(None)),
+            utterance: Arc::new(Mutex::new(None)),
+            last_spoken: Arc::new(Mutex::new(None)),
+            on_idle: Arc::new(Mutex::new(None)),
+            idle_watcher_started: Arc::new(AtomicBool::new(false)),
+            buffer_size: None,
+        })
+    }
+
+    /// Set the voice profile
+    ///
+    /// Out-of-range values are clamped to their supported range (see
+    /// [`VoiceProfile::clamped`]), so a stray volume of 50.0 can't clip.
+    ///
+    /// Takes `&self`, so an engine shared across threads with `Arc<Animalese>`
+    /// can be reconfigured without wrapping it in a `Mutex`.
+    pub fn set_profile(&self, new_profile: VoiceProfile) {
+        if let Ok(mut profile) = self.profile.lock() {
+            *profile = new_profile.clamped();
+        }
+    }
+
+    /// Set the pitch variation, overriding the voice's built-in default
+    ///
+    /// Sets `VoiceProfile::pitch_variation` (clamped to 0.0..=2.0) and turns
+    /// `use_voice_defaults` off so the value takes effect.
+    pub fn set_variation(&self, variation: f32) {
+        if let Ok(mut profile) = self.profile.lock() {
+            profile.pitch_variation = variation.clamp(0.0, MAX_PITCH_VARIATION);
+            profile.use_voice_defaults = false;
+        }
+    }
+
+    /// Whether the assets path has a file for a voice
+    ///
+    /// Checks every supported extension on disk without decoding, so a file
+    /// that exists but is corrupt still counts. Useful with custom packs that
+    /// only ship some voices.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, VoiceType};
+    ///
+    /// let engine = Animalese::with_custom_assets("./my_assets/voice").unwrap();
+    /// if !engine.has_voice(VoiceType::M4) {
+    ///     println!("m4 isn't in this pack");
+    /// }
+    /// ```
+    pub fn has_voice(&self, voice: VoiceType) -> bool {
+        !self.assets.voice_files(voice).is_empty()
+    }
+
+    /// An engine on a voice directory, or on the bundled pack with its
+    /// manifest applied like [`Animalese::new`] does
+    fn with_assets_or_bundled(assets_path: Option<String>, manager: AudioManager<B>) -> Result<Self, Box<dyn std::error::Error>> {
+        match assets_path {
+            Some(assets_path) => Self::with_audio_manager(assets_path, manager),
+            None => {
+                let manifest = bundled_manifest()?;
+                Ok(Self::with_audio_manager(manifest.voice_dir.to_string_lossy(), manager)?.configure(manifest))
+            }
+        }
+    }
+
+    /// Apply a manifest's file names and trims
+    fn configure(mut self, manifest: manifest::Manifest) -> Self {
+        let mut names = HashMap::new();
+        for (voice, entry) in manifest.voices {
+            if let Some(file) = entry.file {
+                names.insert(voice, file);
+            }
+            self.set_voice_trim(voice, entry.trim_db);
+        }
+        self.assets = Arc::new(AssetPack { voice_file_names: names, ..(*self.assets).clone() });
+        self
+    }
+
+    /// Check every voice file and the SFX sheet without playing anything
+    ///
+    /// A QA pass for voice pack authors: each voice's files are decoded and
+    /// checked to be long enough for every letter and special sprite (7s),
+    /// and likewise `sfx.ogg` for every built-in sound effect. Problems are
+    /// reported per file, so a corrupt `f1.ogg` is flagged even if an
+    /// `f1.wav` next to it would be used instead. Packs meant for
+    /// [`SpriteOverflow::Clamp`] or `Wrap` are reported as too short, too.
+    /// Returns an empty list when everything is fine. Decoding every file
+    /// takes a moment, so don't call this on a hot path.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::with_custom_assets("./my_pack/voice").unwrap();
+    /// for issue in engine.validate_assets() {
+    ///     eprintln!("{}", issue);
+    /// }
+    /// ```
+    pub fn validate_assets(&self) -> Vec<AssetIssue> {
+        let voice_length = voice_sheet_length();
+        let sfx_length = SFX_MAP.iter().map(|(_, start)| *start).max().unwrap_or_default() + SFX_SLOT;
+
+        let mut issues = Vec::new();
+        for voice in VoiceType::ALL {
+            let files = self.assets.voice_files(voice);
+            if files.is_empty() {
+                issues.push(AssetIssue {
+                    path: self.assets.voice_file(voice),
+                    problem: AssetProblem::Missing,
+                });
+            }
+            issues.extend(files.into_iter().filter_map(|path| check_asset(path, voice_length)));
+        }
+
+        let sfx = PathBuf::from(&self.assets.sfx_path);
+        if sfx.is_file() {
+            issues.extend(check_asset(sfx, sfx_length));
+        } else {
+            issues.push(AssetIssue { path: sfx, problem: AssetProblem::Missing });
+        }
+        issues
+    }
+
+    /// Check the engine's setup in one call, for bug reports
+    ///
+    /// Runs [`Animalese::validate_assets`], lists the output devices, reads
+    /// the mixer's sample rate and starts a 50ms silent tone through the
+    /// mixer, reporting each result in a [`SelfTestReport`] rather than
+    /// stopping at the first failure. Nothing is audible, and the tone plays
+    /// even when the engine is disabled. Decodes every asset, so it takes a
+    /// moment.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let report = engine.self_test();
+    /// println!("{}", report);
+    /// assert!(report.is_ok());
+    /// ```
+    pub fn self_test(&self) -> SelfTestReport {
+        let frames = (SELF_TEST_TONE.as_secs_f64() * 44_100.0) as usize;
+        let tone = StaticSoundData {
+            sample_rate: 44_100,
+            frames: vec![Frame::ZERO; frames].into(),
+            settings: StaticSoundSettings::new(),
+            slice: None,
+        }
+        .output_destination(&self.voice_track);
+        let test_tone = self.manager.lock().unwrap().play(tone).map(drop).map_err(|e| e.to_string());
+
+        let sample_rate = self.capture_state.sample_rate.load(Ordering::Relaxed);
+        SelfTestReport {
+            backend: std::any::type_name::<B>().to_string(),
+            asset_issues: self.validate_assets(),
+            output_devices: list_output_devices(),
+            sample_rate: (sample_rate != 0).then_some(sample_rate),
+            test_tone,
+        }
+    }
+
+    /// The voices with a file in the assets path, in `VoiceType::ALL` order
+    pub fn available_voices(&self) -> Vec<VoiceType> {
+        VoiceType::ALL.into_iter()
+            .filter(|voice| self.has_voice(*voice))
+            .collect()
+    }
+
+    /// Get a copy of the current voice profile
+    pub fn profile(&self) -> VoiceProfile {
+        self.profile.lock().unwrap().clone()
+    }
+
+    /// Get the current master volume
+    ///
+    /// While a fade is in progress this reports the interpolated value.
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume.lock().unwrap().current()
+    }
+
+    /// Set the master volume immediately (0.0 to 1.0)
+    ///
+    /// Applied on top of the profile volume. Cancels any in-progress fade.
+    pub fn set_master_volume(&self, volume: f32) {
+        self.fade_volume_to(volume, Duration::ZERO);
+    }
+
+    /// Trim one voice's volume by `db` decibels, e.g. to tame a loud voice
+    ///
+    /// Applies to every sprite from that voice's sheet, whichever way the
+    /// voice was picked (the profile, an inline `[m2]` token, `speak_varied()`
+    /// or a chorus layer), including offline renders. Sound effects aren't
+    /// trimmed. Gains multiply: a sprite plays at the voice trim × the
+    /// profile volume (and any markup or jitter on it) × the master volume.
+    /// There's no built-in loudness table underneath; the trim is the only
+    /// per-voice adjustment. `db` is clamped to -60.0..=12.0, and 0.0 removes
+    /// the trim.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, VoiceType};
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_voice_trim(VoiceType::M2, -3.0);
+    /// ```
+    pub fn set_voice_trim(&self, voice: VoiceType, db: f32) {
+        let db = db.clamp(MIN_VOICE_TRIM_DB, MAX_VOICE_TRIM_DB);
+        let mut trims = self.voice_trims.lock().unwrap();
+        if db == 0.0 {
+            trims.remove(&voice);
+        } else {
+            trims.insert(voice, db);
+        }
+    }
+
+    /// The volume trim for a voice in decibels, 0.0 if it has none
+    pub fn voice_trim(&self, voice: VoiceType) -> f32 {
+        self.voice_trims.lock().unwrap().get(&voice).copied().unwrap_or(0.0)
+    }
+
+    /// Ramp the master volume from its current value to `target` over `over`
+    ///
+    /// Handy for ducking animalese under music. The ramp is linear and runs
+    /// on the audio thread, so this returns immediately. Starting a new fade
+    /// cancels any fade already in progress, continuing from wherever it got to.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    /// use std::time::Duration;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.fade_volume_to(0.2, Duration::from_millis(500));
+    /// ```
+    pub fn fade_volume_to(&self, target: f32, over: Duration) {
+        let target = target.clamp(0.0, 1.0);
+
+        let mut fade = self.master_volume.lock().unwrap();
+        *fade = VolumeFade {
+            from: fade.current(),
+            to: target,
+            started: Instant::now(),
+            duration: over,
+        };
+
+        let mut manager = self.manager.lock().unwrap();
+        manager.main_track().set_volume(Volume::Amplitude(target as f64), Tween {
+            duration: over,
+            ..Default::default()
+        });
+    }
+
+    /// Play a letter sound with the current voice profile
+    pub fn play_letter(&self, c: char) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_letter_with_duration(c, None)
+    }
+
+    /// Play a letter sound with optional max duration (for fast typing)
+    pub fn play_letter_with_duration(&self, c: char, max_duration: Option<Duration>) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_letter_with(c, max_duration, 0.0)
+    }
+
+    /// Play a letter sound with optional max duration and extra pitch
+    ///
+    /// `pitch_semitones` is added to the profile's pitch shift for this
+    /// letter only, so interactive typing can lift the pitch of fast bursts
+    /// without touching the shared profile.
+    ///
+    /// With `VoiceProfile::min_retrigger_gap` set, the same letter played
+    /// again sooner than that after it last sounded is dropped, so a held key
+    /// repeating at 30ms doesn't machine-gun its sprite. Different letters
+    /// always play. This debounces live playing only: `speak()` spaces its
+    /// letters by `letter_gap` and plays repeated letters like "aaa" in full.
+    pub fn play_letter_with(&self, c: char, max_duration: Option<Duration>, pitch_semitones: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let sprite_time = letter_to_sprite_time(c)
+            .ok_or("Not a valid letter")?;
+
+        let mut profile = self.profile();
+        if self.is_retrigger(c, profile.min_retrigger_gap) {
+            return Ok(());
+        }
+        profile.pitch_shift += pitch_semitones;
+        let (start, slot) = trim_sprite(sprite_time, LETTER_SLOT, profile.sprite_trim_start);
+        let length = max_duration.map_or(slot, |d| d.min(slot));
+
+        self.play_sprite(&profile, Sheet::Voice(&self.assets), SpriteWindow { start, length, slot }, true, Duration::ZERO)
+    }
+
+    /// Whether `c` repeats the last played letter within `gap`
+    ///
+    /// Remembers `c` as the last played letter unless it's a repeat to drop.
+    fn is_retrigger(&self, c: char, gap: Duration) -> bool {
+        let c = c.to_ascii_lowercase();
+        let now = Instant::now();
+        let mut last = self.last_letter.lock().unwrap();
+        if let Some((letter, at)) = *last
+            && letter == c
+            && now.duration_since(at) < gap
+        {
+            return true;
+        }
+        *last = Some((c, now));
+        false
+    }
+
+    /// Play a special sound (ok, gwah, deska)
+    pub fn play_special(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let sprite_time = special_to_sprite_time(name)
+            .ok_or("Unknown special sound")?;
+
+        self.play_sprite(&self.profile(), Sheet::Voice(&self.assets), SpriteWindow::whole(sprite_time, SFX_SLOT), true, Duration::ZERO)
+    }
+
+    /// Play a sound effect (enter, backspace, etc)
+    ///
+    /// Bare names play from the bundled `sfx.ogg`; `bank:sound` plays from a
+    /// sheet added with `register_sfx_bank()`.
+    pub fn play_sfx(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (path, window) = self.resolve_sfx(&self.assets, name)?;
+        self.play_sprite(&self.profile(), Sheet::Sfx(&path), window, false, Duration::ZERO)
+    }
+
+    /// Add an SFX sprite sheet, played with `play_sfx("name:sound")`
+    ///
+    /// The file is decoded up front and every sprite in the layout is checked
+    /// to start inside it, so a bad bank fails here rather than on first use.
+    /// Registering a name again replaces the earlier bank.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the name is empty or contains `:` or whitespace, the file is
+    /// missing ([`AnimaleseError::AssetNotFound`]) or won't decode, or a sprite
+    /// starts past its end ([`AnimaleseError::SpriteOutOfRange`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, SpriteLayout};
+    /// use std::path::Path;
+    /// use std::time::Duration;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let layout = SpriteLayout::sequential(["click", "hover"], Duration::from_millis(300));
+    /// engine.register_sfx_bank("ui", Path::new("ui.ogg"), layout).unwrap();
+    /// engine.play_sfx("ui:click").unwrap();
+    /// engine.play_sfx("enter").unwrap(); // bundled sfx.ogg
+    /// ```
+    pub fn register_sfx_bank(&self, name: &str, path: &Path, layout: SpriteLayout) -> Result<(), Box<dyn std::error::Error>> {
+        sfx_bank::validate_bank_name(name)?;
+        if !path.is_file() {
+            return Err(AnimaleseError::AssetNotFound(path.to_path_buf()).into());
+        }
+
+        let file_length = StaticSoundData::from_file(path)?.duration();
+        if let Some(start) = layout.last_offset()
+            && start >= file_length
+        {
+            return Err(AnimaleseError::SpriteOutOfRange { start, file_length }.into());
+        }
+
+        let bank = SfxBank { path: path.to_path_buf(), layout };
+        self.sfx_banks.lock().unwrap().insert(name.to_string(), bank);
+        event!(debug, logging::PLAYBACK, "registered sfx bank {} from {}", name, path.display());
+        Ok(())
+    }
+
+    /// Find the sheet and sprite for a bare or `bank:sound` SFX name
+    ///
+    /// Bare names play from the `sfx.ogg` of `assets`.
+    fn resolve_sfx(&self, assets: &AssetPack, name: &str) -> Result<(String, SpriteWindow), Box<dyn std::error::Error>> {
+        match sfx_bank::parse_sfx_name(name) {
+            (None, sound) => {
+                let sprite_time = sfx_to_sprite_time(sound).ok_or("Unknown SFX sound")?;
+                Ok((assets.sfx_path.clone(), SpriteWindow::whole(sprite_time, SFX_SLOT)))
+            }
+            (Some(bank), sound) => {
+                let banks = self.sfx_banks.lock().unwrap();
+                let bank = banks.get(bank).ok_or_else(|| format!("Unknown SFX bank: {}", bank))?;
+                let sprite_time = bank.layout.offset(sound).ok_or_else(|| format!("Unknown SFX sound: {}", name))?;
+                let window = SpriteWindow::whole(sprite_time, bank.layout.sprite_length());
+                Ok((bank.path.to_string_lossy().to_string(), window))
+            }
+        }
+    }
+
+    /// Play a sound effect on repeat, for an ambient sound like a loading hum
+    ///
+    /// The SFX loops at the profile volume until `stop_sfx_loop()` is called.
+    /// Only one loop plays at a time; starting another replaces the first.
+    /// The loop plays alongside speech without holding it up: `stop()` leaves
+    /// it running, and `speak_blocking()` doesn't wait for it to end.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.play_sfx_looping("default").unwrap();
+    /// engine.speak_blocking("loading, please wait").unwrap();
+    /// engine.stop_sfx_loop();
+    /// ```
+    pub fn play_sfx_looping(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (path, window) = self.resolve_sfx(&self.assets, name)?;
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let profile = self.profile();
+        let length = window.length;
+        let sound_data = StaticSoundData::from_file(&path)?;
+        let start = resolve_sprite_start(window.start, length, sound_data.duration(), profile.sprite_overflow)?;
+
+        let start_time = start.as_secs_f64();
+        let mut sound_data = sound_data.slice(start_time..start_time + length.as_secs_f64());
+        if let Some(filter) = profile.filter {
+            sound_data = filter_sprite(&sound_data, filter, 1.0);
+        }
+        let sound_data = sound_data
+            .loop_region(..)
+            .volume(Volume::Amplitude(profile.volume as f64))
+            .output_destination(&self.voice_track);
+        let sound_data = with_fade_in(sound_data, profile.fade_in);
+        let handle = self.manager.lock().unwrap().play(sound_data)?;
+
+        if let Some(mut previous) = self.sfx_loop.lock().unwrap().replace(handle) {
+            previous.stop(Tween::default());
+        }
+        event!(debug, logging::PLAYBACK, "looping sfx {}", name);
+        Ok(())
+    }
+
+    /// Stop the sound effect started by `play_sfx_looping()`, if any
+    pub fn stop_sfx_loop(&self) {
+        if let Some(mut handle) = self.sfx_loop.lock().unwrap().take() {
+            handle.stop(Tween::default());
+            event!(debug, logging::PLAYBACK, "sfx loop stopped");
+        }
+    }
+
+    /// Play a sound effect with its own volume and pitch
+    ///
+    /// Overrides apply to this sound only; the profile isn't touched and no
+    /// random variation is added. Volume is clamped to 0.0..=1.0 and pitch to
+    /// ±12 semitones.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.play_sfx_with("enter", 0.4, 5.0).unwrap();
+    /// ```
+    pub fn play_sfx_with(&self, name: &str, volume: f32, pitch_semitones: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let (path, window) = self.resolve_sfx(&self.assets, name)?;
+
+        let profile = VoiceProfile {
+            pitch_shift: pitch_semitones.clamp(-MAX_PITCH_SHIFT, MAX_PITCH_SHIFT),
+            pitch_variation: 0.0,
+            volume: volume.clamp(0.0, 1.0),
+            ..self.profile()
+        };
+        self.play_sprite(&profile, Sheet::Sfx(&path), window, true, Duration::ZERO)
+    }
+
+    /// Play text as animalese speech with intonation
+    ///
+    /// Letters are voiced one sprite each. The special sounds can be embedded
+    /// with the tokens `[ok]`, `[gwah]` and `[deska]` (case-insensitive), e.g.
+    /// `"see you later [deska]"`. Bracketed text that isn't a known special is
+    /// spoken literally, so `"[hi]"` voices the letters h and i.
+    ///
+    /// Voice tokens switch voice mid-text for simple dialogue:
+    /// `"[m1]Hi there [f2]Hello back"` voices "Hi there" as m1 and the rest as
+    /// f2. A voice lasts until the next voice token, for this call only; the
+    /// shared profile isn't changed. Names that aren't voices, like `[m9]`,
+    /// are spoken literally like any other bracketed text.
+    ///
+    /// Voices switch abruptly by default. A nonzero
+    /// `VoiceProfile::voice_change_fade` crossfades instead: the first letter
+    /// in the new voice fades in over it while the old voice's letters still
+    /// ringing fade out over it, from the moment the new voice starts. This
+    /// costs next to nothing: it's a volume ramp on sounds that overlap
+    /// anyway, with no extra decoding or mixing. `speak_varied()` fades in
+    /// each sentence's new voice the same way, though as its sentences don't
+    /// overlap there's nothing to fade out.
+    ///
+    /// Inline markup adjusts delivery for part of the text:
+    /// `<pitch=+3>`, `<rate=1.5>` and `<vol=0.5>` spans (closed with
+    /// `</pitch>` etc.) and `<pause=250ms>`. Spans nest; pitch adds while rate
+    /// and volume multiply. Malformed or unknown tags are kept as literal
+    /// text, so only the letters inside them are voiced.
+    ///
+    /// With `VoiceProfile::markdown_emphasis` on, `*word*` is spoken with
+    /// mild emphasis (a semitone up, a little louder) and `**word**` with
+    /// strong, excited-style emphasis. The asterisks aren't voiced; unmatched
+    /// ones are kept as literal, silent symbols.
+    ///
+    /// Emoji are silent unless `VoiceProfile::emoji_sfx` maps them to a sound
+    /// effect name (as used by `play_sfx()`), e.g. `'❓' => "question"` or
+    /// `'🔔' => "ui:bell"`. Only single-codepoint emoji are matched:
+    /// multi-codepoint sequences such as flags, skin tones and ZWJ families
+    /// are split into their parts, which are matched individually. Names are
+    /// checked before the first sound plays, so a name that isn't a built-in
+    /// SFX or a sound in a registered bank fails without playing anything.
+    ///
+    /// With `VoiceProfile::acronym_handling` on, all-caps words are spelled
+    /// out with a pause after each letter unless they look pronounceable: at
+    /// least four letters, a vowel, no three consonants in a row and a
+    /// plausible opening. It's a heuristic, so "FBI" is spelled and "NASA"
+    /// isn't, but "USA"-style short acronyms are always spelled.
+    ///
+    /// Letters are spaced by `VoiceProfile::letter_gap`, with an extra
+    /// `word_pause` for each space and `line_pause` for each line break (both
+    /// none by default, and scaled by tempo).
+    /// With `VoiceProfile::adaptive_cadence` on, long texts speed up so they
+    /// stay bearable: texts of up to 50 voiced sounds keep the full gap,
+    /// which then shrinks linearly to 40% of it at 500 sounds and stays
+    /// there for anything longer. Word and line pauses aren't shortened.
+    ///
+    /// With `VoiceProfile::speed_read` on, words of five or more letters are
+    /// abbreviated for a quick impression of the text: vowels after the first
+    /// letter are dropped and at most four letters are kept, so
+    /// "notification" is voiced as "ntfc". Off by default since it's lossy.
+    ///
+    /// Text ending in `?` glides by `VoiceProfile::auto_question_intonation`
+    /// (0.5 by default) when the profile's intonation is 0.0. A nonzero
+    /// profile intonation is always used as-is, and setting the option to
+    /// `None` turns the automatic rise off entirely. The automatic rise
+    /// follows `VoiceProfile::question_shape`. With
+    /// `VoiceProfile::auto_terminal_intonation` on, text ending in `!` also
+    /// rises like `speak_excited()` (without its pitch boost) and text ending
+    /// in `.` falls like `speak_statement()`; it's off by default so plain
+    /// sentences keep a flat delivery.
+    ///
+    /// With `VoiceProfile::detect_question_words` on, text that opens with a
+    /// question word gets the automatic question rise even without a `?`:
+    /// the wh-words what, why, how, who, whom, whose, where, when and which,
+    /// and the auxiliaries is, are, am, was, were, do, does, did, can,
+    /// could, will, would, should, shall, may, might, have, has and had
+    /// (any case). So "what is this" rises and "this is nice" doesn't. Text
+    /// ending in `.` or `!` is left to its punctuation. It's a heuristic
+    /// ("Will you" and "Will said" both open with "will"), so it's off by
+    /// default. `speak_varied()` checks each sentence on its own.
+    ///
+    /// `VoiceProfile::declination` adds a slow downward drift over the whole
+    /// text, like a speaker tiring over a paragraph: the first letter is
+    /// unchanged and the last is `declination` semitones lower. It's added
+    /// on top of the intonation glide rather than replacing it, so a
+    /// question still rises at its end, just from a lower starting point.
+    ///
+    /// Digits and symbols are silent unless `VoiceProfile::unmapped` gives
+    /// them a placeholder sound (see [`UnmappedPolicy`]).
+    ///
+    /// Fullwidth forms are folded to ASCII before anything else, so
+    /// `"ＨＥＬＬＯ？"` speaks exactly like `"HELLO?"`, and the ideographic
+    /// space counts as a space. Decimal digits from Arabic-Indic, Devanagari,
+    /// Bengali and Thai, and the mathematical digits, are folded to `0`-`9`.
+    /// Other letters, such as accented or mathematical-styled ones, aren't
+    /// folded and stay silent.
+    pub fn speak(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.profile();
+        let intonation = resolve_contour(text, &profile);
+
+        self.speak_as(text, &profile, intonation)
+    }
+
+    /// Speak text and wait until it has finished playing
+    ///
+    /// Unlike `speak()`, which returns once the last sprite has started, this
+    /// also waits for every sound to finish. Returns early if `stop()` is called.
+    pub fn speak_blocking(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let generation = self.stop_generation.load(Ordering::SeqCst);
+        self.speak(text)?;
+        self.wait_for_silence(generation);
+        Ok(())
+    }
+
+    /// Speak text only if nothing is playing, without queueing it otherwise
+    ///
+    /// For low-priority lines such as idle NPC barks: returns `Ok(false)`
+    /// and plays nothing if [`is_playing()`](Self::is_playing), otherwise
+    /// speaks like `speak()` and returns `Ok(true)` once the last sprite has
+    /// started. The check and the start of speech aren't atomic, so two
+    /// threads calling this at the same moment can both see an idle engine
+    /// and both speak; use one thread for barks if that matters.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// if !engine.try_speak("nice weather today").unwrap() {
+    ///     // Someone else was talking; skip the bark
+    /// }
+    /// ```
+    pub fn try_speak(&self, text: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.is_playing() {
+            return Ok(false);
+        }
+        self.speak(text)?;
+        Ok(true)
+    }
+
+    /// Speak the most recent utterance again, for a "say that again" button
+    ///
+    /// Replays exactly what was last spoken by `speak()` or any of its
+    /// variants (`speak_varied()`, `speak_chorus()`, `speak_melody()` and
+    /// the rest), letter for letter, with the profile it was first spoken
+    /// with: changing the profile since doesn't change the replay. Only the
+    /// random pitch variation and volume jitter are drawn afresh. Single
+    /// sounds from `play_letter()` and the `play_sfx` family aren't
+    /// utterances and aren't remembered. A no-op returning `Ok` if nothing
+    /// has been spoken yet, or if the last text had nothing to voice.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak("the password is swordfish").unwrap();
+    /// engine.replay_last().unwrap();
+    /// ```
+    pub fn replay_last(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let last = self.last_spoken.lock().unwrap().clone();
+        let Some(last) = last else {
+            return Ok(());
+        };
+        let profiles: Vec<&VoiceProfile> = last.profile_of.iter().map(|&index| &last.profiles[index]).collect();
+        self.speak_layers(&last.plan, &profiles)
+    }
+
+    /// Speak text, wait for it to finish, and return what was played
+    ///
+    /// The audio is heard as usual while the mixer output for the voice is
+    /// copied aside on the audio thread, so nothing is synthesized twice. The
+    /// recording runs from the call until the last sound ends, and is taken
+    /// before the master volume. Sounds from other calls playing at the same
+    /// time are recorded too, so only capture one utterance at a time.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let capture = engine.speak_capturing("hello world").unwrap();
+    /// println!("{} samples, {:?}", capture.samples.len(), capture.duration());
+    /// ```
+    pub fn speak_capturing(&self, text: &str) -> Result<Capture, Box<dyn std::error::Error>> {
+        {
+            let mut captured = self.captured.lock().unwrap();
+            let (consumer, frames) = &mut *captured;
+            consumer.pop_iter().for_each(drop);
+            frames.clear();
+        }
+
+        self.capture_state.capturing.store(true, Ordering::SeqCst);
+        let result = self.speak_blocking(text);
+        self.capture_state.capturing.store(false, Ordering::SeqCst);
+        result?;
+
+        self.drain_capture();
+        let frames = std::mem::take(&mut self.captured.lock().unwrap().1);
+        Ok(Capture {
+            sample_rate: self.capture_state.sample_rate.load(Ordering::Relaxed),
+            samples: frames.iter()
+                .flat_map(|frame| [capture::to_i16(frame.left), capture::to_i16(frame.right)])
+                .collect(),
+        })
+    }
+
+    /// Render text to samples without playing it
+    ///
+    /// Synthesizes the same sprites `speak()` would play with the current
+    /// profile, but mixes them offline on a private [`StreamBackend`], so it
+    /// runs faster than real time and never touches the output device. The
+    /// master volume isn't applied. Samples are rounded to 16 bits at the end;
+    /// see [`Animalese::render_to_samples_f32`] to keep full precision. Set
+    /// `VoiceProfile::fade_in` to zero to render each sprite's onset exactly
+    /// as in the file, though that may bring back clicks.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let clip = engine.render_to_samples("hello world").unwrap();
+    /// println!("{:?} at {} Hz", clip.duration(), clip.sample_rate);
+    /// ```
+    pub fn render_to_samples(&self, text: &str) -> Result<Capture, Box<dyn std::error::Error>> {
+        let (sample_rate, frames) = self.render_frames(text)?;
+
+        Ok(Capture {
+            sample_rate,
+            samples: frames.iter()
+                .flat_map(|frame| [capture::to_i16(frame.left), capture::to_i16(frame.right)])
+                .collect(),
+        })
+    }
+
+    /// Render text to floating-point samples without playing it
+    ///
+    /// Like [`Animalese::render_to_samples`], but returns the mixer's output
+    /// as-is: interleaved stereo `f32` at 44.1kHz, nominally within -1.0..=1.0
+    /// but unclipped. Sprites are decoded, filtered, stretched and mixed in
+    /// `f32` either way; this skips the final rounding to 16 bits, so
+    /// further processing (your own effects, resampling, gain) doesn't stack
+    /// quantization noise and can recover peaks above full scale.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let samples = engine.render_to_samples_f32("hello world").unwrap();
+    /// let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    /// println!("{} samples, peak {:.2}", samples.len(), peak);
+    /// ```
+    pub fn render_to_samples_f32(&self, text: &str) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+        let (_, frames) = self.render_frames(text)?;
+        Ok(frames.iter().flat_map(|frame| [frame.left, frame.right]).collect())
+    }
+
+    /// Render text offline with the current profile, returning the sample rate and frames
+    fn render_frames(&self, text: &str) -> Result<(u32, Vec<Frame>), Box<dyn std::error::Error>> {
+        let profile = self.profile();
+        let intonation = resolve_contour(text, &profile);
+        let plan = plan_utterance(text, &profile, intonation);
+
+        let settings = StreamBackendSettings::default();
+        let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings {
+            backend_settings: settings,
+            ..Default::default()
+        })?;
+        let mut renderer = Animalese::with_audio_manager(self.assets.voice_path.clone(), manager)?;
+        // Draw variation from this engine, so seeding it seeds renders too
+        renderer.rng = self.rng.clone();
+        renderer.voice_trims = self.voice_trims.clone();
+        renderer.assets = self.assets.clone();
+        let stream = renderer.sample_stream();
+
+        // Everything is scheduled up front; the mixer's clock does the timing
+        let fades = Self::plan_voice_fades(&plan, &vec![&profile; plan.len()]);
+        for (index, sound) in plan.iter().enumerate() {
+            let handle = renderer.play_planned_faded(&renderer.assets, &profile, sound, fades.get(index), sound.offset)?;
+            renderer.keep_sound(handle, None);
+        }
+
+        let mut frames = Vec::new();
+        let mut block = vec![0.0f32; 1024];
+        while !renderer.is_silent() {
+            stream.fill(&mut block);
+            frames.extend(block.chunks_exact(2).map(|pair| Frame { left: pair[0], right: pair[1] }));
+        }
+
+        Ok((settings.sample_rate, frames))
+    }
+
+    /// Synthesize a phrase once for cheap repeated playback
+    ///
+    /// Renders the text offline with the current profile (see
+    /// [`Animalese::render_to_samples`]) into a [`Clip`] that
+    /// [`Animalese::play_clip`] can play any number of times without
+    /// synthesizing again. The clip is fixed at prepare time: later profile
+    /// changes don't affect it, and its random pitch variation is the same on
+    /// every play.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let ding = engine.prepare("new message!").unwrap();
+    /// engine.play_clip(&ding).unwrap();
+    /// ```
+    pub fn prepare(&self, text: &str) -> Result<Clip, Box<dyn std::error::Error>> {
+        let (sample_rate, frames) = self.render_frames(text)?;
+
+        Ok(Clip {
+            data: StaticSoundData {
+                sample_rate,
+                frames: frames.into(),
+                settings: StaticSoundSettings::new(),
+                slice: None,
+            },
+        })
+    }
+
+    /// Play a clip made by [`Animalese::prepare`]
+    ///
+    /// Returns immediately. The clip goes through the master volume and is
+    /// cancelled by `stop()` like any other sound.
+    pub fn play_clip(&self, clip: &Clip) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.is_enabled() || !self.has_room() {
+            return Ok(());
+        }
+        let sound_data = clip.data.output_destination(&self.voice_track);
+        let handle = self.manager.lock().unwrap().play(sound_data)?;
+        self.keep_sound(handle, None);
+        Ok(())
+    }
+
+    /// Play externally generated audio through the engine's mixer
+    ///
+    /// `samples` are interleaved stereo, the same layout as [`Capture`], so a
+    /// mono signal needs each sample twice. The audio plays at the profile
+    /// volume and pan through the master volume, the profile's filter, `stop()`,
+    /// `set_enabled()` and `set_on_idle()`, just like a sprite. With
+    /// `apply_pitch` it's also pitched by the profile's `pitch_shift` plus
+    /// random variation, which changes its length as for sprites.
+    ///
+    /// Returns an error if `samples` has an odd length or `sample_rate` is 0.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// // 100ms of 880Hz beep
+    /// let beep: Vec<i16> = (0..4410)
+    ///     .map(|n| ((n as f32 * 880.0 * std::f32::consts::TAU / 44_100.0).sin() * 8000.0) as i16)
+    ///     .flat_map(|s| [s, s])
+    ///     .collect();
+    /// engine.play_samples(beep, 44_100, false).unwrap();
+    /// ```
+    pub fn play_samples(&self, samples: Vec<i16>, sample_rate: u32, apply_pitch: bool) -> Result<(), Box<dyn std::error::Error>> {
+        if !samples.len().is_multiple_of(2) {
+            return Err("play_samples expects interleaved stereo (an even number of samples)".into());
+        }
+        if sample_rate == 0 {
+            return Err("play_samples needs a nonzero sample rate".into());
+        }
+        if !self.is_enabled() || !self.has_room() {
+            return Ok(());
+        }
+
+        let profile = self.profile();
+        let frames: Vec<Frame> = samples
+            .chunks_exact(2)
+            .map(|pair| Frame {
+                left: pair[0] as f32 / i16::MAX as f32,
+                right: pair[1] as f32 / i16::MAX as f32,
+            })
+            .collect();
+        let mut sound_data = StaticSoundData {
+            sample_rate,
+            frames: frames.into(),
+            settings: StaticSoundSettings::new(),
+            slice: None,
+        };
+
+        let playback_rate = if apply_pitch {
+            let random_variation = self.rng.lock().unwrap().gen_range(-1.0..=1.0) * profile.effective_pitch_variation();
+            semitones_to_rate(final_pitch(profile.pitch_shift, random_variation, profile.pitch_floor, profile.pitch_ceiling))
+        } else {
+            1.0
+        };
+        if let Some(filter) = profile.filter {
+            sound_data = filter_sprite(&sound_data, filter, playback_rate);
+        }
+
+        let sound_data = sound_data
+            .playback_rate(playback_rate as f64)
+            .volume(Volume::Amplitude(profile.volume as f64))
+            .panning(kira_panning(profile.pan))
+            .output_destination(&self.voice_track);
+        let handle = self.manager.lock().unwrap().play(sound_data)?;
+        self.keep_sound(handle, None);
+        Ok(())
+    }
+
+    /// Render text offline and write it to a 16-bit WAV file
+    ///
+    /// See [`Animalese::render_to_samples`].
+    pub fn render_to_wav(&self, text: &str, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        self.render_to_samples(text)?.write_wav(path)?;
+        Ok(())
+    }
+
+    /// Speak a script of lines in order, calling `between` after each one
+    ///
+    /// Each line is spoken with `speak_blocking`, then `between` is called with
+    /// its index (e.g. to advance a dialogue box). Empty lines are silent but
+    /// still get a callback. Calling `stop()`, including from inside `between`,
+    /// ends the sequence without speaking the remaining lines.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_lines(&["Hi there!", "Nice weather today."], |i| {
+    ///     println!("finished line {i}");
+    /// }).unwrap();
+    /// ```
+    pub fn speak_lines<F: FnMut(usize)>(&self, lines: &[&str], mut between: F) -> Result<(), Box<dyn std::error::Error>> {
+        let generation = self.stop_generation.load(Ordering::SeqCst);
+        for (index, line) in lines.iter().enumerate() {
+            if self.stop_generation.load(Ordering::SeqCst) != generation {
+                break;
+            }
+            self.speak_blocking(line)?;
+            between(index);
+        }
+        Ok(())
+    }
+
+    /// Speak text from a reader as it arrives, such as a pipe or a growing log
+    ///
+    /// Bytes are decoded as UTF-8 across read boundaries, so a multi-byte
+    /// character split between two reads is kept whole; invalid bytes become
+    /// U+FFFD, which is silent. Each complete line is spoken with
+    /// `speak_blocking` as soon as its newline arrives, so a line ending in
+    /// `?` gets its own question intonation. Text without a newline is held
+    /// until 4KB has built up, then spoken up to its last space, and whatever
+    /// is left at end of input is spoken last. Returns once everything has
+    /// finished playing, or early if `stop()` is called.
+    ///
+    /// Reading is paced by playback: the reader isn't read again until the
+    /// lines already received have been spoken. A writer faster than speech
+    /// therefore blocks on a full pipe rather than growing memory here, and
+    /// a reader that blocks (like stdin waiting for input) delays speech only
+    /// once the lines before it have been spoken.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_reader(std::io::stdin().lock()).unwrap();
+    /// ```
+    pub fn speak_reader<R: std::io::Read>(&self, mut reader: R) -> Result<(), Box<dyn std::error::Error>> {
+        let generation = self.stop_generation.load(Ordering::SeqCst);
+        let stopped = || self.stop_generation.load(Ordering::SeqCst) != generation;
+
+        let mut bytes = Vec::new();
+        let mut text = String::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            bytes.extend_from_slice(&chunk[..read]);
+            text.push_str(&drain_utf8(&mut bytes));
+
+            while let Some(newline) = text.find('\n') {
+                let line: String = text.drain(..=newline).collect();
+                self.speak_blocking(&line)?;
+                if stopped() {
+                    return Ok(());
+                }
+            }
+
+            // Don't wait forever for a newline that may never come
+            if text.len() >= MAX_PENDING_TEXT {
+                let split = text.rfind(char::is_whitespace).map_or(text.len(), |space| space + 1);
+                let words: String = text.drain(..split).collect();
+                self.speak_blocking(&words)?;
+                if stopped() {
+                    return Ok(());
+                }
+            }
+        }
+
+        // A sequence cut off by the end of input
+        if !bytes.is_empty() {
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+        self.speak_blocking(&text)
+    }
+
+    /// Compute the sprite plays `speak()` would make for text, without playing
+    ///
+    /// Useful for diagnosing letter mapping and intonation. Offsets are relative
+    /// to the start of the utterance; random pitch variation isn't included
+    /// since it's chosen at play time.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// for sound in engine.plan("hi [deska]?") {
+    ///     println!("{:?} at {:?}, {:+.2} semitones", sound.source, sound.offset, sound.intonation_shift);
+    /// }
+    /// ```
+    pub fn plan(&self, text: &str) -> Vec<PlannedSound> {
+        let profile = self.profile();
+        let intonation = resolve_contour(text, &profile);
+
+        plan_utterance(text, &profile, intonation)
+    }
+
+    /// Roughly how long `speak()` would take to play text, without playing
+    ///
+    /// The time from the first sprite starting to the last one ending, from
+    /// [`Animalese::plan`]. Sprites pitched far up run out of recording and
+    /// end early, so the real length can come out slightly shorter.
+    pub fn estimated_duration(&self, text: &str) -> Duration {
+        self.plan(text)
+            .iter()
+            .map(|sound| sound.offset + sound.length)
+            .max()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// The text `speak()` would voice, after the profile's transforms
+    ///
+    /// For displaying or debugging what's said. `speak()` and every other
+    /// speaking method run the same transforms, in this order:
+    ///
+    /// 1. Fullwidth forms and other scripts' digits fold to ASCII (always)
+    /// 2. Markup tags like `<pitch=+3>` and `<pause=250ms>` are removed,
+    ///    keeping the text they wrap (always)
+    /// 3. The asterisks of `*emphasis*` are removed (`markdown_emphasis`)
+    /// 4. Apostrophes inside words are dropped, so "don't" is "dont" (always)
+    /// 5. Letters past `max_word_length` in a word are dropped
+    /// 6. Long words are abbreviated (`speed_read`)
+    ///
+    /// Special sounds and voice switches stay as `[ok]` or `[m2]`. Spaces,
+    /// digits, punctuation and emoji are kept, even though they aren't voiced
+    /// as letters; `unmapped` and `emoji_sfx` decide what plays for them. The
+    /// crate doesn't spell out numbers, expand abbreviations or transliterate
+    /// other scripts, so those reach `speak()` as written, and acronyms
+    /// spelled out under `acronym_handling` only change the timing.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, VoiceProfile};
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_profile(VoiceProfile { speed_read: true, ..Default::default() });
+    /// assert_eq!(engine.normalize_text("<rate=2>notification</rate> received"), "ntfc rcvd");
+    /// ```
+    pub fn normalize_text(&self, text: &str) -> String {
+        normalize_text(text, &self.profile())
+    }
+
+    /// Describe the sounds `speak()` would play for text as a JSON timeline
+    ///
+    /// For tools that don't link the crate, like video or cutscene editors:
+    /// each sound's start, duration, final pitch and volume, and the sprite
+    /// it's cut from, built from the same data as [`Animalese::plan`].
+    /// Nothing is played. Random pitch variation and volume jitter aren't
+    /// included, since they're chosen at play time. The schema:
+    ///
+    /// ```json
+    /// {
+    ///   "duration_ms": 200.0,
+    ///   "sounds": [
+    ///     {"source": "letter", "name": "h", "sheet": "voice", "voice": "f1",
+    ///      "start_ms": 0.0, "duration_ms": 200.0, "sprite_start_ms": 1400.0,
+    ///      "pitch_semitones": 0.0, "volume": 0.65, "pan": 0.0}
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// `duration_ms` is [`Animalese::estimated_duration`]. `source` is
+    /// `"letter"`, `"special"` or `"sfx"` and `name` the letter or sound.
+    /// `sheet` is `"voice"` for sprites cut from the voice file `voice`
+    /// names, or `"sfx"` for `sfx.ogg`, with `voice` null. Times are in
+    /// milliseconds from the start of the utterance, except
+    /// `sprite_start_ms`, which is the sprite's position in its sheet.
+    /// Numbers that aren't finite are written as `null`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// std::fs::write("hello.json", engine.timeline_json("hello!")).unwrap();
+    /// ```
+    pub fn timeline_json(&self, text: &str) -> String {
+        timeline::to_json(&self.plan(text), &self.profile())
+    }
+
+    /// How many animation frames at `fps` it takes `speak()` to play text
+    ///
+    /// [`Animalese::estimated_duration`] in frames, rounded up so a partial
+    /// last frame counts: 250ms at 30 fps is 8 frames. Zero for text with
+    /// nothing to voice, or an `fps` of 0.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// println!("{} frames", engine.frame_count("hello there", 60));
+    /// ```
+    pub fn frame_count(&self, text: &str, fps: u32) -> usize {
+        frames_covering(self.estimated_duration(text), fps)
+    }
+
+    /// Which letter is sounding on each animation frame at `fps`, for lip sync
+    ///
+    /// One entry per frame of [`Animalese::frame_count`], from the same plan
+    /// `speak()` would play. Frame `i` starts `i / fps` seconds in and shows
+    /// the letter sounding at that instant, as written (case kept), or
+    /// `None` during pauses and special or SFX sounds. Letters overlap, so
+    /// the most recently started one wins. Rounding at frame boundaries: a
+    /// letter starting exactly on a frame's start shows on that frame, and
+    /// one ending exactly there doesn't, so a letter shorter than a frame can
+    /// be skipped entirely. Nothing is played.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// for (frame, letter) in engine.frames("hello", 30).iter().enumerate() {
+    ///     let mouth = if letter.is_some() { "open" } else { "closed" };
+    ///     println!("frame {frame}: {mouth}");
+    /// }
+    /// ```
+    pub fn frames(&self, text: &str, fps: u32) -> Vec<Option<char>> {
+        letter_frames(&self.plan(text), fps)
+    }
+
+    /// Count how often each letter sprite would play across a corpus
+    ///
+    /// For voice-pack QA: every letter `a` to `z` has an entry, so a zero
+    /// marks a slot the corpus never exercises. Counts follow what `speak()`
+    /// would actually play with the current profile (case-folded, and after
+    /// `max_word_length` and `speed_read`). Specials and SFX aren't counted.
+    /// Nothing is played.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let coverage = engine.sprite_coverage(&["the quick brown fox", "jumps over"]);
+    /// let missing: Vec<char> = ('a'..='z').filter(|c| coverage[c] == 0).collect();
+    /// println!("never played: {:?}", missing);
+    /// ```
+    pub fn sprite_coverage(&self, texts: &[&str]) -> HashMap<char, usize> {
+        let mut coverage: HashMap<char, usize> = ('a'..='z').map(|c| (c, 0)).collect();
+        for text in texts {
+            for sound in self.plan(text) {
+                if let SoundSource::Letter(c) = sound.source {
+                    *coverage.entry(c.to_ascii_lowercase()).or_default() += 1;
+                }
+            }
+        }
+        coverage
+    }
+
+    /// Compute the pitch contour `speak()` would give each letter of text
+    ///
+    /// Returns each voiced letter with its final pitch in semitones: the
+    /// profile's `pitch_shift` plus inline `<pitch>` markup plus the
+    /// intonation glide, without the random variation chosen at play time.
+    /// Special sounds aren't included. Nothing is played.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// for (letter, semitones) in engine.pitch_track("really?") {
+    ///     println!("{letter}: {semitones:+.2}");
+    /// }
+    /// ```
+    pub fn pitch_track(&self, text: &str) -> Vec<(char, f32)> {
+        let profile = self.profile();
+        let intonation = resolve_contour(text, &profile);
+
+        pitch_track(&plan_utterance(text, &profile, intonation), &profile)
+    }
+
+    /// Speak text at a pitch and a talking speed, set independently
+    ///
+    /// `pitch_semitones` sets the tone, replacing the profile's
+    /// `pitch_shift` (clamped to ±12), and `words_per_minute` sets the
+    /// delivery speed, replacing its `tempo`, both for this call only. Speed
+    /// never changes pitch or the other way around. Words per minute count
+    /// standard five-letter words, each followed by a space: at tempo 1.0 one
+    /// takes `5 × letter_gap + word_pause` (250ms with the defaults, or 240
+    /// WPM), so the tempo used is `wpm × (5 × letter_gap + word_pause) / 60s`,
+    /// which scales the letter gaps and word pauses alike. Tempo is limited
+    /// to 0.25..=4.0, 60 to 960 WPM with the defaults. Inline `<rate>` markup
+    /// still applies on top.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// // A low voice talking slowly, then a high one rattling along
+    /// engine.speak_at_rate("well, let me think", -4.0, 100.0).unwrap();
+    /// engine.speak_at_rate("oh oh I know this one", 5.0, 300.0).unwrap();
+    /// ```
+    pub fn speak_at_rate(&self, text: &str, pitch_semitones: f32, words_per_minute: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.profile();
+        let profile = VoiceProfile {
+            pitch_shift: pitch_semitones.clamp(-MAX_PITCH_SHIFT, MAX_PITCH_SHIFT),
+            tempo: tempo_for_wpm(words_per_minute, &profile),
+            ..profile
+        };
+        let intonation = resolve_contour(text, &profile);
+
+        self.speak_as(text, &profile, intonation)
+    }
+
+    /// Speak text with its own letter spacing
+    ///
+    /// `letter_gap` replaces the profile's `letter_gap` (50ms by default) for
+    /// this call only; the shared profile isn't modified, so the next line is
+    /// paced normally again. Tempo and `<rate>` markup still scale the gap.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    /// use std::time::Duration;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_paced("well... I suppose", Duration::from_millis(140)).unwrap();
+    /// engine.speak_paced("quick, hide!", Duration::from_millis(30)).unwrap();
+    /// ```
+    pub fn speak_paced(&self, text: &str, letter_gap: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = VoiceProfile { letter_gap, ..self.profile() };
+        let intonation = resolve_contour(text, &profile);
+
+        self.speak_as(text, &profile, intonation)
+    }
+
+    /// Speak text backwards, for a rewind or undo effect
+    ///
+    /// The letters play last to first with the timing and intonation mirrored.
+    /// Set `VoiceProfile::reverse_sprites` to also play each sprite backwards,
+    /// which sounds more like tape running in reverse.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, VoiceProfile};
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_profile(VoiceProfile { reverse_sprites: true, ..Default::default() });
+    /// engine.speak_reversed("oops, never mind").unwrap();
+    /// ```
+    pub fn speak_reversed(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.profile();
+        let intonation = resolve_contour(text, &profile);
+
+        self.speak_plan(&reverse_plan(&plan_utterance(text, &profile, intonation)), &profile)
+    }
+
+    /// Speak text with several voices at once, for a crowd or chorus effect
+    ///
+    /// Each voice speaks the whole text with the current profile (apart from
+    /// its voice type), all layered into one utterance. To thicken the sound,
+    /// every voice after the first is nudged by a fixed random delay of up to
+    /// 30ms and a fixed random pitch offset within `±detune` semitones; around
+    /// 0.2 to 0.5 sounds like a group rather than a mistake. Each layer is
+    /// quietened by `1/√n` for `n` voices to keep the mix from clipping. An
+    /// empty `voices` plays nothing.
+    ///
+    /// This relies on the mixer playing overlapping sounds, and every voice
+    /// decodes and resamples its own sprites, so the CPU cost and the number
+    /// of sounds in flight grow with the voice count. A handful of voices is
+    /// fine; dozens will start to stutter on slower machines. The lookahead
+    /// (see `set_lookahead`) counts sounds across all voices, so raise it with
+    /// the voice count if playback falls behind.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, VoiceType};
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_chorus("happy birthday!", &[VoiceType::F1, VoiceType::M2, VoiceType::F3], 0.3).unwrap();
+    /// ```
+    pub fn speak_chorus(&self, text: &str, voices: &[VoiceType], detune: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.profile();
+        let intonation = resolve_contour(text, &profile);
+        let plan = plan_utterance(text, &profile, intonation);
+        let layered = chorus_plan(&plan, voices, detune, &mut *self.rng.lock().unwrap());
+
+        // Layers sound together, so there are no voice changes to crossfade
+        let voice_profiles: Vec<VoiceProfile> = voices.iter()
+            .map(|&voice_type| VoiceProfile { voice_type, voice_change_fade: Duration::ZERO, ..profile.clone() })
+            .collect();
+        let profiles: Vec<&VoiceProfile> = layered.iter()
+            .map(|(voice, _)| &voice_profiles[*voice])
+            .collect();
+        let plan: Vec<PlannedSound> = layered.into_iter().map(|(_, sound)| sound).collect();
+
+        self.speak_layers(&plan, &profiles)
+    }
+
+    /// Speak each sentence of text in a different, randomly picked voice
+    ///
+    /// Sentences end at `.`, `!`, `?` or `…` followed by whitespace. Each is
+    /// voiced by a voice from `available_voices()` other than the previous
+    /// sentence's, with the rest of the profile unchanged, and gets its own
+    /// intonation, so a question mid-text still rises. Single-sentence input
+    /// is just spoken in one random voice. The voices depend only on `seed`
+    /// and the text, so the same seed gives the same cast every time (the
+    /// per-letter pitch variation is still random). Plays as one utterance
+    /// for `stop()`, `progress()` and `skip_ahead()`. Inline markup spans
+    /// shouldn't cross a sentence boundary, since each sentence is parsed
+    /// on its own, and `VoiceProfile::declination` drifts over each sentence
+    /// rather than the whole text.
+    ///
+    /// With `VoiceProfile::breath_sfx` set to a built-in sound effect name
+    /// (as used by `play_sfx()`), that sound plays between sentences as a
+    /// breath, unpitched at 30% of the profile's volume, and the next
+    /// sentence starts 300ms after it. Sound bank (`bank:sound`) and unknown
+    /// names are ignored, so there are no breaths.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_varied("Welcome! Who's next? Me! No, me.", 7).unwrap();
+    /// ```
+    pub fn speak_varied(&self, text: &str, seed: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.profile();
+        let sentences = split_sentences(text);
+        let voices = varied_voices(sentences.len(), &self.available_voices(), seed);
+
+        let sentence_plans = sentences.iter().zip(&voices)
+            .map(|(sentence, &voice_type)| {
+                let sentence_profile = VoiceProfile { voice_type, ..profile.clone() };
+                plan_utterance(sentence, &sentence_profile, resolve_contour(sentence, &sentence_profile))
+            })
+            .collect();
+        let (plan, sentence_of) = join_sentences(sentence_plans, profile.breath_sfx.as_deref());
+
+        let voice_profiles: Vec<VoiceProfile> = voices.iter()
+            .map(|&voice_type| VoiceProfile { voice_type, ..profile.clone() })
+            .collect();
+        let profiles: Vec<&VoiceProfile> = sentence_of.iter().map(|&index| &voice_profiles[index]).collect();
+        self.speak_layers(&plan, &profiles)
+    }
+
+    /// Speak letters for exactly the given durations, back to back
+    ///
+    /// For audio locked to an animation's frame timings: each letter starts
+    /// the moment the previous item's duration is up, with no letter gap,
+    /// word pauses or intonation added. A duration longer than the 200ms
+    /// letter sprite is clamped to it, leaving silence for the rest, rather
+    /// than looping the sprite. Characters that aren't ASCII letters are
+    /// silent for their duration. The profile's pitch, variation and volume
+    /// apply as usual.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    /// use std::time::Duration;
+    ///
+    /// let frame = Duration::from_secs_f64(1.0 / 24.0);
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_timed(&[('h', frame * 2), ('i', frame * 3), (' ', frame), ('!', frame)]).unwrap();
+    /// ```
+    pub fn speak_timed(&self, items: &[(char, Duration)]) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.profile();
+        self.speak_plan(&plan_timed(items, &profile), &profile)
+    }
+
+    /// Speak a number in English words with the current profile
+    ///
+    /// For timers and countdowns: `1234` is spoken as "one thousand two
+    /// hundred thirty four" and `-7` as "minus seven", then voiced like any
+    /// text passed to `speak()`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// for n in (1..=3).rev() {
+    ///     engine.speak_number(n).unwrap();
+    /// }
+    /// ```
+    pub fn speak_number(&self, n: i64) -> Result<(), Box<dyn std::error::Error>> {
+        self.speak(&number_words(n))
+    }
+
+    /// Speak text while its stereo position sweeps from `from` to `to`
+    ///
+    /// For a character walking across the screen as they talk. Pans run from
+    /// -1.0 (hard left) through 0.0 (centre) to 1.0 (hard right) and are
+    /// interpolated across the sounds of the utterance the way intonation is
+    /// across letters: the first sits at `from`, the last at `to`. These
+    /// replace the profile's `pan` for this call only. Panning uses kira's
+    /// equal-power law, so loudness stays even across the sweep (a sound
+    /// hard to one side is √2 louder on that side than either channel at
+    /// the centre). Panning scales each channel of the stereo sprites; mono
+    /// voice packs are upmixed to stereo when decoded, so they pan the same.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_panning("coming through!", -1.0, 1.0).unwrap();
+    /// ```
+    pub fn speak_panning(&self, text: &str, from: f32, to: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = VoiceProfile { pan: 0.0, ..self.profile() };
+        let mut plan = plan_utterance(text, &profile, resolve_contour(text, &profile));
+        pan_plan(&mut plan, from.clamp(-1.0, 1.0), to.clamp(-1.0, 1.0));
+        self.speak_plan(&plan, &profile)
+    }
+
+    /// Speak text with sprites from another voice pack, just this once
+    ///
+    /// `assets_path` is a voice directory with `sfx.ogg` next to it, as for
+    /// [`Animalese::with_custom_assets`], or a pack's `voices.toml` (see
+    /// [`Animalese::from_manifest`]), whose file names and trims are then
+    /// used. The engine's voice trims don't apply to the other pack, and its
+    /// own pack, profile and trims are left as they are. Everything else
+    /// works as for `speak()`; a [`Animalese::replay_last`]
+    /// afterwards replays from the engine's own pack.
+    ///
+    /// Sprites aren't cached, so this costs no more per sprite than
+    /// `speak()`, which also decodes the voice file for every sound. The
+    /// extra cost is checking the pack exists, and reading its manifest.
+    ///
+    /// # Errors
+    ///
+    /// [`AnimaleseError::AssetNotFound`] if the pack's voice directory or
+    /// `sfx.ogg` is missing, or the manifest's errors, before anything plays.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    /// use std::path::Path;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_from_assets(Path::new("./guest_pack/voice"), "a visitor!").unwrap();
+    /// ```
+    pub fn speak_from_assets(&self, assets_path: &Path, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let assets = AssetPack::load(assets_path)?;
+        let profile = self.profile();
+        let plan = plan_utterance(text, &profile, resolve_contour(text, &profile));
+        self.speak_layers_from(&assets, &plan, &vec![&profile; plan.len()])
+    }
+
+    /// Speak text as a tune, pitching each letter by a scale
+    ///
+    /// The i-th voiced letter is shifted by `scale_semitones[i % len]`
+    /// semitones, so a scale shorter than the text cycles from its start
+    /// again: `[0.0, 2.0, 4.0]` over "hello" gives 0, 2, 4, 0, 2. The scale
+    /// replaces intonation and declination for this call, while the profile's
+    /// `pitch_shift`, inline `<pitch>` markup and random `pitch_variation`
+    /// still add on top (set the variation to 0.0 for an exact tune), and
+    /// the result stays within the profile's pitch floor and ceiling.
+    /// Special sounds aren't pitched by the scale. An empty scale speaks the
+    /// text flat.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// // Up and down a major scale
+    /// engine.speak_melody("do re mi fa so", &[0.0, 2.0, 4.0, 5.0, 7.0, 5.0, 4.0, 2.0]).unwrap();
+    /// ```
+    pub fn speak_melody(&self, text: &str, scale_semitones: &[f32]) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = VoiceProfile { intonation: 0.0, declination: 0.0, ..self.profile() };
+        let mut plan = plan_utterance(text, &profile, 0.0);
+        melody_plan(&mut plan, scale_semitones);
+        self.speak_plan(&plan, &profile)
+    }
+
+    /// Speak text with an intonation preset
+    ///
+    /// The preset replaces the profile's intonation for this call only; the
+    /// shared profile isn't modified. See [`Intonation`] for the pitch each
+    /// preset produces.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, Intonation};
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_with_intonation("Really", Intonation::Question).unwrap();
+    /// engine.speak_with_intonation("Hmm", Intonation::Custom(-0.8)).unwrap();
+    /// ```
+    pub fn speak_with_intonation(&self, text: &str, intonation: Intonation) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.effective_profile_for(intonation);
+        let shape = match intonation {
+            Intonation::Question => profile.question_shape,
+            _ => QuestionShape::Rising,
+        };
+        self.speak_as(text, &profile, Contour { glide: profile.intonation, shape })
+    }
+
+    /// The profile `speak_with_intonation()` and the emotion helpers speak with
+    ///
+    /// The current profile with the preset's pitch boost added to
+    /// `pitch_shift` and its glide as `intonation`, replacing the profile's
+    /// own; see [`Intonation::pitch_boost`] and [`Intonation::glide`]. Handy
+    /// for seeing why `speak_excited()` sounds the way it does without
+    /// playing anything.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{Animalese, Intonation};
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// let excited = engine.effective_profile_for(Intonation::Excited);
+    /// println!("{} semitones, glide {}", excited.pitch_shift, excited.intonation);
+    /// ```
+    pub fn effective_profile_for(&self, intonation: Intonation) -> VoiceProfile {
+        let mut profile = self.profile();
+        profile.pitch_shift += intonation.pitch_boost();
+        profile.intonation = intonation.glide();
+        profile
+    }
+
+    /// Speak text with rising intonation (for questions)
+    ///
+    /// Automatically applies a rising pitch contour, perfect for questions
+    /// or uncertain statements. Set `VoiceProfile::question_shape` to
+    /// [`QuestionShape::RisingFalling`] for a rise that dips at the end, as
+    /// in "where did it go?".
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_question("What's that").unwrap();
+    /// ```
+    pub fn speak_question(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.speak_with_intonation(text, Intonation::Question)
+    }
+
+    /// Speak text with excitement (higher pitch, rising intonation)
+    ///
+    /// Applies higher pitch and rising intonation for excited or enthusiastic
+    /// speech. Great for exclamations!
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_excited("Amazing!").unwrap();
+    /// ```
+    pub fn speak_excited(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.speak_with_intonation(text, Intonation::Excited)
+    }
+
+    /// Speak text with falling intonation (for statements)
+    ///
+    /// Applies a gentle falling pitch contour, typical of declarative
+    /// statements and confident assertions.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak_statement("I see").unwrap();
+    /// ```
+    pub fn speak_statement(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.speak_with_intonation(text, Intonation::Statement)
+    }
+
+    /// How far through the current utterance playback is, from 0.0 to 1.0
+    ///
+    /// Counts the sprites of the utterance `speak()` is playing that have
+    /// started so far, so it moves in per-letter steps rather than smoothly
+    /// per sample, and reaches 1.0 when the last letter starts. Returns 0.0
+    /// when nothing is being spoken. With several threads speaking at once it
+    /// follows whichever utterance started most recently.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    /// use std::sync::Arc;
+    ///
+    /// let engine = Arc::new(Animalese::new().unwrap());
+    /// let speaker = engine.clone();
+    /// std::thread::spawn(move || speaker.speak("a long line of dialogue").is_ok());
+    ///
+    /// std::thread::sleep(std::time::Duration::from_millis(200));
+    /// println!("{:.0}% done", engine.progress() * 100.0);
+    /// ```
+    pub fn progress(&self) -> f32 {
+        self.utterance.lock().unwrap().as_ref().map_or(0.0, Utterance::progress)
+    }
+
+    /// Whether anything is playing or being spoken
+    ///
+    /// True while `speak()` is working through an utterance (including the
+    /// gaps between letters) or any sound, such as a letter or SFX, hasn't
+    /// finished yet. The SFX loop and the keepalive signal don't count, just
+    /// as they don't keep `set_on_idle()` or `try_speak()` waiting.
+    pub fn is_playing(&self) -> bool {
+        is_busy(&self.utterance, &self.active_sounds)
+    }
+
+    /// Jump ahead in the utterance `speak()` is currently playing
+    ///
+    /// Drops the next `letters` sprites that haven't started yet (letters,
+    /// specials and emoji SFX each count as one) and moves the rest of the
+    /// utterance up, so the sprite after them plays right away. Sprites already
+    /// sounding finish normally. Skipping past the end finishes the utterance,
+    /// and `speak()` returns as if it had played everything.
+    ///
+    /// Like `progress()`, this applies to the utterance that started most
+    /// recently and does nothing when nothing is being spoken; skips requested
+    /// before `speak()` starts are not carried over. Requests add up until the
+    /// speaking thread picks them up, which it does within about 10ms.
+    /// `stop()` cancels the utterance outright, pending skips included; there
+    /// is no pause, so a skip always applies to live playback.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    /// use std::sync::Arc;
+    ///
+    /// let engine = Arc::new(Animalese::new().unwrap());
+    /// let speaker = engine.clone();
+    /// let line = std::thread::spawn(move || speaker.speak("a long line of dialogue").is_ok());
+    ///
+    /// // The player pressed skip
+    /// engine.skip_ahead(10);
+    /// line.join().unwrap();
+    /// ```
+    pub fn skip_ahead(&self, letters: usize) {
+        if let Some(utterance) = self.utterance.lock().unwrap().as_mut() {
+            utterance.pending_skip += letters;
+        }
+    }
+
+    /// Get how many sprites `speak()` schedules ahead of playback
+    pub fn lookahead(&self) -> usize {
+        self.lookahead.load(Ordering::Relaxed)
+    }
+
+    /// Set how many sprites `speak()` schedules ahead of playback (at least 1)
+    ///
+    /// Sprites are handed to the mixer with sample-accurate start times a few
+    /// letters before they play, rather than all at once, so very long text
+    /// never floods the mixer and `stop()` only has a handful of sounds to
+    /// cancel. Larger values tolerate a busier calling thread; the default is 8.
+    pub fn set_lookahead(&self, sprites: usize) {
+        self.lookahead.store(sprites.max(1), Ordering::Relaxed);
+    }
+
+    /// How many sounds are playing right now
+    ///
+    /// Counts letters, specials, SFX, clips and `play_samples()` audio that
+    /// have started and haven't finished. Sprites `speak()` has scheduled
+    /// ahead (see [`Animalese::set_lookahead`]) count once they start, and
+    /// the SFX loop and keepalive aren't counted.
+    pub fn concurrent_sounds(&self) -> usize {
+        let now = Instant::now();
+        let mut active = self.active_sounds.lock().unwrap();
+        active.retain(|sound| sound.handle.state() != kira::sound::PlaybackState::Stopped);
+        active.iter().filter(|sound| sound.starts <= now).count()
+    }
+
+    /// Get the cap on concurrent sounds, if any
+    pub fn max_concurrent_sounds(&self) -> Option<usize> {
+        match self.max_sounds.load(Ordering::Relaxed) {
+            0 => None,
+            max => Some(max),
+        }
+    }
+
+    /// Cap how many sounds can play at once (at least 1), or `None` for no cap
+    ///
+    /// Guards the mixer against a runaway caller stacking up dozens of
+    /// overlapping sprites, which clips and can exhaust kira's sound
+    /// capacity. A sound that would go over the cap is dropped, not queued:
+    /// `play_letter()` and friends return `Ok` without playing, and `speak()`
+    /// skips that sprite and carries on with the next, so the utterance
+    /// keeps its timing. The count is [`concurrent_sounds()`](Self::concurrent_sounds),
+    /// taken when a sound is started, so sprites `speak()` scheduled ahead
+    /// or several threads starting sounds at once can overshoot it by a few
+    /// sounds. No cap by default.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_max_concurrent_sounds(Some(16));
+    /// ```
+    pub fn set_max_concurrent_sounds(&self, max: Option<usize>) {
+        self.max_sounds.store(max.map_or(0, |max| max.max(1)), Ordering::Relaxed);
+    }
+
+    /// Whether another sound fits under the concurrent sound cap
+    fn has_room(&self) -> bool {
+        let Some(max) = self.max_concurrent_sounds() else {
+            return true;
+        };
+        let playing = self.concurrent_sounds();
+        if playing >= max {
+            event!(debug, logging::PLAYBACK, "sound dropped: {} of {} already playing", playing, max);
+            return false;
+        }
+        true
+    }
+
+    /// Whether speech and sound effects are allowed to play
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Gate all playback on or off, e.g. while an NPC's dialogue box is open
+    ///
+    /// While disabled, `speak()` and its variants, `play_letter()`,
+    /// `play_special()`, the `play_sfx` family and `play_clip()` return `Ok`
+    /// without queuing anything, so callers don't need to check first. Unlike
+    /// a volume of zero, nothing is mixed at all. Disabling doesn't cut off
+    /// sounds already playing or an utterance in progress; call `stop()` for
+    /// that. Offline rendering is unaffected. Enabled by default.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_enabled(false);
+    /// engine.speak("nobody hears this").unwrap();
+    /// engine.set_enabled(true);
+    /// ```
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        event!(debug, logging::PLAYBACK, "playback {}", if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// Whether a sound played now would be heard
+    ///
+    /// False while playback is disabled with `set_enabled(false)`, or when
+    /// the effective volume (the profile voice's trim times the profile
+    /// volume times the master volume, mid-fade if one is running) is below
+    /// -60 dB. This can't see past the
+    /// backend: an engine on the default device assumes the device is
+    /// listened to, and a [`StreamBackend`] engine assumes its samples are
+    /// pulled and played. Effects that can silence a single sound, like
+    /// the profile's pitch floor or filter, aren't considered.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// if !engine.is_audible() {
+    ///     println!("hello");
+    /// }
+    /// ```
+    pub fn is_audible(&self) -> bool {
+        let profile = self.profile();
+        let volume = trimmed_volume(profile.volume, self.voice_trim(profile.voice_type));
+        self.is_enabled() && volume * self.master_volume() >= AUDIBLE_VOLUME
+    }
+
+    /// Reseed the random pitch and volume variation, for repeatable output
+    ///
+    /// Engines start from a random seed. After `set_seed`, the same calls
+    /// with the same profile draw the same per-letter pitch variation,
+    /// volume jitter and chorus detune, so two renders of a line match
+    /// sample for sample. The sequence carries on across calls, so reseed
+    /// before each line to repeat it. `speak_varied()` picks its voices from
+    /// its own seed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_seed(42);
+    /// engine.speak("same every time").unwrap();
+    /// ```
+    pub fn set_seed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+    }
+
+    /// Call `callback` each time the engine finishes everything it was playing
+    ///
+    /// Fires once per busy-to-idle transition: when the last queued sound of
+    /// `speak()`, `play_letter()`, `play_sfx()` and friends has finished and no
+    /// utterance is still being scheduled, and not again until something else
+    /// plays and finishes. `stop()` counts as finishing. The SFX loop and the
+    /// keepalive signal don't keep the engine busy.
+    ///
+    /// The callback runs on a background watcher thread, started on the first
+    /// call and stopped when the engine is dropped, within about 10ms of the
+    /// engine going idle; hence the `Send` bound. No engine locks are held
+    /// while it runs, so it may call back into a shared engine, but it should
+    /// return promptly since later transitions wait for it. Setting a new
+    /// callback replaces the old one.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_on_idle(Box::new(|| println!("done talking, cue the music")));
+    /// engine.speak("see you tomorrow").unwrap();
+    /// ```
+    pub fn set_on_idle(&self, callback: Box<dyn Fn() + Send>) {
+        *self.on_idle.lock().unwrap() = Some(callback);
+        // The slot is empty while the callback runs, so it can't tell us
+        // whether a watcher exists
+        if !self.idle_watcher_started.swap(true, Ordering::SeqCst) {
+            self.watch_idle();
+        }
+    }
+
+    /// Start the thread behind `set_on_idle()`
+    ///
+    /// It only holds weak references, so it exits once the engine is dropped.
+    fn watch_idle(&self) {
+        let active_sounds = Arc::downgrade(&self.active_sounds);
+        let utterance = Arc::downgrade(&self.utterance);
+        let on_idle = Arc::downgrade(&self.on_idle);
+
+        std::thread::spawn(move || {
+            let mut was_busy = false;
+            loop {
+                let (Some(active_sounds), Some(utterance), Some(on_idle)) =
+                    (active_sounds.upgrade(), utterance.upgrade(), on_idle.upgrade())
+                else {
+                    return;
+                };
+
+                let busy = is_busy(&utterance, &active_sounds);
+
+                if was_busy && !busy {
+                    event!(debug, logging::PLAYBACK, "engine idle");
+                    // Call without the lock held, so the callback can replace itself
+                    let callback = on_idle.lock().unwrap().take();
+                    if let Some(callback) = callback {
+                        callback();
+                        on_idle.lock().unwrap().get_or_insert(callback);
+                    }
+                }
+                was_busy = busy;
+
+                drop((active_sounds, utterance, on_idle));
+                std::thread::sleep(IDLE_POLL);
+            }
+        });
+    }
+
+    /// Output buffer size in frames set with [`AnimaleseBuilder::buffer_size`]
+    ///
+    /// This is the size after fitting it to the device's supported range.
+    /// `None` means the device chose its own, as it does by default and for
+    /// engines made with [`Animalese::with_audio_manager`].
+    pub fn buffer_size(&self) -> Option<u32> {
+        self.buffer_size
+    }
+
+    /// How many times the output has underrun since the engine was created
+    ///
+    /// An underrun is the device running out of audio because the mixer
+    /// didn't render it in time, usually heard as a crackle or dropout on a
+    /// loaded system; it's a different problem from clipping, which a quieter
+    /// master volume fixes. New underruns are also logged as warnings on the
+    /// `animalese::playback` target when this is called and when an
+    /// utterance starts, with the `log` feature on.
+    ///
+    /// Neither kira nor cpal reports underruns, so they're inferred from the
+    /// mixer's clock falling behind the wall clock by more than 50ms past
+    /// the swing of rendering a whole device buffer at once, so large
+    /// buffers and slow clock drift don't count. That misses shorter
+    /// dropouts, such as a single late buffer of a few milliseconds, and
+    /// counts a long one once. Engines whose mixer is
+    /// paused, like a [`StreamBackend`] nobody pulls from for a while, count
+    /// the pause as an underrun.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.speak("hello").unwrap();
+    /// if engine.underrun_count() > 0 {
+    ///     eprintln!("audio dropped out; try a larger buffer_size");
+    /// }
+    /// ```
+    pub fn underrun_count(&self) -> u64 {
+        let count = self.capture_state.underruns.load(Ordering::Relaxed);
+        let reported = self.reported_underruns.swap(count, Ordering::Relaxed);
+        if count > reported {
+            event!(warn, logging::PLAYBACK, "{} audio underruns ({} in total); the system may be overloaded",
+                count - reported, count);
+        }
+        count
+    }
+
+    /// Whether the keepalive signal is playing
+    pub fn keepalive(&self) -> bool {
+        self.keepalive.lock().unwrap().is_some()
+    }
+
+    /// Keep the output device awake between utterances
+    ///
+    /// Some output devices (notably Bluetooth and HDMI) power down after a
+    /// stretch of silence, so the first sprite after a pause gets clipped or
+    /// delayed. With keepalive on, an inaudible signal (about -90 dBFS) loops
+    /// in the background so the device never sees pure silence. It costs a
+    /// little power, so it's off by default. `stop()` leaves it running.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::Animalese;
+    ///
+    /// let engine = Animalese::new().unwrap();
+    /// engine.set_keepalive(true).unwrap();
+    /// ```
+    pub fn set_keepalive(&self, enabled: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let mut keepalive = self.keepalive.lock().unwrap();
+        match (enabled, keepalive.as_mut()) {
+            (true, None) => {
+                let handle = self.manager.lock().unwrap().play(keepalive_sound())?;
+                *keepalive = Some(handle);
+                event!(debug, logging::PLAYBACK, "keepalive on");
+            }
+            (false, Some(handle)) => {
+                handle.stop(Tween::default());
+                *keepalive = None;
+                event!(debug, logging::PLAYBACK, "keepalive off");
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Speak text with a per-utterance profile and resolved intonation
+    ///
+    /// Returns early, without error, if `stop()` is called meanwhile.
+    fn speak_as(&self, text: &str, profile: &VoiceProfile, intonation: impl Into<Contour>) -> Result<(), Box<dyn std::error::Error>> {
+        self.speak_plan(&plan_utterance(text, profile, intonation), profile)
+    }
+
+    /// Speak an already planned utterance with a per-utterance profile
+    ///
+    /// Returns early, without error, if `stop()` is called meanwhile.
+    fn speak_plan(&self, plan: &[PlannedSound], profile: &VoiceProfile) -> Result<(), Box<dyn std::error::Error>> {
+        self.speak_layers(plan, &vec![profile; plan.len()])
+    }
+
+    /// Speak an already planned utterance, with a profile for each sound
+    ///
+    /// Returns early, without error, if `stop()` is called meanwhile. An
+    /// empty plan (text with nothing to voice) is a no-op: it doesn't become
+    /// the current utterance, so `progress()` and `set_on_idle()` never see it.
+    fn speak_layers(&self, plan: &[PlannedSound], profiles: &[&VoiceProfile]) -> Result<(), Box<dyn std::error::Error>> {
+        self.speak_layers_from(&self.assets, plan, profiles)
+    }
+
+    /// Speak an already planned utterance with sprites from `assets`
+    ///
+    /// Like [`Animalese::speak_layers`], for a pack other than the engine's.
+    fn speak_layers_from(&self, assets: &AssetPack, plan: &[PlannedSound], profiles: &[&VoiceProfile]) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.is_enabled() {
+            event!(debug, logging::SPEECH, "utterance discarded: playback disabled");
+            return Ok(());
+        }
+        if plan.is_empty() {
+            return Ok(());
+        }
+        *self.last_spoken.lock().unwrap() = Some(Arc::new(SpokenUtterance::new(plan, profiles)));
+        self.underrun_count();
+        let generation = self.stop_generation.load(Ordering::SeqCst);
+        let started = Instant::now();
+        event!(info, logging::SPEECH, "utterance started: {} sounds over {:?}",
+            plan.len(), plan.last().map_or(Duration::ZERO, |sound| sound.offset));
+
+        *self.utterance.lock().unwrap() = Some(Utterance {
+            started,
+            offsets: plan.iter().map(|sound| sound.offset).collect(),
+            skipped: Duration::ZERO,
+            pending_skip: 0,
+        });
+        let result = self.play_plan(assets, plan, profiles, started, generation);
+
+        match &result {
+            Ok(()) if self.stop_generation.load(Ordering::SeqCst) != generation => {
+                event!(debug, logging::SPEECH, "utterance stopped after {:?}", started.elapsed())
+            }
+            Ok(()) => event!(debug, logging::SPEECH, "utterance finished after {:?}", started.elapsed()),
+            Err(e) => event!(warn, logging::SPEECH, "utterance failed: {}", e),
+        }
+
+        // Leave a newer utterance from another thread alone
+        let mut utterance = self.utterance.lock().unwrap();
+        if utterance.as_ref().is_some_and(|u| u.started == started) {
+            *utterance = None;
+        }
+        result
+    }
+
+    /// Schedule a plan's sprites against `started`, returning early if stopped
+    ///
+    /// `profiles` holds the profile to play each planned sound with, and
+    /// `assets` the pack to cut the sprites from.
+    fn play_plan(&self, assets: &AssetPack, plan: &[PlannedSound], profiles: &[&VoiceProfile], started: Instant, generation: u64) -> Result<(), Box<dyn std::error::Error>> {
+        // Check SFX names first, so an unknown one fails before anything plays
+        for sound in plan {
+            if let SoundSource::Sfx(name) = &sound.source {
+                self.resolve_sfx(assets, name)?;
+            }
+        }
+
+        // Time jumped over by skip_ahead(), which moves the rest of the plan up
+        let mut skipped = Duration::ZERO;
+        let fades = Self::plan_voice_fades(plan, profiles);
+        let mut index = 0;
+        while index < plan.len() {
+            let due = schedule_at(plan, index, self.lookahead());
+            if due > started.elapsed() + skipped {
+                event!(trace, logging::SPEECH, "lookahead full, waiting {:?}", due - (started.elapsed() + skipped));
+            }
+            match self.wait_until(started, skipped, due, generation) {
+                Wait::Stopped => return Ok(()),
+                Wait::Skip(count) => {
+                    index = self.skip_sprites(plan, started, &mut skipped, index, count);
+                    continue;
+                }
+                Wait::Due => {}
+            }
+
+            if !self.has_room() {
+                index += 1;
+                continue;
+            }
+            let sound = &plan[index];
+            let delay = sound.offset.saturating_sub(started.elapsed() + skipped);
+            event!(trace, logging::SPEECH, "queued {:?} at {:?} (in {:?})", sound.source, sound.offset, delay);
+            let handle = self.play_planned_faded(assets, profiles[index], sound, fades.get(index), delay)?;
+            if !self.keep_sprite(handle, (started, index), Instant::now() + delay, generation) {
+                return Ok(());
+            }
+            index += 1;
+        }
+
+        // Block until the last sprite has started, like unscheduled playback
+        if let Some(last) = plan.last() {
+            while let Wait::Skip(count) = self.wait_until(started, skipped, last.offset, generation) {
+                self.skip_sprites(plan, started, &mut skipped, index, count);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the next `count` sprites of a plan that haven't started
+    ///
+    /// Dropped sprites already waiting in the mixer are cancelled, and the
+    /// clock jumps so the sprite after them is due now. Sprites queued past
+    /// the dropped ones are cancelled too, to be queued again on the new
+    /// clock. Returns the index to carry on queueing from.
+    fn skip_sprites(&self, plan: &[PlannedSound], started: Instant, skipped: &mut Duration, queued: usize, count: usize) -> usize {
+        let now = started.elapsed() + *skipped;
+        let first = plan.partition_point(|sound| sound.offset <= now).min(queued);
+        let end = (first + count).min(plan.len());
+        event!(debug, logging::SPEECH, "skipping {} sprites from {}", end - first, first);
+
+        for sound in self.active_sounds.lock().unwrap().iter_mut() {
+            if let Some((utterance, index)) = sound.sprite
+                && utterance == started
+                && (first..queued).contains(&index)
+            {
+                sound.handle.stop(Tween::default());
+            }
+        }
+
+        let target = plan.get(end).or(plan.last()).map_or(now, |sound| sound.offset);
+        *skipped += target.saturating_sub(now);
+        if let Some(utterance) = self.utterance.lock().unwrap().as_mut()
+            && utterance.started == started
+        {
+            utterance.skipped = *skipped;
+        }
+        end
+    }
+
+    /// Sleep until `due` on the utterance clock
+    ///
+    /// The clock is time since `started` plus what `skip_ahead()` jumped over.
+    /// Returns early if stopped, or if a skip is requested for the utterance.
+    fn wait_until(&self, started: Instant, skipped: Duration, due: Duration, generation: u64) -> Wait {
+        loop {
+            if self.stop_generation.load(Ordering::SeqCst) != generation {
+                return Wait::Stopped;
+            }
+            if let Some(utterance) = self.utterance.lock().unwrap().as_mut()
+                && utterance.started == started
+                && utterance.pending_skip > 0
+            {
+                return Wait::Skip(std::mem::take(&mut utterance.pending_skip));
+            }
+            let Some(remaining) = due.checked_sub(started.elapsed() + skipped) else {
+                return Wait::Due;
+            };
+            self.drain_capture();
+            // Wake regularly so stop() and skip_ahead() are noticed promptly
+            std::thread::sleep(remaining.min(Duration::from_millis(10)));
+        }
+    }
+
+    /// Wait until every sound has finished, returning false if stopped meanwhile
+    fn wait_for_silence(&self, generation: u64) -> bool {
+        loop {
+            if self.stop_generation.load(Ordering::SeqCst) != generation {
+                return false;
+            }
+            if self.is_silent() {
+                return true;
+            }
+            self.drain_capture();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Whether every sound has finished playing
+    fn is_silent(&self) -> bool {
+        let mut active = self.active_sounds.lock().unwrap();
+        active.retain(|sound| sound.handle.state() != kira::sound::PlaybackState::Stopped);
+        active.is_empty()
+    }
+
+    /// Hold on to a playing sound so `stop()` and `is_silent()` can see it
+    fn keep_sound(&self, handle: StaticSoundHandle, sprite: Option<(Instant, usize)>) {
+        let mut active = self.active_sounds.lock().unwrap();
+        active.push(ActiveSound { handle, sprite, starts: Instant::now() });
+
+        // Clean up finished sounds
+        active.retain(|sound| sound.handle.state() != kira::sound::PlaybackState::Stopped);
+    }
+
+    /// Hold on to a sprite of an utterance, unless `stop()` has been called
+    ///
+    /// A `stop()` landing while the sprite was being started has already
+    /// cleared the active sounds, so the sprite is stopped here instead of
+    /// playing on untracked. Checking under the lock closes the gap: `stop()`
+    /// bumps the generation before it takes the lock. Returns whether the
+    /// sprite was kept.
+    fn keep_sprite(&self, mut handle: StaticSoundHandle, sprite: (Instant, usize), starts: Instant, generation: u64) -> bool {
+        let mut active = self.active_sounds.lock().unwrap();
+        if self.stop_generation.load(Ordering::SeqCst) != generation {
+            handle.stop(Tween::default());
+            return false;
+        }
+        active.push(ActiveSound { handle, sprite: Some(sprite), starts });
+        active.retain(|sound| sound.handle.state() != kira::sound::PlaybackState::Stopped);
+        true
+    }
+
+    /// Move recorded frames out of the capture tap's buffer
+    fn drain_capture(&self) {
+        let mut captured = self.captured.lock().unwrap();
+        let (consumer, frames) = &mut *captured;
+        frames.extend(consumer.pop_iter());
+    }
+
+    /// Voice crossfades for a plan played with the given profiles
+    ///
+    /// Empty when the first profile's `voice_change_fade` is zero.
+    fn plan_voice_fades(plan: &[PlannedSound], profiles: &[&VoiceProfile]) -> Vec<VoiceFade> {
+        if profiles.first().is_none_or(|profile| profile.voice_change_fade.is_zero()) {
+            return Vec::new();
+        }
+        let voices: Vec<Option<VoiceType>> = plan.iter().zip(profiles)
+            .map(|(sound, profile)| match sound.source {
+                SoundSource::Sfx(_) => None,
+                SoundSource::Letter(_) | SoundSource::Special(_) => Some(sound.voice.unwrap_or(profile.voice_type)),
+            })
+            .collect();
+        voice_fades(plan, &voices)
+    }
+
+    /// Start one planned sound after `delay`, crossfading it for a voice change
+    ///
+    /// `fade` is the sound's entry from [`Animalese::plan_voice_fades`], if any.
+    fn play_planned_faded(&self, assets: &AssetPack, profile: &VoiceProfile, sound: &PlannedSound, fade: Option<&VoiceFade>, delay: Duration) -> Result<StaticSoundHandle, Box<dyn std::error::Error>> {
+        let Some(fade) = fade else {
+            return self.play_planned(assets, profile, sound, delay);
+        };
+        let crossfade = profile.voice_change_fade;
+        let mut handle = if fade.fade_in {
+            let profile = VoiceProfile { fade_in: profile.fade_in.max(crossfade), ..profile.clone() };
+            self.play_planned(assets, &profile, sound, delay)?
+        } else {
+            self.play_planned(assets, profile, sound, delay)?
+        };
+        // Ramp the volume rather than stopping: a stop sent before the sound
+        // starts would replace its fade-in and silence it
+        if let Some(after) = fade.fade_out_after {
+            handle.set_volume(Volume::Amplitude(0.0), Tween {
+                start_time: StartTime::Delayed(delay + after),
+                duration: crossfade,
+                ..Default::default()
+            });
+        }
+        Ok(handle)
+    }
+
+    /// Start one planned sound from `assets` with the given profile after `delay`
+    fn play_planned(&self, assets: &AssetPack, profile: &VoiceProfile, sound: &PlannedSound, delay: Duration) -> Result<StaticSoundHandle, Box<dyn std::error::Error>> {
+        let mut profile = profile.clone();
+        profile.pitch_shift += sound.pitch_offset + sound.intonation_shift;
+        profile.volume *= sound.volume;
+        if let Some(voice) = sound.voice {
+            profile.voice_type = voice;
+        }
+        profile.pan = (profile.pan + sound.pan).clamp(-1.0, 1.0);
+
+        let sfx_path;
+        let (sheet, window) = match &sound.source {
+            SoundSource::Sfx(name) => {
+                let (path, found) = self.resolve_sfx(assets, name)?;
+                sfx_path = path;
+                (Sheet::Sfx(&sfx_path), SpriteWindow { length: sound.length.min(found.slot), ..found })
+            }
+            SoundSource::Letter(_) | SoundSource::Special(_) => {
+                let slot = sprite_slot_end(&sound.source)
+                    .map_or(sound.length, |end| end.saturating_sub(sound.sprite_start));
+                (Sheet::Voice(assets), SpriteWindow { start: sound.sprite_start, length: sound.length, slot })
+            }
+        };
+        self.start_sprite(&profile, sheet, window, sound.pitched, delay)
+    }
+
+    /// Internal method to play a sprite with kira
+    fn play_sprite(&self, profile: &VoiceProfile, sheet: Sheet, window: SpriteWindow, apply_pitch: bool, delay: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.is_enabled() || !self.has_room() {
+            return Ok(());
+        }
+        let handle = self.start_sprite(profile, sheet, window, apply_pitch, delay)?;
+        self.keep_sound(handle, None);
+        Ok(())
+    }
+
+    /// Start a sprite with kira, returning its handle
+    ///
+    /// Pitched sprites use the profile pitch plus random variation; callers
+    /// fold intonation into the profile's `pitch_shift`. The window's length
+    /// is how long the sprite sounds, whatever the pitch; see
+    /// [`SpriteWindow::source_length`].
+    fn start_sprite(&self, profile: &VoiceProfile, sheet: Sheet, window: SpriteWindow, apply_pitch: bool, delay: Duration) -> Result<StaticSoundHandle, Box<dyn std::error::Error>> {
+        // Determine the candidate file paths
+        let (candidates, first) = match sheet {
+            Sheet::Sfx(path) => (vec![PathBuf::from(path)], PathBuf::from(path)),
+            // Try each format the voice may ship in
+            Sheet::Voice(assets) => (assets.voice_files(profile.voice_type), assets.voice_file(profile.voice_type)),
+        };
+
+        // Use the first candidate that decodes
+        let mut last_error: Box<dyn std::error::Error> = AnimaleseError::AssetNotFound(first).into();
+        let mut loaded = None;
+        for path in candidates {
+            match StaticSoundData::from_file(&path) {
+                Ok(data) => {
+                    loaded = Some(data);
+                    break;
+                }
+                Err(e) => {
+                    event!(warn, logging::DECODE, "failed to decode {}: {}", path.display(), e);
+                    last_error = e.into();
+                }
+            }
+        }
+        let Some(sound_data) = loaded else {
+            return Err(last_error);
+        };
+
+        let playback_rate = if apply_pitch {
+            let random_variation = self.rng.lock().unwrap().gen_range(-1.0..=1.0) * profile.effective_pitch_variation();
+            semitones_to_rate(final_pitch(profile.pitch_shift, random_variation, profile.pitch_floor, profile.pitch_ceiling))
+        } else {
+            1.0
+        };
+        // Formant-preserving shifts play the sprite at its own speed
+        let formant_rate = if apply_pitch && profile.preserve_formants { playback_rate } else { 1.0 };
+        let playback_rate = playback_rate / formant_rate;
+        let length = window.source_length(playback_rate);
+
+        // Short packs may not reach this sprite
+        let start = resolve_sprite_start(window.start, length, sound_data.duration(), profile.sprite_overflow)?;
+
+        // Slice to extract only the sprite region
+        let start_time = start.as_secs_f64();
+        let end_time = start_time + length.as_secs_f64();
+        let mut sound_data = sound_data.slice(start_time..end_time);
+
+        // Opt-in pitch-preserving stretch of voice sprites
+        if apply_pitch && profile.time_stretch != 1.0 {
+            sound_data = stretch_sprite(&sound_data, profile.time_stretch);
+        }
+        if apply_pitch && profile.reverse_sprites {
+            sound_data = reverse_sprite(&sound_data);
+        }
+        if formant_rate != 1.0 {
+            sound_data = formant_shift_sprite(&sound_data, formant_rate);
+        }
+        if let Some(filter) = profile.filter {
+            sound_data = filter_sprite(&sound_data, filter, playback_rate);
+        }
+
+        // Sheets other than the voice's are sound effects, which aren't trimmed
+        let volume = match sheet {
+            Sheet::Voice(assets) => {
+                let trim = match &assets.voice_trims {
+                    Some(trims) => trims.get(&profile.voice_type).copied().unwrap_or(0.0),
+                    None => self.voice_trim(profile.voice_type),
+                };
+                trimmed_volume(profile.volume, trim)
+            }
+            Sheet::Sfx(_) => profile.volume,
+        };
+        if apply_pitch {
+            let volume = jittered_volume(volume, profile.volume_variation, &mut *self.rng.lock().unwrap());
+
+            // Configure sound with pitch and volume
+            sound_data = sound_data
+                .playback_rate(playback_rate as f64)
+                .volume(Volume::Amplitude(volume as f64));
+        } else {
+            sound_data = sound_data.volume(Volume::Amplitude(volume as f64));
+        }
+        sound_data = with_fade_in(sound_data, profile.fade_in);
+
+        if profile.pan != 0.0 {
+            sound_data = sound_data.panning(kira_panning(profile.pan));
+        }
+        if !delay.is_zero() {
+            sound_data = sound_data.start_time(StartTime::Delayed(delay));
+        }
+        sound_data = sound_data.output_destination(&self.voice_track);
+
+        // Play the sound
+        let mut manager = self.manager.lock().unwrap();
+        Ok(manager.play(sound_data)?)
+    }
+
+    /// Stop all currently playing sounds
+    ///
+    /// Also interrupts any `speak()` in progress on other threads, which then
+    /// returns `Ok` without playing the rest of its text.
+    pub fn stop(&self) {
+        self.stop_generation.fetch_add(1, Ordering::SeqCst);
+
+        let mut active = self.active_sounds.lock().unwrap();
+        event!(debug, logging::PLAYBACK, "stop: cancelling {} sounds", active.len());
+        for sound in active.iter_mut() {
+            sound.handle.stop(Tween::default());
+        }
+        active.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Engine on the bundled assets that renders without an output device
+    fn stream_engine() -> Animalese<StreamBackend> {
+        let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings::default()).unwrap();
+        Animalese::with_audio_manager(bundled_assets_path().to_string_lossy(), manager).unwrap()
+    }
+
+    /// Pull from a stream on a background thread, like an audio device would
+    ///
+    /// Runs faster than real time. Drop the returned guard to stop it.
+    struct Driver(Arc<std::sync::atomic::AtomicBool>);
+
+    impl Drop for Driver {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn drive(stream: SampleStream) -> Driver {
+        let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = done.clone();
+        std::thread::spawn(move || {
+            let mut block = vec![0.0f32; 1024];
+            while !flag.load(Ordering::SeqCst) {
+                stream.fill(&mut block);
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        });
+        Driver(done)
+    }
+
+    /// A voice pack in a temporary directory, removed when dropped
+    ///
+    /// Laid out like the bundled one: `voice/` with the sheets and `sfx.ogg`
+    /// next to it.
+    struct TempPack {
+        root: PathBuf,
+        voice: PathBuf,
+    }
+
+    impl TempPack {
+        /// Engine on this pack that renders without an output device
+        fn engine(&self) -> Animalese<StreamBackend> {
+            let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings::default()).unwrap();
+            Animalese::with_audio_manager(self.voice.to_string_lossy(), manager).unwrap()
+        }
+    }
+
+    impl Drop for TempPack {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// Make a pack with the bundled `sfx.ogg` and sheets for `voices`
+    fn temp_pack(name: &str, voices: &[VoiceType]) -> TempPack {
+        let root = std::env::temp_dir().join(format!("animalese-{}-{}", name, std::process::id()));
+        let voice = root.join("voice");
+        std::fs::create_dir_all(&voice).unwrap();
+        std::fs::copy(bundled_assets_path().parent().unwrap().join("sfx.ogg"), root.join("sfx.ogg")).unwrap();
+        for voice_type in voices {
+            let file = voice_type.filenames()[0];
+            std::fs::copy(bundled_assets_path().join(file), voice.join(file)).unwrap();
+        }
+        TempPack { root, voice }
+    }
+
+    #[test]
+    fn test_letter_to_sprite_time() {
+        assert_eq!(letter_to_sprite_time('a'), Some(Duration::from_millis(0)));
+        assert_eq!(letter_to_sprite_time('b'), Some(Duration::from_millis(200)));
+        assert_eq!(letter_to_sprite_time('z'), Some(Duration::from_millis(5000)));
+        assert_eq!(letter_to_sprite_time('A'), Some(Duration::from_millis(0)));
+        assert_eq!(letter_to_sprite_time('1'), None);
+    }
+
+    #[test]
+    fn test_sprite_layout() {
+        // Specials follow the alphabet back to back, without overlapping it
+        let alphabet_end = letter_to_sprite_time('z').unwrap() + LETTER_SLOT;
+        assert_eq!(special_to_sprite_time("ok"), Some(alphabet_end));
+        for pair in SPECIAL_MAP.windows(2) {
+            assert_eq!(pair[1].1, pair[0].1 + SFX_SLOT);
+        }
+
+        // The whole sheet fits in a short voice file, which the bundled ones cover
+        let sheet = voice_sheet_length();
+        assert_eq!(sheet, SPECIAL_MAP[2].1 + SFX_SLOT);
+        assert!(sheet <= Duration::from_secs(10), "{:?}", sheet);
+        let voice = StaticSoundData::from_file(bundled_assets_path().join("f1.ogg")).unwrap();
+        assert!(voice.duration() >= sheet, "{:?}", voice.duration());
+    }
+
+    #[test]
+    fn test_effective_profile_for() {
+        let engine = stream_engine();
+        engine.set_profile(VoiceProfile { pitch_shift: -1.0, intonation: 0.9, ..Default::default() });
+
+        let deltas = |intonation| {
+            let profile = engine.effective_profile_for(intonation);
+            (profile.pitch_shift, profile.intonation)
+        };
+        assert_eq!(deltas(Intonation::Flat), (-1.0, 0.0));
+        assert_eq!(deltas(Intonation::Question), (-1.0, 0.6));
+        assert_eq!(deltas(Intonation::Statement), (-1.0, -0.3));
+        assert_eq!(deltas(Intonation::Excited), (1.0, 0.4));
+        assert_eq!(deltas(Intonation::Custom(-0.8)), (-1.0, -0.8));
+        assert_eq!(engine.profile().intonation, 0.9, "the shared profile is untouched");
+    }
+
+    #[test]
+    fn test_plan_timed() {
+        let ms = Duration::from_millis;
+        let plan = plan_timed(&[('H', ms(40)), ('i', ms(500)), ('.', ms(100)), ('y', ms(30))], &VoiceProfile::default());
+
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[0].source, SoundSource::Letter('H'));
+        assert_eq!((plan[0].offset, plan[0].length), (ms(0), ms(40)));
+        assert_eq!((plan[1].offset, plan[1].length), (ms(40), ms(200)), "clamped to the sprite");
+        assert_eq!((plan[2].offset, plan[2].length), (ms(640), ms(30)), "after the silent '.'");
+        assert_eq!(plan[2].sprite_start, letter_to_sprite_time('y').unwrap());
+    }
+
+    #[test]
+    fn test_number_words() {
+        assert_eq!(number_words(0), "zero");
+        assert_eq!(number_words(42), "forty two");
+        assert_eq!(number_words(-7), "minus seven");
+        assert_eq!(number_words(1234), "one thousand two hundred thirty four");
+        assert_eq!(number_words(1_000_015), "one million fifteen");
+        assert_eq!(number_words(90), "ninety");
+        assert_eq!(number_words(i64::MIN), "minus nine quintillion two hundred twenty three quadrillion \
+            three hundred seventy two trillion thirty six billion eight hundred fifty four million \
+            seven hundred seventy five thousand eight hundred eight");
+    }
+
+    #[test]
+    fn test_fade_in_can_be_skipped() {
+        let engine = stream_engine();
+        let onset = |fade_in| {
+            engine.set_profile(VoiceProfile { fade_in, pitch_variation: 0.0, ..Default::default() });
+            let samples = engine.render_to_samples_f32("o").unwrap();
+            let start = samples.iter().position(|&s| s != 0.0).unwrap_or(0);
+            samples[start..start + 2].iter().map(|s| s.abs()).sum::<f32>()
+        };
+
+        // Kira's fade starts from silence, so a faded sprite's first samples are
+        // near zero while the raw sprite starts at full level
+        assert!(onset(Duration::ZERO) > onset(DEFAULT_FADE_IN) * 10.0);
+        assert_eq!(with_fade_in(StaticSoundData::from_file(bundled_assets_path().join("f1.ogg")).unwrap(), Duration::ZERO).settings.fade_in_tween, None);
+    }
+
+    #[test]
+    fn test_final_pitch() {
+        assert_eq!(final_pitch(3.0, -0.5, DEFAULT_PITCH_FLOOR, DEFAULT_PITCH_CEILING), 2.5);
+        // Stacked modifiers can't run away past the bounds
+        assert_eq!(final_pitch(12.0 + 9.0, 2.0, DEFAULT_PITCH_FLOOR, DEFAULT_PITCH_CEILING), 18.0);
+        assert_eq!(final_pitch(-30.0, 0.0, DEFAULT_PITCH_FLOOR, DEFAULT_PITCH_CEILING), -18.0);
+        assert_eq!(final_pitch(5.0, 1.0, -2.0, 4.0), 4.0);
+        assert_eq!(final_pitch(0.0, 0.0, 6.0, 4.0), 4.0, "the ceiling wins a crossed range");
+    }
+
+    #[test]
+    fn test_jittered_volume() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        assert_eq!(jittered_volume(0.65, 0.0, &mut rng), 0.65);
+
+        let gains: Vec<f32> = (0..500).map(|_| jittered_volume(0.5, 0.2, &mut rng)).collect();
+        assert!(gains.iter().all(|g| (0.4..=0.6).contains(g)), "within ±20%");
+        assert!(gains.iter().any(|g| *g < 0.45) && gains.iter().any(|g| *g > 0.55), "spread out");
+        let mean = gains.iter().sum::<f32>() / gains.len() as f32;
+        assert!((mean - 0.5).abs() < 0.01, "centred on the profile volume: {mean}");
+
+        // Never leaves 0.0..=1.0
+        assert!((0..200).map(|_| jittered_volume(0.95, 1.0, &mut rng)).all(|g| (0.0..=1.0).contains(&g)));
+
+        let mut again = StdRng::seed_from_u64(3);
+        assert_eq!(jittered_volume(0.65, 0.0, &mut again), 0.65);
+        assert_eq!(jittered_volume(0.5, 0.2, &mut again), gains[0], "seeded RNG is repeatable");
+    }
+
+    #[test]
+    fn test_semitones_to_rate() {
+        assert!((semitones_to_rate(0.0) - 1.0).abs() < 0.001);
+        assert!((semitones_to_rate(12.0) - 2.0).abs() < 0.001);
+        assert!((semitones_to_rate(-12.0) - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_trim_sprite() {
+        let slot = Duration::from_millis(200);
+        let b = Duration::from_millis(200);
+
+        assert_eq!(trim_sprite(b, slot, Duration::ZERO), (b, slot));
+        assert_eq!(
+            trim_sprite(b, slot, Duration::from_millis(15)),
+            (Duration::from_millis(215), Duration::from_millis(185))
+        );
+
+        // Never skips past the end of the slot
+        assert_eq!(
+            trim_sprite(b, slot, Duration::from_millis(500)),
+            (Duration::from_millis(400), Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_sprite_source_length() {
+        let ms = Duration::from_millis;
+        let window = SpriteWindow { start: ms(200), length: ms(100), slot: ms(200) };
+
+        assert_eq!(window.source_length(1.0), ms(100));
+        assert_eq!(window.source_length(0.5), ms(50));
+        // An octave up needs twice the source to sound as long
+        assert_eq!(window.source_length(2.0), ms(200));
+        // ...but never reads past the slot into the next sprite
+        assert_eq!(window.source_length(4.0), ms(200));
+        assert_eq!(SpriteWindow::whole(ms(0), ms(600)).source_length(3.0), ms(600));
+
+        assert_eq!(sprite_slot_end(&SoundSource::Letter('b')), Some(ms(400)));
+        assert_eq!(sprite_slot_end(&SoundSource::Special("ok")), Some(ms(5800)));
+    }
+
+    #[test]
+    fn test_resolve_sprite_start() {
+        let ms = Duration::from_millis;
+        let file = ms(2000);
+
+        for overflow in [SpriteOverflow::Error, SpriteOverflow::Clamp, SpriteOverflow::Wrap] {
+            assert_eq!(resolve_sprite_start(ms(1800), ms(200), file, overflow), Ok(ms(1800)));
+            assert_eq!(resolve_sprite_start(ms(1900), ms(200), file, overflow), Ok(ms(1900)));
+        }
+
+        assert_eq!(
+            resolve_sprite_start(ms(5000), ms(200), file, SpriteOverflow::Error),
+            Err(AnimaleseError::SpriteOutOfRange { start: ms(5000), file_length: file })
+        );
+        assert_eq!(resolve_sprite_start(ms(5000), ms(200), file, SpriteOverflow::Clamp), Ok(ms(1800)));
+        assert_eq!(resolve_sprite_start(ms(5000), ms(200), file, SpriteOverflow::Wrap), Ok(ms(1000)));
+        assert_eq!(resolve_sprite_start(ms(2000), ms(200), file, SpriteOverflow::Wrap), Ok(ms(0)));
+    }
+
+    #[test]
+    fn test_truncated_pack() {
+        let pack = temp_pack("short-pack", &[]);
+
+        // One second of audio covers a-e only
+        let clip = Capture { sample_rate: 44_100, samples: vec![1000; 88_200] };
+        clip.write_wav(pack.voice.join("f1.wav")).unwrap();
+
+        let engine = pack.engine();
+        engine.play_letter('e').unwrap();
+        let err = engine.play_letter('z').err().unwrap();
+        assert!(matches!(err.downcast_ref(), Some(AnimaleseError::SpriteOutOfRange { .. })), "{err}");
+
+        engine.set_profile(VoiceProfile { sprite_overflow: SpriteOverflow::Clamp, ..Default::default() });
+        engine.play_letter('z').unwrap();
+    }
+
+    #[test]
+    fn test_validate_assets() {
+        assert_eq!(stream_engine().validate_assets(), Vec::new());
+        assert_eq!(voice_sheet_length(), Duration::from_millis(7000));
+
+        let pack = temp_pack("qa-pack", &VoiceType::ALL);
+        let voice = &pack.voice;
+        std::fs::write(voice.join("f2.ogg"), b"not audio").unwrap();
+        std::fs::remove_file(voice.join("m4.ogg")).unwrap();
+        Capture { sample_rate: 44_100, samples: vec![0; 88_200] }.write_wav(voice.join("m1.wav")).unwrap();
+
+        let issues = pack.engine().validate_assets();
+
+        assert_eq!(issues.len(), 3, "{issues:?}");
+        assert_eq!(issues[0].path, voice.join("f2.ogg"));
+        assert!(matches!(issues[0].problem, AssetProblem::Undecodable(_)));
+        assert_eq!(issues[1], AssetIssue {
+            path: voice.join("m1.wav"),
+            problem: AssetProblem::TooShort { length: Duration::from_secs(1), needed: Duration::from_secs(7) },
+        });
+        assert_eq!(issues[2], AssetIssue { path: voice.join("m4.ogg"), problem: AssetProblem::Missing });
+        assert!(issues[2].to_string().ends_with("m4.ogg: missing"));
+    }
+
+    #[test]
+    fn test_speak_from_assets_uses_its_trims() {
+        let pack = temp_pack("trimmed-guest", &[VoiceType::F1]);
+        let manifest = |trim: f32| {
+            let path = pack.root.join(format!("voices{}.toml", trim));
+            std::fs::write(&path, format!("voice_dir = \"voice\"\n[voices.f1]\ntrim_db = {:?}\n", trim)).unwrap();
+            path
+        };
+        let engine = stream_engine();
+        engine.set_profile(VoiceProfile { pitch_variation: 0.0, ..Default::default() });
+        let stream = engine.sample_stream();
+        let peak_of = |assets: &Path| {
+            engine.speak_from_assets(assets, "hi").unwrap();
+            let mut block = vec![0.0f32; 4096];
+            (0..16).fold(0.0f32, |peak, _| {
+                stream.fill(&mut block);
+                block.iter().fold(peak, |p, s| p.max(s.abs()))
+            })
+        };
+
+        // The host's trim doesn't reach the guest's recordings, and the guest's does
+        engine.set_voice_trim(VoiceType::F1, -40.0);
+        let loud = peak_of(&manifest(0.0));
+        let bare = peak_of(&pack.voice);
+        engine.set_voice_trim(VoiceType::F1, 0.0);
+        let quiet = peak_of(&manifest(-40.0));
+
+        assert!(loud > 0.01, "{loud}");
+        assert!((bare - loud).abs() < loud * 0.1, "{bare} vs {loud}");
+        assert!(quiet < loud * 0.05, "{quiet} vs {loud}");
+        assert_eq!(engine.voice_trim(VoiceType::F1), 0.0, "the engine's trims are untouched");
+    }
+
+    #[test]
+    fn test_speak_from_assets() {
+        let pack = temp_pack("guest-pack", &[VoiceType::M2]);
+        let (root, voice) = (&pack.root, &pack.voice);
+        std::fs::write(root.join("voices.toml"), "voice_dir = \"voice\"\n[voices.f1]\nfile = \"m2.ogg\"\n").unwrap();
+
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+        // The guest pack has no f1, unless its manifest points f1 at m2.ogg
+        let missing = engine.speak_from_assets(voice, "hi").unwrap_err();
+        assert_eq!(missing.downcast_ref(), Some(&AnimaleseError::AssetNotFound(voice.join("f1.ogg"))));
+        let via_manifest = engine.speak_from_assets(&root.join("voices.toml"), "hi");
+        assert!(via_manifest.is_ok(), "{via_manifest:?}");
+        let nowhere = engine.speak_from_assets(&root.join("nowhere"), "hi").unwrap_err();
+        assert_eq!(nowhere.downcast_ref(), Some(&AnimaleseError::AssetNotFound(root.join("nowhere"))));
+
+        std::fs::copy(bundled_assets_path().join("f1.ogg"), voice.join("f1.ogg")).unwrap();
+        let guest = engine.speak_from_assets(voice, "hi");
+        assert!(guest.is_ok(), "{guest:?}");
+
+        // The engine's own pack is untouched
+        assert_eq!(engine.assets.voice_path, bundled_assets_path().to_string_lossy());
+        engine.speak("hi").unwrap();
+    }
+
+    #[test]
+    fn test_underrun_count() {
+        // Only the wiring; capture.rs tests the detector on a fake clock
+        let engine = stream_engine();
+        let stream = engine.sample_stream();
+        let mut block = vec![0.0f32; 2048];
+        stream.fill(&mut block);
+
+        // Nobody pulls for a while, as if the audio thread had stalled
+        std::thread::sleep(Duration::from_millis(300));
+        stream.fill(&mut block);
+        assert!(engine.underrun_count() >= 1);
+    }
+
+    #[test]
+    fn test_self_test() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+        engine.set_enabled(false);
+        let report = engine.self_test();
+
+        assert!(report.is_ok(), "{report}");
+        assert!(report.backend.ends_with("StreamBackend"), "{}", report.backend);
+        let text = report.to_string();
+        assert_eq!(text.lines().count(), 5, "{text}");
+        assert!(text.contains("\nassets: ok\n") && text.ends_with("test tone: ok"), "{text}");
+
+        let broken = SelfTestReport {
+            asset_issues: vec![AssetIssue { path: PathBuf::from("m4.ogg"), problem: AssetProblem::Missing }],
+            output_devices: Vec::new(),
+            sample_rate: None,
+            test_tone: Err("no room".into()),
+            ..report
+        };
+        assert!(!broken.is_ok());
+        assert!(broken.to_string().ends_with(concat!(
+            "assets: m4.ogg: missing\n",
+            "output devices: 0 (no default)\n",
+            "sample rate: not reported yet\n",
+            "test tone: failed: no room",
+        )));
+    }
+
+    #[test]
+    fn test_builder_applies_bundled_manifest() {
+        let manager = || AudioManager::<StreamBackend>::new(AudioManagerSettings::default()).unwrap();
+        let bundled = Animalese::with_assets_or_bundled(None, manager()).unwrap();
+        let manifest = bundled_manifest().unwrap();
+        assert_eq!(bundled.assets.voice_file_names.len(), manifest.voices.len());
+        assert_eq!(Path::new(&bundled.assets.voice_path), manifest.voice_dir);
+
+        // A custom directory has no manifest
+        let custom = Animalese::with_assets_or_bundled(Some(bundled_assets_path().to_string_lossy().into()), manager()).unwrap();
+        assert!(custom.assets.voice_file_names.is_empty());
+    }
+
+    #[test]
+    fn test_manifest() {
+        let pack = temp_pack("manifest-pack", &[]);
+        std::fs::copy(bundled_assets_path().join("m2.ogg"), pack.voice.join("deep.ogg")).unwrap();
+        let toml = "voice_dir = \"voice\"\n\n[voices.m2]\nfile = \"deep.ogg\"\ntrim_db = -3.0\n";
+        let manifest = manifest::Manifest::parse(toml, &pack.root).unwrap();
+
+        let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings::default()).unwrap();
+        let engine = Animalese::with_audio_manager(manifest.voice_dir.to_string_lossy(), manager).unwrap().configure(manifest);
+        let _driver = drive(engine.sample_stream());
+        engine.set_profile(VoiceProfile { voice_type: VoiceType::M2, ..Default::default() });
+        engine.speak("hi").unwrap();
+
+        assert_eq!((engine.has_voice(VoiceType::M2), engine.has_voice(VoiceType::F1)), (true, false));
+        assert_eq!(engine.voice_trim(VoiceType::M2), -3.0);
+        let issues = engine.validate_assets();
+        assert_eq!(issues.len(), 7, "{issues:?}");
+        assert!(issues.iter().all(|issue| issue.problem == AssetProblem::Missing));
+        assert!(issues.iter().any(|issue| issue.path == pack.voice.join("f1.ogg")));
+    }
+
+    #[test]
+    fn test_builtin_sprite_maps() {
+        let sfx = builtin_sfx_map();
+        assert_eq!(sfx.len(), 26);
+        for (index, (name, offset)) in sfx.iter().enumerate() {
+            assert_eq!(*offset, Duration::from_millis(index as u64 * 600), "{name}");
+            assert_eq!(sfx_to_sprite_time(name), Some(*offset));
+        }
+        assert_eq!(sfx[1], ("enter", Duration::from_millis(600)));
+
+        let specials: Vec<_> = builtin_special_map().iter().map(|(name, _)| *name).collect();
+        assert_eq!(specials, vec!["ok", "gwah", "deska"]);
+        // Specials follow the 26 letter sprites, 600ms apart
+        for (index, (name, offset)) in builtin_special_map().iter().enumerate() {
+            assert_eq!(*offset, Duration::from_millis(5200 + index as u64 * 600), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_special_sounds() {
+        assert_eq!(special_to_sprite_time("ok"), Some(Duration::from_millis(5200)));
+        assert_eq!(special_to_sprite_time("gwah"), Some(Duration::from_millis(5800)));
+        assert_eq!(special_to_sprite_time("deska"), Some(Duration::from_millis(6400)));
+        assert_eq!(special_to_sprite_time("unknown"), None);
+    }
+
+    #[test]
+    fn test_inline_voice_tokens() {
+        let plan = plan_utterance("[m1]Hi there [f2]yo", &VoiceProfile::default(), 0.0);
+        let voices: Vec<_> = plan.iter().map(|sound| sound.voice).collect();
+        assert_eq!(voices, [
+            [Some(VoiceType::M1); 7].as_slice(),
+            [Some(VoiceType::F2); 2].as_slice(),
+        ].concat());
+        assert!(plan_utterance("hi", &VoiceProfile::default(), 0.0).iter().all(|sound| sound.voice.is_none()));
+
+        // Only f1 ships in this pack, so the m1 letters can't find their file
+        let pack = temp_pack("inline-voice", &[VoiceType::F1]);
+        let engine = pack.engine();
+        assert!(engine.speak("[f1]hi").is_ok());
+        assert!(engine.speak("hi [m1]there").is_err());
+        assert_eq!(engine.profile().voice_type, VoiceType::F1, "the shared profile is untouched");
+    }
+
+    #[test]
+    fn test_tokenize_specials() {
+        assert_eq!(tokenize("hi [deska]"), vec![
+            Token::Letter('h'),
+            Token::Letter('i'),
+            Token::Other(' '),
+            Token::Special("deska"),
+        ]);
+        assert_eq!(tokenize("[OK]!"), vec![Token::Special("ok"), Token::Other('!')]);
+
+        // Unknown and unterminated tokens are spoken literally
+        assert_eq!(tokenize("[hi]"), vec![
+            Token::Other('['),
+            Token::Letter('h'),
+            Token::Letter('i'),
+            Token::Other(']'),
+        ]);
+        assert_eq!(tokenize("[M1]a[f2]"), vec![
+            Token::Voice(VoiceType::M1),
+            Token::Letter('a'),
+            Token::Voice(VoiceType::F2),
+        ]);
+        assert_eq!(tokenize("[m9]")[0], Token::Other('['));
+        assert_eq!(tokenize("[gwah"), vec![
+            Token::Other('['),
+            Token::Letter('g'),
+            Token::Letter('w'),
+            Token::Letter('a'),
+            Token::Letter('h'),
+        ]);
+    }
+
+    #[test]
+    fn test_plan_utterance() {
+        let profile = VoiceProfile::default();
+        let plan = plan_utterance("ab c", &profile, 1.0);
+
+        let sources: Vec<_> = plan.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(sources, vec![SoundSource::Letter('a'), SoundSource::Letter('b'), SoundSource::Letter('c')]);
+
+        let offsets: Vec<_> = plan.iter().map(|p| p.offset.as_millis()).collect();
+        assert_eq!(offsets, vec![0, 50, 100]);
+
+        assert_eq!(plan[1].sprite_start, Duration::from_millis(200));
+        assert_eq!(plan[1].length, Duration::from_millis(200));
+        assert!((plan[0].intonation_shift - 0.0).abs() < 0.001);
+        assert!((plan[1].intonation_shift - 1.0).abs() < 0.001);
+        assert!((plan[2].intonation_shift - 2.0).abs() < 0.001);
+        assert!(plan.iter().all(|p| p.pitched));
+    }
+
+    #[test]
+    fn test_plan_utterance_fullwidth() {
+        let profile = VoiceProfile::default();
+        assert_eq!(plan_utterance("ＨＥＬＬＯ\u{3000}ｗｏｒｌｄ", &profile, 1.0), plan_utterance("HELLO world", &profile, 1.0));
+        assert_eq!(resolve_contour("ｗｈａｔ？", &profile), resolve_contour("what?", &profile));
+        assert_eq!(letter_to_sprite_time('Ｚ'), letter_to_sprite_time('z'));
+
+        assert_eq!(fold_compatibility("１２３ ٤٥ ५"), "123 45 5");
+        assert_eq!(fold_compatibility("é ẞ 漢"), "é ẞ 漢");
+    }
+
+    #[test]
+    fn test_normalize_text() {
+        let plain = VoiceProfile::default();
+        assert_eq!(normalize_text("Ｈｉ, <pitch=+2>don't</pitch> *go*!", &plain), "Hi, dont *go*!");
+        assert_eq!(normalize_text("[OK] <pause=100ms>[M2]٤٥ ❓", &plain), "[ok] [m2]45 ❓");
+        assert_eq!(normalize_text("", &plain), "");
+
+        // Folding, markup, emphasis, the word cap and speed reading together
+        let profile = VoiceProfile { markdown_emphasis: true, speed_read: true, max_word_length: 9, ..Default::default() };
+        assert_eq!(
+            normalize_text("ｎｏｔｉｆｉｃａｔｉｏｎ *don't* <rate=2>received</rate> ١٢", &profile),
+            "ntfc dont rcvd 12",
+        );
+        // The cap applies before abbreviating, leaving "notif" to abbreviate
+        let capped = VoiceProfile { max_word_length: 5, ..profile.clone() };
+        assert_eq!(normalize_text("notification", &capped), "ntf");
+        assert_eq!(normalize_text("notification", &VoiceProfile { max_word_length: 3, ..profile.clone() }), "not");
+
+        // It's exactly what gets planned
+        let text = "ＦＢＩ agents *don't* <pause=50ms>whisper [gwah]";
+        let letters: String = normalize_text(text, &profile).chars().filter(char::is_ascii_alphabetic).collect();
+        let planned: String = plan_utterance(text, &profile, 0.0).iter()
+            .filter_map(|sound| match sound.source {
+                SoundSource::Letter(c) => Some(c),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(letters.replace("gwah", ""), planned);
+    }
+
+    #[test]
+    fn test_plan_utterance_word_and_line_pauses() {
+        let offsets = |text: &str, profile: &VoiceProfile| -> Vec<u128> {
+            plan_utterance(text, profile, 0.0).iter().map(|p| p.offset.as_millis()).collect()
+        };
+        assert_eq!(offsets("a b\nc", &VoiceProfile::default()), [0, 50, 100]);
+
+        let paused = VoiceProfile {
+            word_pause: Duration::from_millis(100),
+            line_pause: Duration::from_millis(200),
+            tempo: 2.0,
+            ..Default::default()
+        };
+        // Scaled by tempo like the letter gap
+        assert_eq!(offsets("a b\nc", &paused), [0, 75, 200]);
+    }
+
+    #[test]
+    fn test_plan_utterance_letter_gap() {
+        let profile = VoiceProfile { letter_gap: Duration::from_millis(120), tempo: 2.0, ..Default::default() };
+        let offsets: Vec<_> = plan_utterance("abc", &profile, 0.0).iter().map(|p| p.offset.as_millis()).collect();
+        assert_eq!(offsets, vec![0, 60, 120]);
+    }
+
+    #[test]
+    fn test_adaptive_letter_gap() {
+        let gap = Duration::from_millis(50);
+        assert_eq!(adaptive_letter_gap(gap, 0), gap);
+        assert_eq!(adaptive_letter_gap(gap, 50), gap);
+        assert_eq!(adaptive_letter_gap(gap, 275).as_millis(), 35);
+        assert_eq!(adaptive_letter_gap(gap, 500).as_millis(), 20);
+        assert_eq!(adaptive_letter_gap(gap, 5000).as_millis(), 20);
+
+        let text = "ab ".repeat(250);
+        let profile = VoiceProfile { adaptive_cadence: true, ..Default::default() };
+        assert_eq!(plan_utterance(&text, &profile, 0.0)[1].offset.as_millis(), 20);
+        assert_eq!(plan_utterance("ab", &profile, 0.0)[1].offset.as_millis(), 50);
+        assert_eq!(plan_utterance(&text, &VoiceProfile::default(), 0.0)[1].offset.as_millis(), 50);
+    }
+
+    #[test]
+    fn test_letter_frames() {
+        let profile = VoiceProfile::default();
+        let plan = plan_utterance("hi", &profile, 0.0);
+        // 250ms at 30 fps; "i" starts at 50ms and sounds from the third frame on
+        assert_eq!(frames_covering(Duration::from_millis(250), 30), 8);
+        assert_eq!(letter_frames(&plan, 30), [Some('h'), Some('h'), Some('i'), Some('i'), Some('i'), Some('i'), Some('i'), Some('i')]);
+
+        let plan = plan_utterance("a<pause=300ms>B", &profile, 0.0);
+        let frames = letter_frames(&plan, 30);
+        assert_eq!(frames.len(), 17);
+        assert_eq!(frames[..6], [Some('a'); 6]);
+        assert_eq!(frames[6..11], [None; 5], "a ends exactly on frame 6");
+        assert_eq!(frames[11..], [Some('B'); 6]);
+
+        // A letter starting on a frame boundary shows from that frame
+        assert_eq!(letter_frames(&plan_utterance("ab", &profile, 0.0), 20)[..2], [Some('a'), Some('b')]);
+
+        let engine = stream_engine();
+        assert_eq!(engine.frame_count("hi", 30), 8);
+        assert_eq!(engine.frames("hi", 30).len(), 8);
+        assert_eq!((engine.frame_count("", 30), engine.frame_count("hi", 0)), (0, 0));
+        assert!(engine.frames("hi", 0).is_empty());
+    }
+
+    #[test]
+    fn test_tempo_for_wpm() {
+        let paused = VoiceProfile { word_pause: Duration::from_millis(100), ..Default::default() };
+        assert!((tempo_for_wpm(60.0 / 0.25, &VoiceProfile::default()) - 1.0).abs() < 1e-4);
+        assert!((tempo_for_wpm(60.0 / 0.35, &paused) - 1.0).abs() < 1e-4);
+        assert_eq!(tempo_for_wpm(1.0, &paused), MIN_TEMPO);
+        assert_eq!(tempo_for_wpm(10_000.0, &paused), MAX_TEMPO);
+
+        // Consecutive five-letter words start 60/wpm seconds apart
+        for wpm in [60.0, 120.0, 240.0] {
+            let profile = VoiceProfile { tempo: tempo_for_wpm(wpm, &paused), ..paused.clone() };
+            let plan = plan_utterance("hello there world", &profile, 0.0);
+            let word = 60.0 / wpm as f64;
+            for (index, sound) in plan.iter().step_by(5).enumerate() {
+                assert!((sound.offset.as_secs_f64() - word * index as f64).abs() < 0.001, "{wpm} WPM: {:?}", sound.offset);
+            }
+        }
+    }
+
+    #[test]
+    fn test_speak_paced_leaves_profile() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+
+        let started = Instant::now();
+        engine.speak_paced("abcd", Duration::from_millis(100)).unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_eq!(engine.profile().letter_gap, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_plan_utterance_specials_and_empty() {
+        let profile = VoiceProfile::default();
+        let plan = plan_utterance("a[ok]b", &profile, 0.0);
+
+        assert_eq!(plan[1].source, SoundSource::Special("ok"));
+        assert_eq!(plan[1].sprite_start, Duration::from_millis(5200));
+        assert_eq!(plan[1].length, Duration::from_millis(600));
+        assert_eq!(plan[2].offset.as_millis(), 350);
+
+        assert!(plan_utterance("", &profile, 0.0).is_empty());
+        assert!(plan_utterance("?! 123", &profile, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_plan_utterance_markup() {
+        let profile = VoiceProfile::default();
+        let plan = plan_utterance("a<pitch=+3><rate=2>b</rate></pitch><pause=100ms>c", &profile, 0.0);
+
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[0].pitch_offset, 0.0);
+        assert_eq!(plan[1].pitch_offset, 3.0);
+        assert_eq!(plan[1].length, Duration::from_millis(100));
+
+        // a's 50ms gap, b's 25ms gap at double rate, then the pause
+        assert_eq!(plan[2].offset, Duration::from_millis(175));
+        assert_eq!(plan[2].pitch_offset, 0.0);
+    }
+
+    #[test]
+    fn test_plan_utterance_markdown_emphasis() {
+        let mut profile = VoiceProfile::default();
+        let pitches = |text: &str, profile: &VoiceProfile| -> Vec<f32> {
+            plan_utterance(text, profile, 0.0).iter().map(|sound| sound.pitch_offset).collect()
+        };
+        assert_eq!(pitches("*hi*", &profile), [0.0, 0.0]);
+
+        profile.markdown_emphasis = true;
+        assert_eq!(pitches("*hi*", &profile), [1.0, 1.0]);
+        assert_eq!(pitches("**hi**", &profile), [2.0, 2.0]);
+        assert_eq!(pitches("a*b", &profile), [0.0, 0.0]);
+
+        // The asterisks of a span are gone, so they can't play as placeholders
+        profile.unmapped = UnmappedPolicy::Blip;
+        assert_eq!(plan_utterance("*hi*", &profile, 0.0).len(), 2);
+        assert_eq!(plan_utterance("a*b", &profile, 0.0).len(), 3);
+    }
+
+    #[test]
+    fn test_is_pronounceable() {
+        for word in ["NASA", "NATO", "SCUBA", "LASER", "UNICEF"] {
+            assert!(is_pronounceable(word), "{word} should be a word");
+        }
+        for word in ["FBI", "USA", "HTML", "NYC", "HTTPS", "BBC", "PDFS"] {
+            assert!(!is_pronounceable(word), "{word} should be spelled");
+        }
+    }
+
+    #[test]
+    fn test_plan_utterance_emoji() {
+        let mut profile = VoiceProfile::default();
+        assert_eq!(plan_utterance("hi ❓", &profile, 0.0).len(), 2);
+
+        profile.emoji_sfx.insert('❓', "question".to_string());
+        profile.emoji_sfx.insert('🔔', "ui:bell".to_string());
+        let plan = plan_utterance("hi ❓ 🔔yo", &profile, 1.0);
+
+        let sources: Vec<_> = plan.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(sources, vec![
+            SoundSource::Letter('h'),
+            SoundSource::Letter('i'),
+            SoundSource::Sfx("question".into()),
+            SoundSource::Sfx("ui:bell".into()),
+            SoundSource::Letter('y'),
+            SoundSource::Letter('o'),
+        ]);
+        assert_eq!(plan[2].sprite_start, Duration::from_millis(1800));
+        assert!(!plan[2].pitched);
+        // Bank sounds are looked up when played
+        assert_eq!(plan[3].sprite_start, Duration::ZERO);
+        assert_eq!(plan[4].offset.as_millis(), 700);
+
+        // Emoji don't take a place in the intonation contour
+        assert!((plan[4].intonation_shift - 1.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_plan_utterance_unmapped() {
+        let plan_with = |unmapped| {
+            let profile = VoiceProfile { unmapped, ..Default::default() };
+            plan_utterance("a1, #b!", &profile, 1.0)
+        };
+        let offsets = |plan: &[PlannedSound]| -> Vec<u128> { plan.iter().map(|p| p.offset.as_millis()).collect() };
+
+        assert_eq!(offsets(&plan_with(UnmappedPolicy::Skip)), [0, 50]);
+
+        // Digits and symbols get a placeholder; punctuation stays silent
+        let plan = plan_with(UnmappedPolicy::DefaultSfx);
+        let sources: Vec<_> = plan.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(sources, [
+            SoundSource::Letter('a'),
+            SoundSource::Sfx("default".into()),
+            SoundSource::Sfx("default".into()),
+            SoundSource::Letter('b'),
+        ]);
+        assert_eq!(offsets(&plan), [0, 50, 100, 150]);
+        assert!(!plan[1].pitched);
+        assert_eq!(plan[1].intonation_shift, 0.0);
+
+        let plan = plan_with(UnmappedPolicy::Blip);
+        assert_eq!(offsets(&plan), [0, 50, 100, 150]);
+        assert_eq!(plan[1].source, SoundSource::Letter('u'));
+        assert_eq!(plan[1].length, BLIP_LENGTH);
+        assert!(plan[1].pitched && plan[1].volume < 1.0);
+        // Placeholders don't take a place in the intonation contour
+        assert!((plan[3].intonation_shift - DEFAULT_INTONATION_RANGE * 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_intonation_range() {
+        let last_shift = |intonation_range_semitones| {
+            let profile = VoiceProfile { intonation_range_semitones, ..Default::default() };
+            plan_utterance("abcd", &profile, 1.0)[3].intonation_shift
+        };
+        assert!((last_shift(3.0) - 2.25).abs() < 0.001);
+        assert!((last_shift(8.0) - 6.0).abs() < 0.001);
+        assert_eq!(last_shift(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_declination() {
+        let shifts = |declination, intonation| -> Vec<f32> {
+            let profile = VoiceProfile { declination, ..Default::default() };
+            plan_utterance("ab cde", &profile, intonation).iter().map(|s| s.intonation_shift).collect()
+        };
+        assert_eq!(shifts(0.0, 0.0), [0.0; 5]);
+        assert_eq!(shifts(2.0, 0.0), [0.0, -0.5, -1.0, -1.5, -2.0]);
+
+        // Stacks on the per-sentence glide
+        let glide = shifts(0.0, 1.0);
+        let both = shifts(2.0, 1.0);
+        for (i, (both, glide)) in both.iter().zip(glide).enumerate() {
+            assert!((both - (glide - 0.5 * i as f32)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_intonation_basis() {
+        let shifts = |intonation_basis| -> Vec<f32> {
+            let profile = VoiceProfile { intonation_basis, ..Default::default() };
+            plan_utterance("ab <pause=1000ms>cd", &profile, 1.0).iter().map(|s| s.intonation_shift).collect()
+        };
+
+        // Evenly spaced steps regardless of the pause
+        let by_letter = shifts(IntonationBasis::LetterCount);
+        assert!((by_letter[1] - 0.75).abs() < 1e-4 && (by_letter[2] - 1.5).abs() < 1e-4, "{by_letter:?}");
+
+        // By time, the pitch climbs during the pause, so 'c' starts much higher
+        let by_time = shifts(IntonationBasis::Time);
+        assert_eq!(by_time[0], by_letter[0]);
+        assert!((by_time[3] - by_letter[3]).abs() < 1e-4, "same end point");
+        assert!(by_time[1] < 0.2, "{by_time:?}");
+        assert!(by_time[2] > 2.0, "{by_time:?}");
+
+        // Without pauses the two agree
+        let even = VoiceProfile { intonation_basis: IntonationBasis::Time, ..Default::default() };
+        let by_time: Vec<f32> = plan_utterance("abcd", &even, 1.0).iter().map(|s| s.intonation_shift).collect();
+        let by_letter: Vec<f32> = plan_utterance("abcd", &VoiceProfile::default(), 1.0).iter().map(|s| s.intonation_shift).collect();
+        for (t, l) in by_time.iter().zip(&by_letter) {
+            assert!((t - l).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        assert_eq!(split_sentences("Hi! How are you? Fine. "), ["Hi!", "How are you?", "Fine."]);
+        assert_eq!(split_sentences("Wait... what?! ok"), ["Wait...", "what?!", "ok"]);
+        assert_eq!(split_sentences("v1.2 is out"), ["v1.2 is out"]);
+        assert_eq!(split_sentences("one sentence"), ["one sentence"]);
+        assert!(split_sentences("  ").is_empty());
+    }
+
+    #[test]
+    fn test_join_sentences_breaths() {
+        let profile = VoiceProfile::default();
+        let sentences = || vec![plan_utterance("hi", &profile, 0.0), plan_utterance("yo", &profile, 0.0)];
+
+        let (plan, sentence_of) = join_sentences(sentences(), Some("tab"));
+        let sources: Vec<_> = plan.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(sources, [
+            SoundSource::Letter('h'),
+            SoundSource::Letter('i'),
+            SoundSource::Sfx("tab".into()),
+            SoundSource::Letter('y'),
+            SoundSource::Letter('o'),
+        ]);
+        assert_eq!(sentence_of, [0, 0, 0, 1, 1]);
+        // The breath comes as "hi" ends, and "yo" after its pause
+        assert_eq!(plan[2].offset, Duration::from_millis(250));
+        assert_eq!(plan[3].offset, Duration::from_millis(250) + BREATH_PAUSE);
+        assert_eq!((plan[2].volume, plan[2].pitched), (BREATH_VOLUME, false));
+
+        let (plan, _) = join_sentences(sentences(), None);
+        assert_eq!(plan.len(), 4);
+        assert_eq!(plan[2].offset, Duration::from_millis(250));
+        assert_eq!(join_sentences(sentences(), Some("nope")).0, plan);
+        assert_eq!(join_sentences(vec![plan_utterance("hi", &profile, 0.0)], Some("tab")).0.len(), 2);
+    }
+
+    #[test]
+    fn test_varied_voices() {
+        let voices = varied_voices(20, &VoiceType::ALL, 42);
+        assert_eq!(voices, varied_voices(20, &VoiceType::ALL, 42), "same seed, same cast");
+        assert_ne!(voices, varied_voices(20, &VoiceType::ALL, 43));
+        assert!(voices.windows(2).all(|w| w[0] != w[1]), "{voices:?}");
+
+        let only_two = [VoiceType::F2, VoiceType::M3];
+        let voices = varied_voices(6, &only_two, 1);
+        assert!(voices.iter().all(|v| only_two.contains(v)));
+        assert!(voices.windows(2).all(|w| w[0] != w[1]));
+
+        assert_eq!(varied_voices(3, &[VoiceType::M1], 1), [VoiceType::M1; 3]);
+        assert_eq!(varied_voices(2, &[], 1).len(), 2);
+    }
+
+    #[test]
+    fn test_plan_utterance_acronyms() {
+        let offsets = |text: &str, acronym_handling: bool| -> Vec<u128> {
+            let profile = VoiceProfile { acronym_handling, ..Default::default() };
+            plan_utterance(text, &profile, 0.0).iter().map(|p| p.offset.as_millis()).collect()
+        };
+
+        assert_eq!(offsets("FBI ok", true), vec![0, 250, 500, 750, 800]);
+        assert_eq!(offsets("FBI ok", false), vec![0, 50, 100, 150, 200]);
+        // Pronounceable, lowercase and mixed-case words keep normal cadence
+        assert_eq!(offsets("NASA", true), vec![0, 50, 100, 150]);
+        assert_eq!(offsets("fbi Fbi", true), vec![0, 50, 100, 150, 200, 250]);
+    }
+
+    #[test]
+    fn test_contractions() {
+        assert_eq!(tokenize("don't"), tokenize("dont"));
+        assert_eq!(tokenize("we’re"), tokenize("were"));
+        // Only apostrophes inside a word are dropped
+        assert_eq!(tokenize("'tis dogs'"), vec![
+            Token::Other('\''), Token::Letter('t'), Token::Letter('i'), Token::Letter('s'), Token::Other(' '),
+            Token::Letter('d'), Token::Letter('o'), Token::Letter('g'), Token::Letter('s'), Token::Other('\''),
+        ]);
+
+        // Cadence, acronym spelling and speed reading treat them as one word
+        let profile = VoiceProfile { acronym_handling: true, speed_read: true, ..Default::default() };
+        for (with, without) in [("don't stop", "dont stop"), ("DON'T", "DONT"), ("couldn't", "couldnt")] {
+            assert_eq!(plan_utterance(with, &profile, 0.0), plan_utterance(without, &profile, 0.0), "{with}");
+        }
+    }
+
+    #[test]
+    fn test_speed_read_tokens() {
+        let read = |text: &str| -> String {
+            speed_read_tokens(tokenize(text)).iter()
+                .map(|token| match token {
+                    Token::Letter(c) | Token::Other(c) => *c,
+                    Token::Special(_) | Token::Voice(_) => '#',
+                })
+                .collect()
+        };
+
+        assert_eq!(read("notification"), "ntfc");
+        assert_eq!(read("hello world"), "hll wrld");
+        assert_eq!(read("Animal crossing"), "Anml crss");
+        assert_eq!(read("the read, quick!"), "the read, qck!");
+        assert_eq!(read("rhythm[ok]aeiou"), "rhyt#a");
+
+        // Off by default
+        let plan = plan_utterance("notification", &VoiceProfile::default(), 0.0);
+        assert_eq!(plan.len(), 12);
+        let plan = plan_utterance("notification", &VoiceProfile { speed_read: true, ..Default::default() }, 0.0);
+        assert_eq!(plan.len(), 4);
+    }
+
+    #[test]
+    fn test_voice_default_variation() {
+        let profile = VoiceProfile { voice_type: VoiceType::M3, pitch_variation: 1.5, ..Default::default() };
+        assert_eq!(profile.effective_pitch_variation(), 1.5);
+
+        let profile = VoiceProfile { use_voice_defaults: true, ..profile };
+        assert_eq!(profile.effective_pitch_variation(), 0.4);
+        assert!(VoiceType::ALL.iter().all(|v| (0.0..=MAX_PITCH_VARIATION).contains(&v.default_pitch_variation())));
+
+        let engine = stream_engine();
+        engine.set_profile(profile);
+        engine.set_variation(3.0);
+        let profile = engine.profile();
+        assert!(!profile.use_voice_defaults);
+        assert_eq!(profile.effective_pitch_variation(), MAX_PITCH_VARIATION);
+    }
+
+    #[test]
+    fn test_set_seed_repeats_render() {
+        let engine = stream_engine();
+        engine.set_seed(42);
+        let first = engine.render_to_samples("hello there").unwrap();
+        let unseeded = engine.render_to_samples("hello there").unwrap();
+        engine.set_seed(42);
+        assert_eq!(engine.render_to_samples("hello there").unwrap(), first);
+        assert_ne!(unseeded, first, "the sequence moves on between calls");
+    }
+
+    #[test]
+    fn test_speak_melody() {
+        let mut plan = plan_utterance("hi[ok] you", &VoiceProfile::default(), 1.0);
+        melody_plan(&mut plan, &[0.0, 3.0, 7.0]);
+        let shifts: Vec<_> = plan.iter().map(|p| (p.source.clone(), p.intonation_shift)).collect();
+        assert_eq!(shifts, [
+            (SoundSource::Letter('h'), 0.0),
+            (SoundSource::Letter('i'), 3.0),
+            (SoundSource::Special("ok"), plan[2].intonation_shift),
+            (SoundSource::Letter('y'), 7.0),
+            (SoundSource::Letter('o'), 0.0),
+            (SoundSource::Letter('u'), 3.0),
+        ]);
+
+        let flat = plan_utterance("hi", &VoiceProfile::default(), 0.0);
+        let mut unchanged = flat.clone();
+        melody_plan(&mut unchanged, &[]);
+        assert_eq!(unchanged, flat);
+
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+        engine.speak_melody("hi", &[0.0, 12.0]).unwrap();
+    }
+
+    #[test]
+    fn test_voice_fades() {
+        let plan = plan_utterance("[m1]ab[f1]c", &VoiceProfile::default(), 0.0);
+        let voices: Vec<_> = plan.iter().map(|sound| sound.voice).collect();
+        assert_eq!(voice_fades(&plan, &voices), [
+            VoiceFade { fade_in: false, fade_out_after: Some(Duration::from_millis(100)) },
+            VoiceFade { fade_in: false, fade_out_after: Some(Duration::from_millis(50)) },
+            VoiceFade { fade_in: true, fade_out_after: None },
+        ]);
+
+        // Sound effects and sounds that have already finished are left alone
+        let fades = voice_fades(&plan, &[Some(VoiceType::M1), None, Some(VoiceType::F1)]);
+        assert_eq!(fades[1], VoiceFade::default());
+        let late = [PlannedSound { offset: Duration::from_secs(1), ..plan[2].clone() }];
+        let fades = voice_fades(&[plan[0].clone(), late[0].clone()], &[Some(VoiceType::M1), Some(VoiceType::F1)]);
+        assert_eq!(fades, [VoiceFade::default(), VoiceFade { fade_in: true, fade_out_after: None }]);
+
+        // The crossfade changes the overlap after the switch and nothing else
+        let engine = stream_engine();
+        let render = |voice_change_fade| {
+            engine.set_profile(VoiceProfile { pitch_variation: 0.0, voice_change_fade, ..Default::default() });
+            engine.set_seed(1);
+            engine.render_to_samples_f32("[m1]aa[f1]aa").unwrap()
+        };
+        let hard = render(Duration::ZERO);
+        let faded = render(Duration::from_millis(40));
+        assert_eq!(hard.len(), faded.len());
+        let at = |ms: usize| ms * 441 / 10 * 2;
+        let differs = |range: std::ops::Range<usize>| hard[range.clone()].iter().zip(&faded[range]).any(|(a, b)| (a - b).abs() > 1e-6);
+        assert!(!differs(0..at(95)), "before the switch");
+        assert!(differs(at(100)..at(250)), "while the voices overlap");
+        assert!(!differs(at(260)..hard.len()), "after the old voice has ended");
+    }
+
+    #[test]
+    fn test_speak_panning() {
+        let mut plan = plan_utterance("abcd", &VoiceProfile::default(), 0.0);
+        pan_plan(&mut plan, -1.0, 1.0);
+        for (sound, expected) in plan.iter().zip([-1.0, -1.0 / 3.0, 1.0 / 3.0, 1.0]) {
+            assert!((sound.pan - expected).abs() < 1e-6, "{} != {expected}", sound.pan);
+        }
+        assert_eq!((kira_panning(-1.0), kira_panning(0.0), kira_panning(1.0)), (0.0, 0.5, 1.0));
+
+        // A hard-left letter reaches the mixer with a silent right channel
+        let engine = stream_engine();
+        let stream = engine.sample_stream();
+        engine.set_profile(VoiceProfile { pitch_variation: 0.0, ..Default::default() });
+        let handle = engine.play_planned(&engine.assets, &engine.profile(), &plan[0], Duration::ZERO).unwrap();
+        engine.keep_sound(handle, None);
+        let mut block = vec![0.0f32; 8820];
+        stream.fill(&mut block);
+        let (left, right) = block.chunks_exact(2).fold((0.0f32, 0.0f32), |(l, r), frame| {
+            (l.max(frame[0].abs()), r.max(frame[1].abs()))
+        });
+        assert!(left > 0.01, "{left}");
+        assert_eq!(right, 0.0);
+    }
+
+    #[test]
+    fn test_chorus_plan() {
+        let plan = plan_utterance("hi yo", &VoiceProfile::default(), 0.0);
+        let voices = [VoiceType::F1, VoiceType::M2, VoiceType::F3, VoiceType::M4];
+        let layered = chorus_plan(&plan, &voices, 0.4, &mut rand::thread_rng());
+
+        assert_eq!(layered.len(), plan.len() * voices.len());
+        assert!(layered.windows(2).all(|w| w[0].1.offset <= w[1].1.offset));
+        for voice in 0..voices.len() {
+            let copies: Vec<_> = layered.iter().filter(|(v, _)| *v == voice).map(|(_, s)| s).collect();
+            assert_eq!(copies.len(), plan.len());
+
+            // Each voice keeps its own fixed delay and pitch across the utterance
+            let delay = copies[0].offset - plan[0].offset;
+            let pitch = copies[0].pitch_offset - plan[0].pitch_offset;
+            assert!(delay <= CHORUS_MAX_DELAY);
+            assert!(pitch.abs() <= 0.4);
+            for (copy, sound) in copies.iter().zip(&plan) {
+                assert_eq!(copy.offset, sound.offset + delay);
+                assert!((copy.pitch_offset - sound.pitch_offset - pitch).abs() < 1e-6);
+                assert!((copy.volume - sound.volume * 0.5).abs() < 1e-6);
+            }
+            if voice == 0 {
+                assert_eq!(delay, Duration::ZERO);
+                assert_eq!(pitch, 0.0);
+            }
+        }
+
+        assert!(chorus_plan(&plan, &[], 0.4, &mut rand::thread_rng()).is_empty());
+    }
+
+    #[test]
+    fn test_pitch_track() {
+        let engine = stream_engine();
+        engine.set_profile(VoiceProfile { pitch_shift: 2.0, intonation: 1.0, ..Default::default() });
+
+        // Rising 3 semitones per unit of position over four voiced sounds
+        let track = engine.pitch_track("ab[ok]<pitch=-1>c</pitch>");
+        let expected = [('a', 2.0), ('b', 2.75), ('c', 3.25)];
+        assert_eq!(track.len(), expected.len());
+        for ((c, semitones), (want_c, want)) in track.iter().zip(expected) {
+            assert_eq!(*c, want_c);
+            assert!((semitones - want).abs() < 0.001, "{c}: {semitones} != {want}");
+        }
+
+        // Flat with no intonation, regardless of the random variation setting
+        engine.set_profile(VoiceProfile::default());
+        assert!(engine.pitch_track("hello").iter().all(|(_, s)| *s == 0.0));
+    }
+
+    #[test]
+    fn test_question_shape_curve() {
+        let curve = |shape: QuestionShape, total: usize| -> Vec<f32> {
+            (0..total).map(|i| shape.curve(i as f32, total as f32)).collect()
+        };
+        assert_eq!(curve(QuestionShape::Rising, 4), vec![0.0, 0.25, 0.5, 0.75]);
+
+        // Peaks 80% of the way through, back to the start by the last sound
+        let rise_fall = curve(QuestionShape::RisingFalling, 11);
+        assert_eq!(rise_fall[0], 0.0);
+        assert!((rise_fall[4] - 0.5).abs() < 1e-6);
+        assert!((rise_fall[8] - 1.0).abs() < 1e-6);
+        assert!((rise_fall[9] - 0.5).abs() < 1e-6);
+        assert!(rise_fall[10].abs() < 1e-6);
+        assert_eq!(curve(QuestionShape::RisingFalling, 1), vec![0.0]);
+    }
+
+    #[test]
+    fn test_question_shape_contour() {
+        let profile = VoiceProfile { question_shape: QuestionShape::RisingFalling, ..Default::default() };
+        assert_eq!(resolve_contour("why?", &profile), Contour { glide: 0.5, shape: QuestionShape::RisingFalling });
+        // Explicit intonation and statements keep a plain glide
+        assert_eq!(resolve_contour("why", &profile), Contour::from(0.0));
+        let explicit = VoiceProfile { intonation: 0.4, ..profile.clone() };
+        assert_eq!(resolve_contour("why?", &explicit), Contour::from(0.4));
+
+        let shifts: Vec<f32> = plan_utterance("abcdef", &profile, resolve_contour("abcdef?", &profile))
+            .iter()
+            .map(|sound| sound.intonation_shift)
+            .collect();
+        let peak = shifts.iter().cloned().fold(0.0, f32::max);
+        assert!((peak - 1.5).abs() < 1e-6, "{shifts:?}");
+        assert!(shifts[5].abs() < 1e-6, "{shifts:?}");
+        // The default shape is unchanged
+        let rising = plan_utterance("abcdef", &VoiceProfile::default(), 0.5);
+        assert!(rising.windows(2).all(|w| w[0].intonation_shift < w[1].intonation_shift));
+    }
+
+    #[test]
+    fn test_estimated_duration() {
+        let engine = stream_engine();
+        assert_eq!(engine.estimated_duration(""), Duration::ZERO);
+        // Three letters 50ms apart, the last playing for 200ms
+        assert_eq!(engine.estimated_duration("abc"), Duration::from_millis(300));
+        assert_eq!(engine.estimated_duration("[ok]"), Duration::from_millis(600));
+    }
+
+    #[test]
+    fn test_resolve_intonation() {
+        let profile = VoiceProfile::default();
+        assert_eq!(resolve_intonation("what?", &profile), 0.5);
+        assert_eq!(resolve_intonation("what?  ", &profile), 0.5);
+        assert_eq!(resolve_intonation("what", &profile), 0.0);
+
+        let explicit = VoiceProfile { intonation: -0.4, ..Default::default() };
+        assert_eq!(resolve_intonation("what?", &explicit), -0.4);
+
+        let custom = VoiceProfile { auto_question_intonation: Some(0.9), ..Default::default() };
+        assert_eq!(resolve_intonation("what?", &custom), 0.9);
+        assert_eq!(resolve_intonation("what", &custom), 0.0);
+
+        let disabled = VoiceProfile { auto_question_intonation: None, ..Default::default() };
+        assert_eq!(resolve_intonation("what?", &disabled), 0.0);
+        let disabled = VoiceProfile { intonation: 0.2, ..disabled };
+        assert_eq!(resolve_intonation("what?", &disabled), 0.2);
+    }
+
+    #[test]
+    fn test_detect_question_words() {
+        let profile = VoiceProfile { detect_question_words: true, ..Default::default() };
+        assert_eq!(resolve_intonation("what is this", &profile), 0.5);
+        assert_eq!(resolve_intonation("  \"Are you sure", &profile), 0.5);
+        assert_eq!(resolve_intonation("HOW", &profile), 0.5);
+        assert_eq!(resolve_intonation("this is nice", &profile), 0.0);
+        assert_eq!(resolve_intonation("whatever you say", &profile), 0.0);
+        assert_eq!(resolve_intonation("what a day!", &profile), 0.0);
+        assert_eq!(resolve_intonation("", &profile), 0.0);
+        assert_eq!(resolve_contour("why not", &VoiceProfile { question_shape: QuestionShape::RisingFalling, ..profile.clone() }).shape,
+            QuestionShape::RisingFalling);
+
+        // Off by default, and the profile's own intonation still wins
+        assert_eq!(resolve_intonation("what is this", &VoiceProfile::default()), 0.0);
+        assert_eq!(resolve_intonation("what is this", &VoiceProfile { intonation: -0.3, ..profile }), -0.3);
+    }
+
+    #[test]
+    fn test_auto_terminal_intonation() {
+        // Off by default: only '?' glides
+        let profile = VoiceProfile::default();
+        assert_eq!(resolve_intonation("wow!", &profile), 0.0);
+        assert_eq!(resolve_intonation("I see.", &profile), 0.0);
+
+        let profile = VoiceProfile { auto_terminal_intonation: true, ..Default::default() };
+        assert_eq!(resolve_intonation("wow!", &profile), Intonation::Excited.glide());
+        assert_eq!(resolve_intonation("really?!  ", &profile), Intonation::Excited.glide());
+        assert_eq!(resolve_intonation("I see.", &profile), Intonation::Statement.glide());
+        assert_eq!(resolve_intonation("what?", &profile), 0.5);
+        assert_eq!(resolve_intonation("hmm", &profile), 0.0);
+
+        // An explicit intonation overrides every terminator
+        let explicit = VoiceProfile { intonation: 0.2, ..profile.clone() };
+        for text in ["wow!", "I see.", "what?"] {
+            assert_eq!(resolve_intonation(text, &explicit), 0.2);
+        }
+
+        // Only questions follow question_shape
+        let shaped = VoiceProfile { question_shape: QuestionShape::RisingFalling, ..profile };
+        assert_eq!(resolve_contour("wow!", &shaped).shape, QuestionShape::Rising);
+        assert_eq!(resolve_contour("wow?", &shaped).shape, QuestionShape::RisingFalling);
+    }
+
+    #[test]
+    fn test_voice_profile_default() {
+        let profile = VoiceProfile::default();
+        assert_eq!(profile.voice_type, VoiceType::F1);
+        assert_eq!(profile.pitch_shift, 0.0);
+        assert_eq!(profile.pitch_variation, 0.8);
+        assert_eq!(profile.volume, 0.65);
+        assert_eq!(profile.intonation, 0.0);
+        assert_eq!(profile.tempo, 1.0);
+        assert_eq!(profile.sprite_trim_start, Duration::ZERO);
+        assert_eq!(profile.time_stretch, 1.0);
+        assert!(!profile.acronym_handling);
+        assert_eq!(profile.auto_question_intonation, Some(0.5));
+        assert_eq!(profile.max_word_length, 200);
+        assert_eq!(profile.letter_gap, Duration::from_millis(50));
+        assert!(!profile.reverse_sprites);
+        assert_eq!(profile.sprite_overflow, SpriteOverflow::Error);
+        assert_eq!(profile.intonation_range_semitones, 3.0);
+        assert_eq!(profile.filter, None);
+    }
+
+    #[test]
+    fn test_set_profile_clamps() {
+        let engine = stream_engine();
+        engine.set_profile(VoiceProfile {
+            pitch_shift: 40.0,
+            pitch_variation: -1.0,
+            volume: 50.0,
+            intonation: 9.0,
+            tempo: 0.0,
+            time_stretch: 10.0,
+            intonation_range_semitones: 100.0,
+            declination: -3.0,
+            filter: Some(FilterSettings { kind: FilterKind::Lowpass, cutoff_hz: 1e6 }),
+            ..Default::default()
+        });
+
+        let profile = engine.profile();
+        assert_eq!(profile.pitch_shift, 12.0);
+        assert_eq!(profile.pitch_variation, 0.0);
+        assert_eq!(profile.volume, 1.0);
+        assert_eq!(profile.intonation, 1.0);
+        assert_eq!(profile.tempo, MIN_TEMPO);
+        assert_eq!(profile.time_stretch, dsp::MAX_TIME_STRETCH);
+        assert_eq!(profile.intonation_range_semitones, MAX_INTONATION_RANGE);
+        assert_eq!(profile.declination, 0.0);
+        assert_eq!(profile.filter.map(|f| f.cutoff_hz), Some(dsp::MAX_CUTOFF_HZ));
+
+        let low = VoiceProfile { pitch_shift: -30.0, pitch_variation: 5.0, volume: -1.0, intonation: -2.0, ..Default::default() }.clamped();
+        assert_eq!((low.pitch_shift, low.pitch_variation, low.volume, low.intonation), (-12.0, 2.0, 0.0, -1.0));
+
+        // NaN falls back to the default rather than surviving the clamp
+        let nan = VoiceProfile {
+            pitch_shift: f32::NAN,
+            volume: f32::NAN,
+            tempo: f32::NAN,
+            auto_question_intonation: Some(f32::NAN),
+            filter: Some(FilterSettings { kind: FilterKind::Lowpass, cutoff_hz: f32::NAN }),
+            ..Default::default()
+        }.clamped();
+        let default = VoiceProfile::default();
+        assert_eq!((nan.pitch_shift, nan.volume, nan.tempo), (default.pitch_shift, default.volume, default.tempo));
+        assert_eq!(nan.auto_question_intonation, None);
+        assert_eq!(nan.filter.map(|f| f.cutoff_hz), Some(dsp::MAX_CUTOFF_HZ));
+
+        // In-range profiles are untouched
+        assert_eq!(default.clamped(), default);
+    }
+
+    #[test]
+    fn test_scale_for_tempo() {
+        let letter = Duration::from_millis(200);
+        assert_eq!(scale_for_tempo(letter, 1.0).as_millis(), 200);
+        assert_eq!(scale_for_tempo(letter, 2.0).as_millis(), 100);
+        assert_eq!(scale_for_tempo(letter, 0.5).as_millis(), 400);
+
+        // Out-of-range tempos are clamped
+        assert_eq!(scale_for_tempo(letter, 100.0).as_millis(), 50);
+        assert_eq!(scale_for_tempo(letter, f32::NAN).as_millis(), 200);
+        assert_eq!(scale_for_tempo(letter, 0.0).as_millis(), 800);
+    }
+
+    #[test]
+    fn test_utterance_progress() {
+        let offsets: Vec<_> = [0, 50, 100, 150].into_iter().map(Duration::from_millis).collect();
+        assert_eq!(utterance_progress(&offsets, Duration::ZERO), 0.25);
+        assert_eq!(utterance_progress(&offsets, Duration::from_millis(75)), 0.5);
+        assert_eq!(utterance_progress(&offsets, Duration::from_millis(150)), 1.0);
+        assert_eq!(utterance_progress(&offsets, Duration::from_secs(9)), 1.0);
+        assert_eq!(utterance_progress(&[], Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_progress_while_speaking() {
+        let engine = Arc::new(stream_engine());
+        assert_eq!(engine.progress(), 0.0);
+
+        let speaker = {
+            let engine = engine.clone();
+            std::thread::spawn(move || engine.speak(&"a".repeat(20)).is_ok())
+        };
+        std::thread::sleep(Duration::from_millis(300));
+        let midway = engine.progress();
+        assert!(midway > 0.0 && midway < 1.0, "{midway}");
+
+        assert!(speaker.join().unwrap());
+        assert_eq!(engine.progress(), 0.0);
+    }
+
+    #[test]
+    fn test_try_speak_skips_while_playing() {
+        let engine = Arc::new(stream_engine());
+        let _driver = drive(engine.sample_stream());
+        assert!(!engine.is_playing());
+
+        let speaker = {
+            let engine = engine.clone();
+            std::thread::spawn(move || engine.speak(&"a".repeat(10)).is_ok())
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(engine.is_playing());
+        assert!(!engine.try_speak("bark").unwrap());
+
+        assert!(speaker.join().unwrap());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while engine.is_playing() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(engine.try_speak("bark").unwrap());
+    }
+
+    #[test]
+    fn test_skip_ahead() {
+        let engine = Arc::new(stream_engine());
+        let stream = engine.sample_stream();
+        let _driver = drive(stream);
+
+        // Nothing to skip yet
+        engine.skip_ahead(5);
+
+        // 40 letters take two seconds unskipped
+        let started = Instant::now();
+        let speaker = {
+            let engine = engine.clone();
+            std::thread::spawn(move || engine.speak(&"a".repeat(40)).is_ok())
+        };
+        std::thread::sleep(Duration::from_millis(150));
+        let before = engine.progress();
+        engine.skip_ahead(20);
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while engine.progress() < before + 0.45 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(engine.progress() >= before + 0.45, "{before} -> {}", engine.progress());
+
+        engine.skip_ahead(100);
+        assert!(speaker.join().unwrap());
+        assert!(started.elapsed() < Duration::from_millis(1200), "{:?}", started.elapsed());
+        assert_eq!(engine.progress(), 0.0);
+    }
 
-        let result = self.speak(text);
+    #[test]
+    fn test_fade_value() {
+        let second = Duration::from_secs(1);
+        assert_eq!(fade_value(1.0, 0.0, Duration::ZERO, second), 1.0);
+        assert!((fade_value(1.0, 0.0, Duration::from_millis(250), second) - 0.75).abs() < 0.001);
+        assert!((fade_value(0.2, 0.8, Duration::from_millis(500), second) - 0.5).abs() < 0.001);
+        assert_eq!(fade_value(1.0, 0.0, second, second), 0.0);
+        assert_eq!(fade_value(1.0, 0.0, Duration::from_secs(5), second), 0.0);
+        // Zero-length fades jump straight to the target
+        assert_eq!(fade_value(1.0, 0.3, Duration::ZERO, Duration::ZERO), 0.3);
+    }
 
-        // Restore original intonation
-        if let Ok(mut profile) = self.profile.lock() {
-            profile.intonation = original_intonation;
+    #[test]
+    fn test_stream_backend_renders_letters() {
+        let engine = stream_engine();
+        let stream = engine.sample_stream();
+
+        let mut block = vec![0.0f32; 2048];
+        stream.fill(&mut block);
+        assert!(block.iter().all(|s| *s == 0.0), "nothing played yet");
+
+        engine.play_letter('a').unwrap();
+        let mut heard = false;
+        for _ in 0..8 {
+            stream.fill(&mut block);
+            heard |= block.iter().any(|s| s.abs() > 1e-4);
         }
+        assert!(heard, "letter should render into the stream");
+    }
 
-        result
+    #[test]
+    fn test_voice_files_fallback() {
+        let dir = std::env::temp_dir().join(format!("animalese-wav-pack-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("f1.wav"), b"").unwrap();
+
+        assert_eq!(voice_files(&dir, VoiceType::F1), vec![dir.join("f1.wav")]);
+        assert!(voice_files(&dir, VoiceType::F2).is_empty());
+
+        // Higher priority formats win when several are present
+        std::fs::write(dir.join("f1.ogg"), b"").unwrap();
+        assert_eq!(voice_files(&dir, VoiceType::F1), vec![dir.join("f1.ogg"), dir.join("f1.wav")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    /// Speak text with excitement (higher pitch, rising intonation)
-    ///
-    /// Applies higher pitch and rising intonation for excited or enthusiastic
-    /// speech. Great for exclamations!
-    ///
-    /// # Example
-    /// ```no_run
-    /// use animalese::Animalese;
-    ///
-    /// let engine = Animalese::new().unwrap();
-    /// engine.speak_excited("Amazing!").unwrap();
-    /// ```
-    pub fn speak_excited(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Temporarily boost pitch and add rising intonation
-        let (original_pitch, original_intonation) = {
-            let mut profile = self.profile.lock().unwrap();
-            let orig_pitch = profile.pitch_shift;
-            let orig_intonation = profile.intonation;
-            profile.pitch_shift += 2.0; // Raise pitch by 2 semitones
-            profile.intonation = 0.4; // Gentle rising intonation
-            (orig_pitch, orig_intonation)
+    #[test]
+    fn test_available_voices() {
+        assert_eq!(stream_engine().available_voices(), VoiceType::ALL.to_vec());
+
+        let pack = temp_pack("partial-pack", &[]);
+        std::fs::write(pack.voice.join("f2.wav"), b"").unwrap();
+        std::fs::write(pack.voice.join("m3.flac"), b"").unwrap();
+        std::fs::create_dir(pack.voice.join("m1.ogg")).unwrap();
+
+        let engine = pack.engine();
+        assert!(engine.has_voice(VoiceType::F2));
+        assert!(!engine.has_voice(VoiceType::F1));
+        assert!(!engine.has_voice(VoiceType::M1), "directories aren't voice files");
+        assert_eq!(engine.available_voices(), vec![VoiceType::F2, VoiceType::M3]);
+    }
+
+    #[test]
+    fn test_play_sfx_with_overrides() {
+        let engine = stream_engine();
+        let stream = engine.sample_stream();
+        let peak = |stream: &SampleStream| {
+            let mut block = vec![0.0f32; 8192];
+            let mut peak = 0.0f32;
+            for _ in 0..8 {
+                stream.fill(&mut block);
+                peak = block.iter().fold(peak, |p, s| p.max(s.abs()));
+            }
+            peak
         };
 
-        let result = self.speak(text);
+        engine.play_sfx_with("enter", 0.0, 0.0).unwrap();
+        assert_eq!(peak(&stream), 0.0);
 
-        // Restore original settings
-        if let Ok(mut profile) = self.profile.lock() {
-            profile.pitch_shift = original_pitch;
-            profile.intonation = original_intonation;
-        }
+        engine.play_sfx_with("enter", 1.0, 3.0).unwrap();
+        assert!(peak(&stream) > 1e-4);
 
-        result
+        assert!(engine.play_sfx_with("nope", 1.0, 0.0).is_err());
+        assert_eq!(engine.profile().volume, VoiceProfile::default().volume);
     }
 
-    /// Speak text with falling intonation (for statements)
-    ///
-    /// Applies a gentle falling pitch contour, typical of declarative
-    /// statements and confident assertions.
-    ///
-    /// # Example
-    /// ```no_run
-    /// use animalese::Animalese;
-    ///
-    /// let engine = Animalese::new().unwrap();
-    /// engine.speak_statement("I see").unwrap();
-    /// ```
-    pub fn speak_statement(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
-        // Temporarily set intonation to falling
-        let original_intonation = {
-            let mut profile = self.profile.lock().unwrap();
-            let original = profile.intonation;
-            profile.intonation = -0.3; // Gentle falling intonation
-            original
+    #[test]
+    fn test_on_idle_fires_once_per_transition() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+        let idles = Arc::new(AtomicUsize::new(0));
+        let counter = idles.clone();
+        engine.set_on_idle(Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        // Idle to begin with, so nothing fires
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(idles.load(Ordering::SeqCst), 0);
+
+        let wait_for = |count: usize| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while idles.load(Ordering::SeqCst) < count && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
         };
 
-        let result = self.speak(text);
+        engine.speak_blocking("hi").unwrap();
+        wait_for(1);
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(idles.load(Ordering::SeqCst), 1, "not again while idle");
 
-        // Restore original intonation
-        if let Ok(mut profile) = self.profile.lock() {
-            profile.intonation = original_intonation;
+        engine.play_letter('a').unwrap();
+        wait_for(2);
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(idles.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_on_idle_reregistered_from_callback() {
+        fn counting(engine: std::sync::Weak<Animalese<StreamBackend>>, idles: Arc<AtomicUsize>) -> IdleCallback {
+            Box::new(move || {
+                idles.fetch_add(1, Ordering::SeqCst);
+                if let Some(engine) = engine.upgrade() {
+                    engine.set_on_idle(counting(Arc::downgrade(&engine), idles.clone()));
+                }
+            })
         }
 
-        result
+        let engine = Arc::new(stream_engine());
+        let _driver = drive(engine.sample_stream());
+        let idles = Arc::new(AtomicUsize::new(0));
+        engine.set_on_idle(counting(Arc::downgrade(&engine), idles.clone()));
+
+        // A second watcher would fire too, counting each transition twice
+        for expected in 1..=3 {
+            engine.play_letter('a').unwrap();
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while idles.load(Ordering::SeqCst) < expected && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+            assert_eq!(idles.load(Ordering::SeqCst), expected);
+        }
     }
 
-    /// Internal method to play a sprite with kira
-    fn play_sprite(&self, audio_path: &str, start: Duration, duration: Duration, apply_pitch: bool, max_duration: Option<Duration>, intonation_shift: f32) -> Result<(), Box<dyn std::error::Error>> {
-        // Determine the full file path
-        let file_path = if audio_path.ends_with(".ogg") {
-            // It's already a full path to sfx.ogg
-            audio_path.to_string()
-        } else {
-            // It's the voice directory, append the voice filename
-            let profile = self.profile.lock().unwrap();
-            let filename = profile.voice_type.filename();
-            Path::new(audio_path).join(filename)
-                .to_string_lossy()
-                .to_string()
+    #[test]
+    fn test_speak_nothing_is_a_no_op() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+        let idles = Arc::new(AtomicUsize::new(0));
+        let counter = idles.clone();
+        engine.set_on_idle(Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        for text in ["", "   ", "\n", "?!."] {
+            assert!(engine.plan(text).is_empty(), "{text:?}");
+            engine.speak(text).unwrap();
+            engine.speak_blocking(text).unwrap();
+            engine.speak_question(text).unwrap();
+            engine.speak_excited(text).unwrap();
+            engine.speak_statement(text).unwrap();
+            engine.speak_with_intonation(text, Intonation::Custom(0.7)).unwrap();
+            engine.speak_paced(text, Duration::from_millis(80)).unwrap();
+            engine.speak_reversed(text).unwrap();
+            engine.speak_chorus(text, &[VoiceType::F1, VoiceType::M2], 0.3).unwrap();
+            engine.speak_varied(text, 1).unwrap();
+            engine.speak_lines(&[text], |_| {}).unwrap();
+            engine.speak_reader(text.as_bytes()).unwrap();
+            assert!(engine.speak_capturing(text).unwrap().samples.is_empty());
+            assert!(engine.render_to_samples(text).unwrap().samples.is_empty());
+
+            assert!(engine.active_sounds.lock().unwrap().is_empty(), "{text:?} queued a sound");
+            assert!(engine.utterance.lock().unwrap().is_none());
+        }
+
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(idles.load(Ordering::SeqCst), 0, "never became busy");
+    }
+
+    #[test]
+    fn test_sprite_coverage() {
+        let engine = stream_engine();
+        let coverage = engine.sprite_coverage(&["Hello, world!", "[ok] HA", "🐸 llama"]);
+
+        assert_eq!(coverage.len(), 26);
+        assert_eq!(coverage[&'l'], 3 + 2);
+        assert_eq!(coverage[&'h'], 2);
+        assert_eq!(coverage[&'a'], 1 + 2);
+        assert_eq!(coverage[&'o'], 2, "the [ok] special isn't a letter");
+        assert_eq!(coverage[&'z'], 0);
+        assert_eq!(coverage.values().sum::<usize>(), 10 + 2 + 5);
+    }
+
+    #[test]
+    fn test_min_retrigger_gap() {
+        let engine = stream_engine();
+        let queued = || engine.active_sounds.lock().unwrap().len();
+        engine.play_letter('a').unwrap();
+        engine.play_letter('a').unwrap();
+        assert_eq!(queued(), 2, "off by default");
+
+        // Long enough that slow sprite decoding can't outlast it
+        engine.set_profile(VoiceProfile { min_retrigger_gap: Duration::from_secs(10), ..Default::default() });
+        engine.play_letter('b').unwrap();
+        engine.play_letter('B').unwrap();
+        assert_eq!(queued(), 3, "a repeat within the gap is dropped");
+        engine.play_letter('a').unwrap();
+        engine.play_letter('b').unwrap();
+        assert_eq!(queued(), 5, "only back-to-back repeats count");
+
+        engine.speak("bb").unwrap();
+        assert_eq!(queued(), 7, "speak() isn't debounced");
+    }
+
+    #[test]
+    fn test_max_concurrent_sounds() {
+        let engine = stream_engine();
+        assert_eq!(engine.max_concurrent_sounds(), None);
+
+        // Nothing pulls the stream, so every sound stays playing
+        engine.set_max_concurrent_sounds(Some(2));
+        for c in "abcde".chars() {
+            engine.play_letter(c).unwrap();
+        }
+        engine.play_sfx("enter").unwrap();
+        assert_eq!(engine.concurrent_sounds(), 2, "the rest were dropped");
+
+        engine.set_max_concurrent_sounds(None);
+        engine.play_sfx("enter").unwrap();
+        assert_eq!(engine.concurrent_sounds(), 3);
+
+        engine.set_max_concurrent_sounds(Some(0));
+        assert_eq!(engine.max_concurrent_sounds(), Some(1));
+    }
+
+    #[test]
+    fn test_voice_trim() {
+        assert_eq!(trimmed_volume(0.5, 0.0), 0.5);
+        assert!((trimmed_volume(0.5, -6.0206) - 0.25).abs() < 1e-4);
+        assert!((trimmed_volume(0.5, 20.0) - 5.0).abs() < 1e-4);
+        assert!(trimmed_volume(1.0, MIN_VOICE_TRIM_DB) <= 0.001);
+
+        let engine = stream_engine();
+        engine.set_voice_trim(VoiceType::M2, -3.0);
+        engine.set_voice_trim(VoiceType::F1, 40.0);
+        assert_eq!((engine.voice_trim(VoiceType::M2), engine.voice_trim(VoiceType::F1)), (-3.0, MAX_VOICE_TRIM_DB));
+        engine.set_voice_trim(VoiceType::M2, 0.0);
+        assert_eq!(engine.voice_trim(VoiceType::M2), 0.0);
+        assert!(!engine.voice_trims.lock().unwrap().contains_key(&VoiceType::M2));
+
+        // A -6dB trim halves the rendered voice, and renders follow the engine
+        engine.set_profile(VoiceProfile { pitch_variation: 0.0, ..Default::default() });
+        let render = || {
+            engine.set_seed(3);
+            engine.render_to_samples_f32("hi").unwrap()
         };
+        engine.set_voice_trim(VoiceType::F1, 0.0);
+        let full = render();
+        engine.set_voice_trim(VoiceType::F1, -6.0206);
+        let trimmed = render();
+        assert_eq!(full.len(), trimmed.len());
+        assert!(full.iter().zip(&trimmed).all(|(a, b)| (a * 0.5 - b).abs() < 1e-4));
+        assert!(full.iter().any(|s| s.abs() > 0.01));
+    }
 
-        // Calculate parameters
-        let actual_duration = max_duration.unwrap_or(duration);
+    #[test]
+    fn test_replay_last() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+        engine.replay_last().unwrap();
+        assert!(engine.last_spoken.lock().unwrap().is_none(), "nothing spoken yet");
 
-        // Load sound data and slice to extract only the sprite region
-        let start_time = start.as_secs_f64();
-        let end_time = start_time + actual_duration.as_secs_f64();
-        let mut sound_data = StaticSoundData::from_file(&file_path)?
-            .slice(start_time..end_time);
+        let profile = VoiceProfile { voice_type: VoiceType::M3, pitch_shift: 4.0, ..Default::default() };
+        engine.set_profile(profile.clone());
+        engine.speak("hi").unwrap();
+        engine.set_profile(VoiceProfile::default());
 
-        if apply_pitch {
-            let profile = self.profile.lock().unwrap();
-            let mut rng = rand::thread_rng();
-            let random_variation = rng.gen_range(-1.0..=1.0) * profile.pitch_variation;
-            let final_pitch = profile.pitch_shift + random_variation + intonation_shift;
-            let playback_rate = semitones_to_rate(final_pitch);
-            let volume = profile.volume;
+        // The stored plan and original profile are queued again
+        let last = engine.last_spoken.lock().unwrap().clone().unwrap();
+        assert_eq!(last.plan, plan_utterance("hi", &profile, resolve_contour("hi", &profile)));
+        assert_eq!((last.profiles.as_slice(), last.profile_of.as_slice()), (std::slice::from_ref(&profile), [0, 0].as_slice()));
+        engine.replay_last().unwrap();
+        let replayed = engine.last_spoken.lock().unwrap().clone().unwrap();
+        assert_eq!((&replayed.plan, &replayed.profiles), (&last.plan, &last.profiles));
 
-            // Configure sound with pitch and volume
-            sound_data = sound_data
-                .playback_rate(playback_rate as f64)
-                .volume(Volume::Amplitude(volume as f64))
-                .fade_in_tween(Tween {
-                    duration: std::time::Duration::from_millis(5),
-                    ..Default::default()
-                });
-        } else {
-            let profile = self.profile.lock().unwrap();
-            let volume = profile.volume;
+        // Per-sentence profiles are kept apart
+        engine.speak_varied("One. Two. Three.", 5).unwrap();
+        let last = engine.last_spoken.lock().unwrap().clone().unwrap();
+        assert!(last.profiles.len() >= 2, "{}", last.profiles.len());
+        assert_eq!(last.profile_of.len(), last.plan.len());
+    }
 
-            sound_data = sound_data
-                .volume(Volume::Amplitude(volume as f64))
-                .fade_in_tween(Tween {
-                    duration: std::time::Duration::from_millis(5),
-                    ..Default::default()
-                });
+    #[test]
+    fn test_is_audible() {
+        let engine = stream_engine();
+        assert!(engine.is_audible());
+
+        engine.set_enabled(false);
+        assert!(!engine.is_audible());
+        engine.set_enabled(true);
+
+        engine.set_master_volume(0.0);
+        assert!(!engine.is_audible());
+        engine.set_master_volume(1.0);
+
+        engine.set_profile(VoiceProfile { volume: 0.0, ..Default::default() });
+        assert!(!engine.is_audible());
+        engine.set_profile(VoiceProfile { volume: 0.01, ..Default::default() });
+        engine.set_master_volume(0.01);
+        assert!(!engine.is_audible(), "-80 dB overall");
+        engine.set_master_volume(0.5);
+        assert!(engine.is_audible());
+        engine.set_voice_trim(VoiceType::F1, -40.0);
+        assert!(!engine.is_audible());
+    }
+
+    #[test]
+    fn test_disabled_queues_nothing() {
+        let engine = stream_engine();
+        let clip = engine.prepare("hi").unwrap();
+        engine.set_enabled(false);
+        assert!(!engine.is_enabled());
+
+        engine.speak("hello there").unwrap();
+        engine.speak_blocking("hello").unwrap();
+        engine.play_letter('a').unwrap();
+        engine.play_special("ok").unwrap();
+        engine.play_sfx("enter").unwrap();
+        engine.play_sfx_looping("default").unwrap();
+        engine.play_clip(&clip).unwrap();
+        assert!(engine.active_sounds.lock().unwrap().is_empty());
+        assert!(engine.sfx_loop.lock().unwrap().is_none());
+        assert!(engine.is_silent());
+
+        engine.set_enabled(true);
+        engine.play_letter('a').unwrap();
+        assert_eq!(engine.active_sounds.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sfx_banks() {
+        let engine = stream_engine();
+        let sheet = bundled_assets_path().parent().unwrap().join("sfx.ogg");
+        let ms = Duration::from_millis;
+
+        assert!(engine.play_sfx("ui:click").is_err(), "unregistered bank");
+        engine.register_sfx_bank("ui", &sheet, SpriteLayout::sequential(["click", "hover"], ms(300))).unwrap();
+        engine.play_sfx("ui:hover").unwrap();
+        engine.play_sfx_with("ui:click", 0.5, 2.0).unwrap();
+        assert!(engine.play_sfx("ui:enter").is_err(), "not in the ui layout");
+        engine.play_sfx("enter").unwrap();
+
+        // Validated at registration
+        assert!(engine.register_sfx_bank("a:b", &sheet, SpriteLayout::builtin_sfx()).is_err());
+        let missing = engine.register_sfx_bank("game", Path::new("/no/such/bank.ogg"), SpriteLayout::builtin_sfx());
+        assert!(matches!(
+            missing.unwrap_err().downcast_ref::<AnimaleseError>(),
+            Some(AnimaleseError::AssetNotFound(_))
+        ));
+        let too_long = SpriteLayout::with_offsets([("late", Duration::from_secs(3600))], ms(600));
+        let out_of_range = engine.register_sfx_bank("game", &sheet, too_long);
+        assert!(matches!(
+            out_of_range.unwrap_err().downcast_ref::<AnimaleseError>(),
+            Some(AnimaleseError::SpriteOutOfRange { .. })
+        ));
+        assert!(engine.play_sfx("game:late").is_err());
+    }
+
+    #[test]
+    fn test_emoji_sfx_checked_when_played() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+        let sheet = bundled_assets_path().parent().unwrap().join("sfx.ogg");
+        let mut profile = VoiceProfile::default();
+        profile.emoji_sfx.insert('🔔', "ui:bell".to_string());
+        engine.set_profile(profile);
+
+        // Fails before the letters play, not halfway through
+        assert!(engine.speak("hi 🔔").is_err(), "unregistered bank");
+        assert!(engine.active_sounds.lock().unwrap().is_empty());
+
+        engine.register_sfx_bank("ui", &sheet, SpriteLayout::sequential(["bell"], Duration::from_millis(300))).unwrap();
+        engine.speak("hi 🔔").unwrap();
+        let queued_bell = engine.active_sounds.lock().unwrap().iter()
+            .any(|sound| sound.sprite.is_some_and(|(_, index)| index == 2));
+        assert!(queued_bell);
+    }
+
+    #[test]
+    fn test_speak_lines_callbacks() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+
+        let mut finished = Vec::new();
+        engine.speak_lines(&["hi", "", "  ", "yo"], |i| finished.push(i)).unwrap();
+        assert_eq!(finished, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_speak_lines_stop() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
+
+        let mut finished = Vec::new();
+        engine.speak_lines(&["hi", "there", "friend"], |i| {
+            finished.push(i);
+            engine.stop();
+        }).unwrap();
+        assert_eq!(finished, vec![0]);
+    }
+
+    #[test]
+    fn test_drain_utf8() {
+        let mut bytes = "hé".as_bytes().to_vec();
+        let tail = bytes.pop().unwrap();
+        assert_eq!(drain_utf8(&mut bytes), "h");
+        assert_eq!(bytes.len(), 1, "half of é waits for the rest");
+
+        bytes.push(tail);
+        bytes.extend_from_slice(b"!\xffok");
+        assert_eq!(drain_utf8(&mut bytes), "é!\u{FFFD}ok");
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_speak_reader() {
+        /// Hands out one byte per read, splitting every multi-byte character
+        struct Trickle<'a>(&'a [u8], usize);
+
+        impl std::io::Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let Some(byte) = self.0.get(self.1) else {
+                    return Ok(0);
+                };
+                buf[0] = *byte;
+                self.1 += 1;
+                Ok(1)
+            }
         }
 
-        // Play the sound
-        let mut manager = self.manager.lock().unwrap();
-        let handle = manager.play(sound_data)?;
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
 
-        // Store handle to keep it alive
-        let mut active = self.active_sounds.lock().unwrap();
-        active.push(handle);
+        let mut reader = Trickle("héllo\nwörld 🎉".as_bytes(), 0);
+        engine.speak_reader(&mut reader).unwrap();
+        assert_eq!(reader.1, reader.0.len());
+        assert!(engine.is_silent());
+    }
 
-        // Clean up finished sounds
-        active.retain(|h| h.state() != kira::sound::PlaybackState::Stopped);
+    #[test]
+    fn test_speak_capturing() {
+        let engine = stream_engine();
+        let _driver = drive(engine.sample_stream());
 
-        Ok(())
+        let capture = engine.speak_capturing("hello").unwrap();
+        assert_eq!(capture.sample_rate, 44_100);
+        assert!(capture.duration() >= Duration::from_millis(400), "{:?}", capture.duration());
+        assert!(capture.samples.iter().any(|s| s.unsigned_abs() > 100));
+
+        // Nothing is recorded outside a capture
+        engine.speak_blocking("hi").unwrap();
+        assert!(engine.speak_capturing("").unwrap().samples.iter().all(|s| *s == 0));
     }
 
-    /// Stop all currently playing sounds
-    pub fn stop(&self) {
-        let mut active = self.active_sounds.lock().unwrap();
-        for handle in active.iter_mut() {
-            let _ = handle.stop(Tween::default());
+    #[test]
+    fn test_render_to_samples() {
+        let engine = stream_engine();
+        engine.set_profile(VoiceProfile { pitch_variation: 0.0, ..Default::default() });
+        let clip = engine.render_to_samples("hello").unwrap();
+
+        // Five letters 50ms apart, the last one playing for 200ms
+        assert_eq!(clip.sample_rate, 44_100);
+        assert!(clip.duration() >= Duration::from_millis(400), "{:?}", clip.duration());
+        assert!(clip.duration() < Duration::from_millis(600), "{:?}", clip.duration());
+        assert!(clip.samples.iter().any(|s| s.unsigned_abs() > 100));
+
+        assert!(engine.render_to_samples("").unwrap().samples.is_empty());
+    }
+
+    #[test]
+    fn test_render_to_samples_f32() {
+        let engine = stream_engine();
+        engine.set_profile(VoiceProfile { pitch_variation: 0.0, ..Default::default() });
+        let samples = engine.render_to_samples_f32("hello").unwrap();
+        let clip = engine.render_to_samples("hello").unwrap();
+
+        // The same audio, short of the final rounding to 16 bits
+        assert_eq!(samples.len(), clip.samples.len());
+        let rounded: Vec<i16> = samples.iter().map(|s| capture::to_i16(*s)).collect();
+        assert_eq!(rounded, clip.samples);
+        assert!(samples.iter().any(|s| (s * i16::MAX as f32).fract() != 0.0), "finer than 16 bits");
+    }
+
+    #[test]
+    fn test_keepalive() {
+        let engine = stream_engine();
+        let stream = engine.sample_stream();
+        let peak = |stream: &SampleStream| {
+            let mut block = vec![0.0f32; 4096];
+            let mut peak = 0.0f32;
+            for _ in 0..4 {
+                stream.fill(&mut block);
+                peak = block.iter().fold(peak, |p, s| p.max(s.abs()));
+            }
+            peak
+        };
+
+        assert!(!engine.keepalive());
+        engine.set_keepalive(true).unwrap();
+        engine.set_keepalive(true).unwrap();
+        assert!(engine.keepalive());
+        let level = peak(&stream);
+        assert!(level > 0.0 && level < 1e-4, "{level}");
+
+        // Survives stop() and doesn't count as speech still playing
+        engine.stop();
+        assert!(peak(&stream) > 0.0);
+        let _driver = drive(engine.sample_stream());
+        engine.speak_blocking("a").unwrap();
+        drop(_driver);
+
+        engine.set_keepalive(false).unwrap();
+        assert!(!engine.keepalive());
+        peak(&stream); // let the stop fade finish
+        assert_eq!(peak(&stream), 0.0);
+    }
+
+    #[test]
+    fn test_sfx_loop() {
+        let engine = stream_engine();
+        let stream = engine.sample_stream();
+        // Peak over about 0.75s, longer than one 600ms sprite
+        let peak = |stream: &SampleStream| {
+            let mut block = vec![0.0f32; 8192];
+            let mut peak = 0.0f32;
+            for _ in 0..8 {
+                stream.fill(&mut block);
+                peak = block.iter().fold(peak, |p, s| p.max(s.abs()));
+            }
+            peak
+        };
+
+        assert!(engine.play_sfx_looping("nope").is_err());
+        engine.play_sfx_looping("default").unwrap();
+        for _ in 0..4 {
+            assert!(peak(&stream) > 1e-3, "still looping");
         }
-        active.clear();
+
+        // Runs independently of speech
+        engine.stop();
+        assert!(peak(&stream) > 1e-3);
+        assert!(engine.is_silent());
+        assert!(!engine.is_playing(), "the loop doesn't count as playing");
+
+        engine.stop_sfx_loop();
+        peak(&stream); // let the stop fade finish
+        assert_eq!(peak(&stream), 0.0);
+        engine.stop_sfx_loop();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::time::Duration;
+    #[test]
+    fn test_prepare_and_play_clip() {
+        let engine = stream_engine();
+        engine.set_profile(VoiceProfile { pitch_variation: 0.0, ..Default::default() });
+        let clip = engine.prepare("hello").unwrap();
+        assert_eq!(clip.duration(), engine.render_to_samples("hello").unwrap().duration());
+
+        // Later profile changes don't reach the clip
+        engine.set_profile(VoiceProfile { volume: 0.0, ..Default::default() });
+        let stream = engine.sample_stream();
+        engine.play_clip(&clip).unwrap();
+        let mut block = vec![0.0f32; 4096];
+        let mut peak = 0.0f32;
+        for _ in 0..8 {
+            stream.fill(&mut block);
+            peak = block.iter().fold(peak, |p, s| p.max(s.abs()));
+        }
+        assert!(peak > 1e-3, "{peak}");
+    }
 
     #[test]
-    fn test_letter_to_sprite_time() {
-        assert_eq!(letter_to_sprite_time('a'), Some(Duration::from_millis(0)));
-        assert_eq!(letter_to_sprite_time('b'), Some(Duration::from_millis(200)));
-        assert_eq!(letter_to_sprite_time('z'), Some(Duration::from_millis(5000)));
-        assert_eq!(letter_to_sprite_time('A'), Some(Duration::from_millis(0)));
-        assert_eq!(letter_to_sprite_time('1'), None);
+    fn test_play_samples() {
+        let engine = stream_engine();
+        let stream = engine.sample_stream();
+        let peak = |stream: &SampleStream| {
+            let mut block = vec![0.0f32; 4096];
+            let mut peak = 0.0f32;
+            for _ in 0..4 {
+                stream.fill(&mut block);
+                peak = block.iter().fold(peak, |p, s| p.max(s.abs()));
+            }
+            peak
+        };
+        let tone: Vec<i16> = (0..4410)
+            .map(|n| ((n as f32 * 440.0 * std::f32::consts::TAU / 44_100.0).sin() * 16384.0) as i16)
+            .flat_map(|s| [s, s])
+            .collect();
+
+        // Mixed at the profile volume
+        engine.set_profile(VoiceProfile { volume: 0.5, pitch_variation: 0.0, ..Default::default() });
+        engine.play_samples(tone.clone(), 44_100, false).unwrap();
+        let level = peak(&stream);
+        assert!((level - 0.25).abs() < 0.02, "{level}");
+
+        engine.play_samples(tone.clone(), 44_100, true).unwrap();
+        assert!(peak(&stream) > 0.2);
+
+        engine.set_enabled(false);
+        engine.play_samples(tone.clone(), 44_100, false).unwrap();
+        assert_eq!(peak(&stream), 0.0);
+        engine.set_enabled(true);
+
+        assert!(engine.play_samples(vec![0; 3], 44_100, false).is_err());
+        assert!(engine.play_samples(tone, 0, false).is_err());
     }
 
     #[test]
-    fn test_semitones_to_rate() {
-        assert!((semitones_to_rate(0.0) - 1.0).abs() < 0.001);
-        assert!((semitones_to_rate(12.0) - 2.0).abs() < 0.001);
-        assert!((semitones_to_rate(-12.0) - 0.5).abs() < 0.001);
+    fn test_stretch_sprite_uses_slice() {
+        let data = StaticSoundData::from_file(bundled_assets_path().join("f1.ogg")).unwrap();
+        let sprite = data.slice(0.2..0.4);
+        let (start, end) = sprite.slice.unwrap();
+
+        let stretched = stretch_sprite(&sprite, 0.6);
+        assert_eq!(stretched.slice, None);
+        assert_eq!(stretched.num_frames(), ((end - start) as f32 * 0.6).round() as usize);
     }
 
     #[test]
-    fn test_special_sounds() {
-        assert_eq!(special_to_sprite_time("ok"), Some(Duration::from_millis(5200)));
-        assert_eq!(special_to_sprite_time("gwah"), Some(Duration::from_millis(5800)));
-        assert_eq!(special_to_sprite_time("deska"), Some(Duration::from_millis(6400)));
-        assert_eq!(special_to_sprite_time("unknown"), None);
+    fn test_reverse_plan() {
+        let profile = VoiceProfile::default();
+        let plan = plan_utterance("ab<pause=100ms>c", &profile, 1.0);
+        let reversed = reverse_plan(&plan);
+
+        let sources: Vec<_> = reversed.iter().map(|p| p.source.clone()).collect();
+        assert_eq!(sources, vec![SoundSource::Letter('c'), SoundSource::Letter('b'), SoundSource::Letter('a')]);
+        let offsets: Vec<_> = reversed.iter().map(|p| p.offset.as_millis()).collect();
+        assert_eq!(offsets, vec![0, 150, 200]);
+        assert_eq!(reversed[0].intonation_shift, plan[2].intonation_shift);
+
+        assert!(reverse_plan(&[]).is_empty());
     }
 
     #[test]
-    fn test_voice_profile_default() {
+    fn test_reverse_sprite() {
+        let data = StaticSoundData::from_file(bundled_assets_path().join("f1.ogg")).unwrap();
+        let sprite = data.slice(0.2..0.4);
+        let (start, end) = sprite.slice.unwrap();
+
+        let reversed = reverse_sprite(&sprite);
+        assert_eq!(reversed.num_frames(), end - start);
+        assert_eq!(reversed.frames[0], data.frames[end - 1]);
+        assert_eq!(reversed.frames[end - start - 1], data.frames[start]);
+    }
+
+    #[test]
+    fn test_preserve_formants() {
+        let data = StaticSoundData::from_file(bundled_assets_path().join("f1.ogg")).unwrap();
+        let sprite = data.slice(0.0..0.2);
+        let (start, end) = sprite.slice.unwrap();
+        let shifted = formant_shift_sprite(&sprite, 1.5);
+        assert_eq!(shifted.slice, None);
+        assert_eq!(shifted.num_frames(), end - start);
+
+        let engine = stream_engine();
+        let profile = VoiceProfile { pitch_shift: 7.0, pitch_variation: 0.0, volume_variation: 0.0, ..Default::default() };
+        engine.set_profile(profile.clone());
+        let sped_up = engine.render_to_samples_f32("aha").unwrap();
+        engine.set_profile(VoiceProfile { preserve_formants: true, ..profile });
+        let preserved = engine.render_to_samples_f32("aha").unwrap();
+        // Sped up, a sprite pitched this far runs out of its slot and ends early
+        assert!(preserved.len() > sped_up.len());
+        assert!(preserved.iter().any(|s| s.abs() > 0.01));
+    }
+
+    #[test]
+    fn test_schedule_at_lookahead() {
         let profile = VoiceProfile::default();
-        assert_eq!(profile.voice_type, VoiceType::F1);
-        assert_eq!(profile.pitch_shift, 0.0);
-        assert_eq!(profile.pitch_variation, 0.8);
-        assert_eq!(profile.volume, 0.65);
-        assert_eq!(profile.intonation, 0.0);
+        let plan = plan_utterance("abcdef", &profile, 0.0);
+
+        // The first `lookahead` sounds go out immediately
+        assert_eq!(schedule_at(&plan, 0, 2), Duration::ZERO);
+        assert_eq!(schedule_at(&plan, 1, 2), Duration::ZERO);
+        // Later ones wait for the sound two places earlier to start
+        assert_eq!(schedule_at(&plan, 2, 2), plan[0].offset);
+        assert_eq!(schedule_at(&plan, 5, 2), plan[3].offset);
+        // A lookahead of 0 behaves like 1
+        assert_eq!(schedule_at(&plan, 3, 0), plan[2].offset);
     }
 
     #[test]
-    fn test_intonation_values() {
-        let mut profile = VoiceProfile::default();
+    fn test_stop_interrupts_speak() {
+        let engine = Arc::new(stream_engine());
+
+        // 2000 letters is well over a minute of speech
+        let long_text = "a".repeat(2000);
+        let speaker = {
+            let engine = engine.clone();
+            std::thread::spawn(move || engine.speak(&long_text).is_ok())
+        };
+
+        std::thread::sleep(Duration::from_millis(100));
+        let stopped = Instant::now();
+        engine.stop();
+        assert!(speaker.join().unwrap());
+        assert!(stopped.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_stop_silences_long_backlog() {
+        let engine = Arc::new(stream_engine());
+        engine.set_profile(VoiceProfile { max_word_length: 0, ..Default::default() });
+        let driver = drive(engine.sample_stream());
+
+        let speaker = {
+            let engine = engine.clone();
+            std::thread::spawn(move || engine.speak(&"a".repeat(1000)).is_ok())
+        };
+        std::thread::sleep(Duration::from_millis(200));
+        engine.stop();
+        assert!(speaker.join().unwrap());
+        assert!(!engine.is_playing(), "nothing is left queued");
+
+        // Pull the mixer by hand from here: past the stop fade, the rest of
+        // the 1000 letters never sound
+        drop(driver);
+        std::thread::sleep(Duration::from_millis(20));
+        let stream = engine.sample_stream();
+        let mut fade = vec![0.0f32; 2 * 2205];
+        stream.fill(&mut fade);
+        let mut after = vec![0.0f32; 2 * 44_100];
+        stream.fill(&mut after);
+        assert!(after.iter().all(|&sample| sample == 0.0));
+    }
+
+    #[test]
+    fn test_long_word_is_capped() {
+        let word = "a".repeat(100_000);
+        let profile = VoiceProfile::default();
+        assert_eq!(plan_utterance(&word, &profile, 0.0).len(), 200);
+        assert_eq!(plan_utterance(&format!("{word} hi {word}"), &profile, 0.0).len(), 402);
+
+        let unlimited = VoiceProfile { max_word_length: 0, ..Default::default() };
+        assert_eq!(plan_utterance(&word, &unlimited, 0.0).len(), 100_000);
+
+        // Even uncapped, stop() cuts it short promptly
+        let engine = Arc::new(stream_engine());
+        engine.set_profile(unlimited);
+        let speaker = {
+            let engine = engine.clone();
+            std::thread::spawn(move || engine.speak(&word).is_ok())
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        let stopped = Instant::now();
+        engine.stop();
+        assert!(speaker.join().unwrap());
+        assert!(stopped.elapsed() < Duration::from_millis(500));
+    }
 
+    #[test]
+    fn test_engine_is_shareable() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Animalese>();
+        assert_send_sync::<Animalese<StreamBackend>>();
+    }
+
+    #[test]
+    fn test_missing_assets_error() {
+        let missing = std::env::temp_dir().join("animalese-missing-assets").join("voice");
+        let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings::default()).unwrap();
+        let err = Animalese::with_audio_manager(missing.to_string_lossy(), manager).err().unwrap();
+        assert_eq!(err.downcast_ref(), Some(&AnimaleseError::AssetNotFound(missing)));
+
+        // Voice directory present but no sfx.ogg beside it
+        let root = std::env::temp_dir().join(format!("animalese-no-sfx-{}", std::process::id()));
+        let voice = root.join("voice");
+        std::fs::create_dir_all(&voice).unwrap();
+        let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings::default()).unwrap();
+        let err = Animalese::with_audio_manager(voice.to_string_lossy(), manager).err().unwrap();
+        assert_eq!(err.downcast_ref(), Some(&AnimaleseError::AssetNotFound(root.join("sfx.ogg"))));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_device_error() {
+        let err = AnimaleseBuilder::new().device("no such device, surely").build().err().unwrap();
+        assert_eq!(
+            err.downcast_ref(),
+            Some(&AnimaleseError::DeviceNotFound("no such device, surely".to_string()))
+        );
+
+        // Listing never fails, even with no audio hardware
+        let _ = list_output_devices();
+    }
+
+    #[test]
+    fn test_intonation_presets() {
+        assert_eq!(Intonation::Flat.final_semitones(), 0.0);
+        assert!((Intonation::Question.final_semitones() - 1.8).abs() < 0.001);
+        assert!((Intonation::Statement.final_semitones() + 0.9).abs() < 0.001);
+        assert!((Intonation::Excited.final_semitones() - 3.2).abs() < 0.001);
+        assert_eq!(Intonation::Excited.pitch_boost(), 2.0);
+        assert_eq!(Intonation::Custom(-0.5).glide(), -0.5);
+        assert_eq!(Intonation::Custom(-0.5).pitch_boost(), 0.0);
+    }
+
+    #[test]
+    fn test_intonation_values() {
         // Test setting various intonation values
-        profile.intonation = 0.5;
+        let mut profile = VoiceProfile { intonation: 0.5, ..Default::default() };
         assert_eq!(profile.intonation, 0.5);
 
         profile.intonation = -0.5;
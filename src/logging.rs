@@ -0,0 +1,26 @@
+//! Optional `log` events
+//!
+//! With the `log` feature on, [`event!`] forwards to the `log` crate; without
+//! it the arguments are type-checked but nothing is emitted or evaluated at
+//! runtime. Targets:
+//!
+//! - `animalese::speech`: utterances starting and ending, each sprite queued,
+//!   and speak() waiting on its lookahead window
+//! - `animalese::decode`: voice or SFX files that failed to decode
+//! - `animalese::playback`: stop() and keepalive changes, and audio underruns
+
+pub(crate) const SPEECH: &str = "animalese::speech";
+pub(crate) const DECODE: &str = "animalese::decode";
+pub(crate) const PLAYBACK: &str = "animalese::playback";
+
+/// `event!(level, target, format, args...)` where level is a `log` macro name
+macro_rules! event {
+    ($level:ident, $target:expr, $($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::$level!(target: $target, $($arg)+);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = ($target, format_args!($($arg)+));
+        }
+    }};
+}
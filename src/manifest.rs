@@ -0,0 +1,165 @@
+//! Describing a voice pack with a `voices.toml` manifest
+//!
+//! A manifest says where a pack's voice sheets are, which file each voice
+//! uses and how loud it should be, in the same TOML subset as profiles:
+//!
+//! ```toml
+//! voice_dir = "voice"     # relative to the manifest; sfx.ogg sits next to it
+//!
+//! [voices.f1]
+//! file = "f1.ogg"         # leave out to try f1.ogg, f1.wav, f1.mp3, f1.flac
+//! trim_db = -2.0          # see Animalese::set_voice_trim
+//! ```
+//!
+//! Voices without a table use their built-in filenames and no trim. That's
+//! all a manifest can say for now: per-voice pitch defaults stay the built-in
+//! [`VoiceType::default_pitch_variation`](crate::VoiceType::default_pitch_variation),
+//! and every pack must use the fixed sprite layout of
+//! [`LETTER_SLOT_MS`](crate::LETTER_SLOT_MS) and
+//! [`SFX_SLOT_MS`](crate::SFX_SLOT_MS) slots. Keys for either are rejected
+//! as unknown.
+
+use crate::profile_file::{number, parse_pair, string};
+use crate::{AnimaleseError, VoiceType};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The manifest of the bundled voice pack
+pub(crate) const BUNDLED_MANIFEST: &str = include_str!("../assets/audio/voices.toml");
+
+/// One voice's entry in a manifest
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct VoiceEntry {
+    /// The voice's file in the voice directory, if not its built-in names
+    pub file: Option<String>,
+    /// Volume trim in decibels
+    pub trim_db: f32,
+}
+
+/// A parsed manifest
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Manifest {
+    /// The voice directory, resolved against the manifest's directory
+    pub voice_dir: PathBuf,
+    /// Entries for the voices that have a table
+    pub voices: HashMap<VoiceType, VoiceEntry>,
+}
+
+fn invalid(line: usize, message: impl Into<String>) -> AnimaleseError {
+    AnimaleseError::InvalidManifest { line, message: message.into() }
+}
+
+/// Report a parse error from the shared TOML helpers as a manifest error
+fn as_manifest_error(error: AnimaleseError) -> AnimaleseError {
+    match error {
+        AnimaleseError::InvalidProfile { line, message } => invalid(line, message),
+        other => other,
+    }
+}
+
+impl Manifest {
+    /// Parse a manifest, resolving `voice_dir` against `base`
+    ///
+    /// Returns [`AnimaleseError::InvalidManifest`] for syntax errors, unknown
+    /// keys, tables or voices, values of the wrong type, a file name that
+    /// isn't a plain name in the voice directory, and repeated keys.
+    pub(crate) fn parse(toml: &str, base: &Path) -> Result<Self, AnimaleseError> {
+        let mut voice_dir = None;
+        let mut voices: HashMap<VoiceType, VoiceEntry> = HashMap::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut table: Option<VoiceType> = None;
+
+        for (index, raw) in toml.lines().enumerate() {
+            let line = index + 1;
+            let text = raw.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = text.strip_prefix('[') {
+                let name = header.split(']').next().unwrap_or_default().trim();
+                let voice = name.strip_prefix("voices.").filter(|_| header.contains(']'))
+                    .ok_or_else(|| invalid(line, format!("unknown table `[{}]`", name)))?;
+                let voice = voice.parse().map_err(|e: String| invalid(line, e))?;
+                if voices.insert(voice, VoiceEntry::default()).is_some() {
+                    return Err(invalid(line, format!("`[{}]` appears twice", name)));
+                }
+                table = Some(voice);
+                continue;
+            }
+
+            let (key, value) = parse_pair(text, line).map_err(as_manifest_error)?;
+            let qualified = match table {
+                Some(voice) => format!("voices.{}.{}", voice.name(), key),
+                None => key.clone(),
+            };
+            if let Some(first) = seen.insert(qualified, line) {
+                return Err(invalid(line, format!("`{}` is already set on line {}", key, first)));
+            }
+
+            if let Some(voice) = table {
+                let entry = voices.entry(voice).or_default();
+                match key.as_str() {
+                    "file" => {
+                        let file = string(&key, &value, line).map_err(as_manifest_error)?;
+                        let plain = Path::new(file).file_name().is_some_and(|name| name == file);
+                        if !plain {
+                            return Err(invalid(line, format!("`{}` should be a file name in the voice directory", file)));
+                        }
+                        entry.file = Some(file.to_string());
+                    }
+                    "trim_db" => entry.trim_db = number(&key, &value, line).map_err(as_manifest_error)? as f32,
+                    _ => return Err(invalid(line, format!("unknown voice key `{}`", key))),
+                }
+                continue;
+            }
+
+            match key.as_str() {
+                "voice_dir" => voice_dir = Some(base.join(string(&key, &value, line).map_err(as_manifest_error)?)),
+                _ => return Err(invalid(line, format!("unknown key `{}`", key))),
+            }
+        }
+
+        let voice_dir = voice_dir.ok_or_else(|| invalid(0, "`voice_dir` is missing"))?;
+        Ok(Manifest { voice_dir, voices })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let manifest = Manifest::parse(
+            "voice_dir = \"sheets\"\n\n[voices.m2]\nfile = \"low.wav\"\ntrim_db = -3.0\n\n[voices.f1]\n",
+            Path::new("/packs/mine"),
+        ).unwrap();
+        assert_eq!(manifest.voice_dir, Path::new("/packs/mine/sheets"));
+        assert_eq!(manifest.voices[&VoiceType::M2], VoiceEntry { file: Some("low.wav".into()), trim_db: -3.0 });
+        assert_eq!(manifest.voices[&VoiceType::F1], VoiceEntry::default());
+        assert_eq!(manifest.voices.len(), 2);
+
+        let line_of = |toml: &str| match Manifest::parse(toml, Path::new(".")) {
+            Err(AnimaleseError::InvalidManifest { line, .. }) => line,
+            other => panic!("expected an invalid manifest, got {:?}", other),
+        };
+        assert_eq!(line_of("[voices.f1]"), 0);
+        // Custom layouts aren't supported, so there's nothing to set
+        assert_eq!(line_of("voice_dir = \"v\"\nletter_slot_ms = 200"), 2);
+        assert_eq!(line_of("voice_dir = \"v\"\n[voices.x9]"), 2);
+        assert_eq!(line_of("voice_dir = \"v\"\n[voices.f1]\nfile = \"../f1.ogg\""), 3);
+        assert_eq!(line_of("voice_dir = \"v\"\n[voices.f1]\npitch_variation = 1.0"), 3);
+        assert_eq!(line_of("voice_dir = 3"), 1);
+    }
+
+    #[test]
+    fn test_bundled_manifest() {
+        let manifest = Manifest::parse(BUNDLED_MANIFEST, Path::new("assets/audio")).unwrap();
+        assert_eq!(manifest.voice_dir, Path::new("assets/audio/voice"));
+        for voice in VoiceType::ALL {
+            assert_eq!(manifest.voices[&voice].file.as_deref(), Some(voice.filenames()[0]));
+            assert_eq!(manifest.voices[&voice].trim_db, 0.0);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Inline markup for `speak()`
+//!
+//! A minimal SSML-like syntax for adjusting delivery within one string:
+//!
+//! - `<pitch=+3>...</pitch>` shifts pitch by the given semitones
+//! - `<rate=1.5>...</rate>` multiplies tempo
+//! - `<vol=0.5>...</vol>` multiplies volume
+//! - `<pause=250ms>` inserts a pause (also `<pause=250ms/>`), of at most a minute
+//!
+//! Spans nest, with pitch adding and rate/volume multiplying. A span left open
+//! runs to the end of the text. Anything that isn't a well-formed tag, including
+//! unknown tags, bad values and closing tags that don't match the innermost open
+//! span, is kept as literal text, where the angle brackets are silent like any
+//! other punctuation.
+//!
+//! With `markdown_emphasis` on, `*word*` and `**word**` are emphasis spans
+//! too; see [`emphasize`].
+
+use std::time::Duration;
+
+/// Longest `<pause>`, so offsets summed over many pauses stay in range
+const MAX_PAUSE: Duration = Duration::from_secs(60);
+
+/// Delivery adjustments active over a span of text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SpanStyle {
+    /// Semitones added to the profile pitch
+    pub pitch: f32,
+    /// Tempo multiplier
+    pub rate: f32,
+    /// Volume multiplier
+    pub volume: f32,
+}
+
+impl Default for SpanStyle {
+    fn default() -> Self {
+        Self {
+            pitch: 0.0,
+            rate: 1.0,
+            volume: 1.0,
+        }
+    }
+}
+
+/// A piece of marked-up text
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MarkupItem {
+    /// Text spoken with a style
+    Text(String, SpanStyle),
+    /// Silence before the next sound
+    Pause(Duration),
+}
+
+/// Kinds of span tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    Pitch,
+    Rate,
+    Vol,
+}
+
+impl TagKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pitch" => Some(TagKind::Pitch),
+            "rate" => Some(TagKind::Rate),
+            "vol" => Some(TagKind::Vol),
+            _ => None,
+        }
+    }
+}
+
+/// A recognized tag
+enum Tag {
+    Open(TagKind, f32),
+    Close(TagKind),
+    Pause(Duration),
+}
+
+/// Parse the inside of `<...>`, or `None` if it isn't a tag we understand
+fn parse_tag(inner: &str) -> Option<Tag> {
+    let inner = inner.trim();
+    if let Some(name) = inner.strip_prefix('/') {
+        return TagKind::from_name(name.trim()).map(Tag::Close);
+    }
+
+    let (name, value) = inner.split_once('=')?;
+    let name = name.trim();
+    let value = value.trim();
+
+    if name == "pause" {
+        let value = value.strip_suffix('/').unwrap_or(value).trim();
+        let ms = value.strip_suffix("ms").unwrap_or(value).trim();
+        let ms: f64 = ms.parse().ok()?;
+        if !ms.is_finite() || ms < 0.0 {
+            return None;
+        }
+        let pause = Duration::try_from_secs_f64(ms / 1000.0).ok()?;
+        return Some(Tag::Pause(pause.min(MAX_PAUSE)));
+    }
+
+    let kind = TagKind::from_name(name)?;
+    let amount: f32 = value.parse().ok()?;
+    if !amount.is_finite() || (kind != TagKind::Pitch && amount < 0.0) {
+        return None;
+    }
+    Some(Tag::Open(kind, amount))
+}
+
+/// Split marked-up text into styled runs and pauses
+pub(crate) fn parse(text: &str) -> Vec<MarkupItem> {
+    let mut items = Vec::new();
+    let mut stack: Vec<(TagKind, SpanStyle)> = Vec::new();
+    let mut style = SpanStyle::default();
+    let mut run = String::new();
+    let mut rest = text;
+
+    fn flush(items: &mut Vec<MarkupItem>, run: &mut String, style: SpanStyle) {
+        if !run.is_empty() {
+            items.push(MarkupItem::Text(std::mem::take(run), style));
+        }
+    }
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<'
+            && let Some(end) = rest.find('>')
+            && let Some(tag) = parse_tag(&rest[1..end])
+        {
+            match tag {
+                Tag::Open(kind, amount) => {
+                    flush(&mut items, &mut run, style);
+                    stack.push((kind, style));
+                    match kind {
+                        TagKind::Pitch => style.pitch += amount,
+                        TagKind::Rate => style.rate *= amount,
+                        TagKind::Vol => style.volume *= amount,
+                    }
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                Tag::Close(kind) if stack.last().is_some_and(|(open, _)| *open == kind) => {
+                    flush(&mut items, &mut run, style);
+                    style = stack.pop().unwrap().1;
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                Tag::Pause(duration) => {
+                    flush(&mut items, &mut run, style);
+                    items.push(MarkupItem::Pause(duration));
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                // Mismatched closing tag, keep it literally
+                Tag::Close(_) => {}
+            }
+        }
+
+        run.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    flush(&mut items, &mut run, style);
+
+    items
+}
+
+/// Boost for `*mild*` emphasis
+const MILD_EMPHASIS: SpanStyle = SpanStyle { pitch: 1.0, rate: 1.0, volume: 1.2 };
+
+/// Boost for `**strong**` emphasis, raised like excited speech
+const STRONG_EMPHASIS: SpanStyle = SpanStyle { pitch: 2.0, rate: 1.0, volume: 1.4 };
+
+/// Find where an emphasis span opened at the start of `text` closes
+///
+/// As in markdown, the opening delimiter must be followed and the closing
+/// one preceded by something other than whitespace. The closing delimiter
+/// is the tail of a run of asterisks, which may also close a span nested
+/// inside (`***` ends both `**a *b***` spans), but a `**` run never closes
+/// a single `*`.
+fn find_closing(text: &str, delimiter: &str) -> Option<usize> {
+    let inner = &text[delimiter.len()..];
+    if inner.is_empty() || inner.starts_with(|c: char| c.is_whitespace() || c == '*') {
+        return None;
+    }
+    let bytes = text.as_bytes();
+    let mut i = delimiter.len() + 1;
+    while i < bytes.len() {
+        if bytes[i] != b'*' {
+            i += 1;
+            continue;
+        }
+        let run_start = i;
+        while bytes.get(i) == Some(&b'*') {
+            i += 1;
+        }
+        let run = i - run_start;
+        let closes = match delimiter.len() {
+            1 => !run.is_multiple_of(2),
+            _ => run == 2 || run == 3,
+        };
+        if closes && !bytes[run_start - 1].is_ascii_whitespace() {
+            return Some(i - delimiter.len());
+        }
+    }
+    None
+}
+
+/// Split one run at its emphasis spans, boosting the text inside them
+fn emphasize_run(text: &str, style: SpanStyle, items: &mut Vec<MarkupItem>) {
+    let mut run = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '*' {
+            let (delimiter, boost) = if rest.starts_with("**") { ("**", STRONG_EMPHASIS) } else { ("*", MILD_EMPHASIS) };
+            if let Some(close) = find_closing(rest, delimiter) {
+                if !run.is_empty() {
+                    items.push(MarkupItem::Text(std::mem::take(&mut run), style));
+                }
+                let boosted = SpanStyle {
+                    pitch: style.pitch + boost.pitch,
+                    rate: style.rate * boost.rate,
+                    volume: style.volume * boost.volume,
+                };
+                emphasize_run(&rest[delimiter.len()..close], boosted, items);
+                rest = &rest[close + delimiter.len()..];
+            } else {
+                // Unmatched, keep the asterisks as literal (silent) symbols
+                run.push_str(delimiter);
+                rest = &rest[delimiter.len()..];
+            }
+            continue;
+        }
+        run.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    if !run.is_empty() {
+        items.push(MarkupItem::Text(run, style));
+    }
+}
+
+/// Turn markdown-style `*mild*` and `**strong**` emphasis into styled runs
+///
+/// Mild emphasis raises pitch a semitone and volume a little; strong
+/// emphasis gets the 2-semitone raise of excited speech and a bigger volume
+/// boost. Both stack on any tag styles, and nest. The asterisks of a span
+/// aren't voiced; unmatched ones are left as literal text. Spans don't cross
+/// tags or pauses.
+pub(crate) fn emphasize(items: Vec<MarkupItem>) -> Vec<MarkupItem> {
+    let mut emphasized = Vec::with_capacity(items.len());
+    for item in items {
+        match item {
+            MarkupItem::Text(text, style) => emphasize_run(&text, style, &mut emphasized),
+            pause => emphasized.push(pause),
+        }
+    }
+    emphasized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str, pitch: f32, rate: f32, volume: f32) -> MarkupItem {
+        MarkupItem::Text(s.to_string(), SpanStyle { pitch, rate, volume })
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(parse("hello"), vec![text("hello", 0.0, 1.0, 1.0)]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn test_spans_and_pause() {
+        assert_eq!(parse("hello <pitch=+3>world</pitch>!"), vec![
+            text("hello ", 0.0, 1.0, 1.0),
+            text("world", 3.0, 1.0, 1.0),
+            text("!", 0.0, 1.0, 1.0),
+        ]);
+        assert_eq!(parse("a<pause=250ms>b<pause=100/>"), vec![
+            text("a", 0.0, 1.0, 1.0),
+            MarkupItem::Pause(Duration::from_millis(250)),
+            text("b", 0.0, 1.0, 1.0),
+            MarkupItem::Pause(Duration::from_millis(100)),
+        ]);
+    }
+
+    #[test]
+    fn test_nested_tags() {
+        assert_eq!(parse("<pitch=2>a<pitch=-5>b<rate=2><vol=0.5>c</vol></rate></pitch>d</pitch>e"), vec![
+            text("a", 2.0, 1.0, 1.0),
+            text("b", -3.0, 1.0, 1.0),
+            text("c", -3.0, 2.0, 0.5),
+            text("d", 2.0, 1.0, 1.0),
+            text("e", 0.0, 1.0, 1.0),
+        ]);
+
+        // Unclosed spans run to the end
+        assert_eq!(parse("<rate=2>fast"), vec![text("fast", 0.0, 2.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_malformed_tags_are_literal() {
+        assert_eq!(parse("a < b"), vec![text("a < b", 0.0, 1.0, 1.0)]);
+        assert_eq!(parse("<pitch=high>x"), vec![text("<pitch=high>x", 0.0, 1.0, 1.0)]);
+        assert_eq!(parse("<shout>x"), vec![text("<shout>x", 0.0, 1.0, 1.0)]);
+        assert_eq!(parse("<rate=-1>x"), vec![text("<rate=-1>x", 0.0, 1.0, 1.0)]);
+        assert_eq!(parse("x</pitch>"), vec![text("x</pitch>", 0.0, 1.0, 1.0)]);
+        assert_eq!(parse("a<pause=1e30ms>b"), vec![text("a<pause=1e30ms>b", 0.0, 1.0, 1.0)]);
+
+        // Long pauses that fit a Duration are capped instead
+        assert_eq!(parse("a<pause=1e12ms>"), vec![text("a", 0.0, 1.0, 1.0), MarkupItem::Pause(MAX_PAUSE)]);
+
+        // Closing tag that doesn't match the innermost span
+        assert_eq!(parse("<pitch=1>a</rate>b"), vec![text("a</rate>b", 1.0, 1.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_emphasis() {
+        assert_eq!(emphasize(parse("*hi*")), vec![text("hi", 1.0, 1.0, 1.2)]);
+        assert_eq!(emphasize(parse("oh **hi**!")), vec![
+            text("oh ", 0.0, 1.0, 1.0),
+            text("hi", 2.0, 1.0, 1.4),
+            text("!", 0.0, 1.0, 1.0),
+        ]);
+        assert_eq!(emphasize(parse("**a *b***")), vec![
+            text("a ", 2.0, 1.0, 1.4),
+            text("b", 3.0, 1.0, 1.4 * 1.2),
+        ]);
+        assert_eq!(emphasize(parse("*a **b** c*")), vec![
+            text("a ", 1.0, 1.0, 1.2),
+            text("b", 3.0, 1.0, 1.2 * 1.4),
+            text(" c", 1.0, 1.0, 1.2),
+        ]);
+        assert_eq!(emphasize(parse("<pitch=-1>*hi*</pitch>")), vec![text("hi", 0.0, 1.0, 1.2)]);
+
+        // Stray and spaced-out asterisks are literal
+        assert_eq!(emphasize(parse("a*b")), vec![text("a*b", 0.0, 1.0, 1.0)]);
+        assert_eq!(emphasize(parse("2 * 3 * 4")), vec![text("2 * 3 * 4", 0.0, 1.0, 1.0)]);
+        assert_eq!(emphasize(parse("**")), vec![text("**", 0.0, 1.0, 1.0)]);
+    }
+}
@@ -0,0 +1,494 @@
+//! Saving and loading voice profiles as TOML
+//!
+//! Profiles are written as flat `key = value` pairs, with emoji mappings in an
+//! `[emoji_sfx]` table:
+//!
+//! ```toml
+//! voice_type = "m2"
+//! pitch_shift = -3.0
+//! pitch_variation = 0.4
+//! auto_question_intonation = 0.5   # or false to turn it off
+//! letter_gap_ms = 50.0
+//! sprite_overflow = "error"        # "clamp" or "wrap"
+//! lowpass_hz = 800.0               # or highpass_hz; leave out for no filter
+//! breath_sfx = "tab"               # leave out for no breaths
+//!
+//! [emoji_sfx]
+//! "❓" = "question"
+//! ```
+//!
+//! Only this subset of TOML is understood. Keys left out of a file keep their
+//! default values; unknown keys and malformed values are errors.
+
+use crate::{AnimaleseError, FilterKind, FilterSettings, IntonationBasis, QuestionShape, SpriteOverflow, UnmappedPolicy, VoiceProfile, VoiceType};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+/// A parsed TOML value
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Number(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl Value {
+    fn describe(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "a number",
+            Value::Bool(_) => "true or false",
+            Value::Str(_) => "a string",
+        }
+    }
+}
+
+fn invalid(line: usize, message: impl Into<String>) -> AnimaleseError {
+    AnimaleseError::InvalidProfile { line, message: message.into() }
+}
+
+/// Quote a string for TOML
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Parse a quoted string at the start of `s`, returning it and the rest
+fn parse_string(s: &str, line: usize) -> Result<(String, &str), AnimaleseError> {
+    let mut chars = s.char_indices();
+    if chars.next().map(|(_, c)| c) != Some('"') {
+        return Err(invalid(line, "expected a quoted string"));
+    }
+
+    let mut out = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((out, &s[i + 1..])),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('"') => out.push('"'),
+                Some('\\') => out.push('\\'),
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                _ => return Err(invalid(line, "unsupported escape in string")),
+            },
+            c => out.push(c),
+        }
+    }
+    Err(invalid(line, "unterminated string"))
+}
+
+/// Parse the value after `=`, allowing a trailing comment
+fn parse_value(s: &str, line: usize) -> Result<Value, AnimaleseError> {
+    let s = s.trim();
+    let (value, rest) = if s.starts_with('"') {
+        let (string, rest) = parse_string(s, line)?;
+        (Value::Str(string), rest)
+    } else {
+        let end = s.find('#').unwrap_or(s.len());
+        let token = s[..end].trim();
+        let value = match token {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => match token.parse::<f64>() {
+                Ok(n) if n.is_finite() => Value::Number(n),
+                _ => return Err(invalid(line, format!("can't read value `{}`", token))),
+            },
+        };
+        (value, &s[end..])
+    };
+
+    let rest = rest.trim();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(invalid(line, format!("unexpected `{}` after value", rest)));
+    }
+    Ok(value)
+}
+
+/// Split `key = value` into its parts, with the key unquoted if needed
+pub(crate) fn parse_pair(s: &str, line: usize) -> Result<(String, Value), AnimaleseError> {
+    let (key, rest) = if s.starts_with('"') {
+        parse_string(s, line)?
+    } else {
+        let end = s.find('=').ok_or_else(|| invalid(line, "expected `key = value`"))?;
+        (s[..end].trim().to_string(), &s[end..])
+    };
+
+    let rest = rest.trim_start().strip_prefix('=').ok_or_else(|| invalid(line, "expected `=` after key"))?;
+    Ok((key, parse_value(rest, line)?))
+}
+
+pub(crate) fn number(key: &str, value: &Value, line: usize) -> Result<f64, AnimaleseError> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        other => Err(invalid(line, format!("`{}` should be a number, not {}", key, other.describe()))),
+    }
+}
+
+fn millis(key: &str, value: &Value, line: usize) -> Result<Duration, AnimaleseError> {
+    let ms = number(key, value, line)?;
+    if ms < 0.0 {
+        return Err(invalid(line, format!("`{}` can't be negative", key)));
+    }
+    Duration::try_from_secs_f64(ms / 1000.0).map_err(|_| invalid(line, format!("`{}` is too long", key)))
+}
+
+fn boolean(key: &str, value: &Value, line: usize) -> Result<bool, AnimaleseError> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        other => Err(invalid(line, format!("`{}` should be true or false, not {}", key, other.describe()))),
+    }
+}
+
+pub(crate) fn string<'a>(key: &str, value: &'a Value, line: usize) -> Result<&'a str, AnimaleseError> {
+    match value {
+        Value::Str(s) => Ok(s),
+        other => Err(invalid(line, format!("`{}` should be a string, not {}", key, other.describe()))),
+    }
+}
+
+fn overflow_name(overflow: SpriteOverflow) -> &'static str {
+    match overflow {
+        SpriteOverflow::Error => "error",
+        SpriteOverflow::Clamp => "clamp",
+        SpriteOverflow::Wrap => "wrap",
+    }
+}
+
+fn question_shape_name(shape: QuestionShape) -> &'static str {
+    match shape {
+        QuestionShape::Rising => "rising",
+        QuestionShape::RisingFalling => "rising-falling",
+    }
+}
+
+fn intonation_basis_name(basis: IntonationBasis) -> &'static str {
+    match basis {
+        IntonationBasis::LetterCount => "letter-count",
+        IntonationBasis::Time => "time",
+    }
+}
+
+fn unmapped_name(policy: UnmappedPolicy) -> &'static str {
+    match policy {
+        UnmappedPolicy::Skip => "skip",
+        UnmappedPolicy::DefaultSfx => "default-sfx",
+        UnmappedPolicy::Blip => "blip",
+    }
+}
+
+impl VoiceProfile {
+    /// Write the profile as TOML
+    ///
+    /// The output reads back with [`VoiceProfile::from_toml`] to an equal
+    /// profile.
+    pub fn to_toml(&self) -> String {
+        let mut toml = String::new();
+        // Writing to a String can't fail
+        let _ = writeln!(toml, "voice_type = {}", quote(self.voice_type.name()));
+        let _ = writeln!(toml, "pitch_shift = {:?}", self.pitch_shift);
+        let _ = writeln!(toml, "pitch_variation = {:?}", self.pitch_variation);
+        let _ = writeln!(toml, "volume = {:?}", self.volume);
+        let _ = writeln!(toml, "intonation = {:?}", self.intonation);
+        let _ = writeln!(toml, "tempo = {:?}", self.tempo);
+        let _ = writeln!(toml, "volume_variation = {:?}", self.volume_variation);
+        let _ = writeln!(toml, "sprite_trim_start_ms = {:?}", self.sprite_trim_start.as_secs_f64() * 1000.0);
+        let _ = writeln!(toml, "time_stretch = {:?}", self.time_stretch);
+        let _ = writeln!(toml, "acronym_handling = {}", self.acronym_handling);
+        let _ = writeln!(toml, "markdown_emphasis = {}", self.markdown_emphasis);
+        let auto_question = self.auto_question_intonation.map_or("false".to_string(), |glide| format!("{:?}", glide));
+        let _ = writeln!(toml, "auto_question_intonation = {}", auto_question);
+        let _ = writeln!(toml, "max_word_length = {}", self.max_word_length);
+        let _ = writeln!(toml, "letter_gap_ms = {:?}", self.letter_gap.as_secs_f64() * 1000.0);
+        let _ = writeln!(toml, "word_pause_ms = {:?}", self.word_pause.as_secs_f64() * 1000.0);
+        let _ = writeln!(toml, "line_pause_ms = {:?}", self.line_pause.as_secs_f64() * 1000.0);
+        let _ = writeln!(toml, "adaptive_cadence = {}", self.adaptive_cadence);
+        let _ = writeln!(toml, "min_retrigger_gap_ms = {:?}", self.min_retrigger_gap.as_secs_f64() * 1000.0);
+        let _ = writeln!(toml, "reverse_sprites = {}", self.reverse_sprites);
+        let _ = writeln!(toml, "sprite_overflow = {}", quote(overflow_name(self.sprite_overflow)));
+        let _ = writeln!(toml, "speed_read = {}", self.speed_read);
+        let _ = writeln!(toml, "use_voice_defaults = {}", self.use_voice_defaults);
+        let _ = writeln!(toml, "question_shape = {}", quote(question_shape_name(self.question_shape)));
+        let _ = writeln!(toml, "unmapped = {}", quote(unmapped_name(self.unmapped)));
+        let _ = writeln!(toml, "intonation_range_semitones = {:?}", self.intonation_range_semitones);
+        let _ = writeln!(toml, "declination = {:?}", self.declination);
+        let _ = writeln!(toml, "pan = {:?}", self.pan);
+        let _ = writeln!(toml, "pitch_floor = {:?}", self.pitch_floor);
+        let _ = writeln!(toml, "pitch_ceiling = {:?}", self.pitch_ceiling);
+        let _ = writeln!(toml, "fade_in_ms = {:?}", self.fade_in.as_secs_f64() * 1000.0);
+        let _ = writeln!(toml, "voice_change_fade_ms = {:?}", self.voice_change_fade.as_secs_f64() * 1000.0);
+        let _ = writeln!(toml, "auto_terminal_intonation = {}", self.auto_terminal_intonation);
+        let _ = writeln!(toml, "detect_question_words = {}", self.detect_question_words);
+        let _ = writeln!(toml, "preserve_formants = {}", self.preserve_formants);
+        let _ = writeln!(toml, "intonation_basis = {}", quote(intonation_basis_name(self.intonation_basis)));
+        match self.filter {
+            Some(FilterSettings { kind: FilterKind::Lowpass, cutoff_hz }) => {
+                let _ = writeln!(toml, "lowpass_hz = {:?}", cutoff_hz);
+            }
+            Some(FilterSettings { kind: FilterKind::Highpass, cutoff_hz }) => {
+                let _ = writeln!(toml, "highpass_hz = {:?}", cutoff_hz);
+            }
+            None => {}
+        }
+        if let Some(breath) = &self.breath_sfx {
+            let _ = writeln!(toml, "breath_sfx = {}", quote(breath));
+        }
+
+        if !self.emoji_sfx.is_empty() {
+            let mut emoji: Vec<_> = self.emoji_sfx.iter().collect();
+            emoji.sort();
+            let _ = writeln!(toml, "\n[emoji_sfx]");
+            for (c, sfx) in emoji {
+                let _ = writeln!(toml, "{} = {}", quote(&c.to_string()), quote(sfx));
+            }
+        }
+        toml
+    }
+
+    /// Read a profile from TOML written by [`VoiceProfile::to_toml`] or by hand
+    ///
+    /// Missing keys keep their defaults. Returns
+    /// [`AnimaleseError::InvalidProfile`] for syntax errors, unknown keys,
+    /// values of the wrong type, unknown voices and repeated keys. SFX names
+    /// aren't checked, since they may name a bank registered later.
+    /// Values aren't range-checked here; `set_profile` clamps them.
+    pub fn from_toml(toml: &str) -> Result<Self, AnimaleseError> {
+        let mut profile = VoiceProfile::default();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut in_emoji_table = false;
+
+        for (index, raw) in toml.lines().enumerate() {
+            let line = index + 1;
+            let text = raw.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = text.strip_prefix('[') {
+                let name = header.split(']').next().unwrap_or_default().trim();
+                if name != "emoji_sfx" || !header.contains(']') {
+                    return Err(invalid(line, format!("unknown table `[{}]`", name)));
+                }
+                in_emoji_table = true;
+                continue;
+            }
+
+            let (key, value) = parse_pair(text, line)?;
+            let qualified = if in_emoji_table { format!("emoji_sfx.{}", key) } else { key.clone() };
+            if let Some(first) = seen.insert(qualified, line) {
+                return Err(invalid(line, format!("`{}` is already set on line {}", key, first)));
+            }
+
+            if in_emoji_table {
+                let mut chars = key.chars();
+                let (Some(emoji), None) = (chars.next(), chars.next()) else {
+                    return Err(invalid(line, format!("emoji key `{}` must be a single character", key)));
+                };
+                // Names are checked when played, since sound banks are registered at runtime
+                profile.emoji_sfx.insert(emoji, string(&key, &value, line)?.to_string());
+                continue;
+            }
+
+            match key.as_str() {
+                "voice_type" => {
+                    profile.voice_type = string(&key, &value, line)?
+                        .parse::<VoiceType>()
+                        .map_err(|e| invalid(line, e))?;
+                }
+                "pitch_shift" => profile.pitch_shift = number(&key, &value, line)? as f32,
+                "pitch_variation" => profile.pitch_variation = number(&key, &value, line)? as f32,
+                "volume" => profile.volume = number(&key, &value, line)? as f32,
+                "intonation" => profile.intonation = number(&key, &value, line)? as f32,
+                "tempo" => profile.tempo = number(&key, &value, line)? as f32,
+                "volume_variation" => profile.volume_variation = number(&key, &value, line)? as f32,
+                "intonation_range_semitones" => profile.intonation_range_semitones = number(&key, &value, line)? as f32,
+                "declination" => profile.declination = number(&key, &value, line)? as f32,
+                "pan" => profile.pan = number(&key, &value, line)? as f32,
+                "pitch_floor" => profile.pitch_floor = number(&key, &value, line)? as f32,
+                "pitch_ceiling" => profile.pitch_ceiling = number(&key, &value, line)? as f32,
+                "fade_in_ms" => profile.fade_in = millis(&key, &value, line)?,
+                "voice_change_fade_ms" => profile.voice_change_fade = millis(&key, &value, line)?,
+                "lowpass_hz" | "highpass_hz" => {
+                    let kind = if key == "lowpass_hz" { FilterKind::Lowpass } else { FilterKind::Highpass };
+                    let cutoff_hz = number(&key, &value, line)? as f32;
+                    profile.filter = Some(FilterSettings { kind, cutoff_hz });
+                }
+                "sprite_trim_start_ms" => profile.sprite_trim_start = millis(&key, &value, line)?,
+                "time_stretch" => profile.time_stretch = number(&key, &value, line)? as f32,
+                "acronym_handling" => profile.acronym_handling = boolean(&key, &value, line)?,
+                "markdown_emphasis" => profile.markdown_emphasis = boolean(&key, &value, line)?,
+                "auto_question_intonation" => {
+                    profile.auto_question_intonation = match value {
+                        Value::Bool(false) => None,
+                        ref other => Some(number(&key, other, line)? as f32),
+                    };
+                }
+                "max_word_length" => {
+                    let n = number(&key, &value, line)?;
+                    if n < 0.0 || n.fract() != 0.0 {
+                        return Err(invalid(line, "`max_word_length` should be a whole number"));
+                    }
+                    profile.max_word_length = n as usize;
+                }
+                "letter_gap_ms" => profile.letter_gap = millis(&key, &value, line)?,
+                "word_pause_ms" => profile.word_pause = millis(&key, &value, line)?,
+                "line_pause_ms" => profile.line_pause = millis(&key, &value, line)?,
+                "breath_sfx" => profile.breath_sfx = Some(string(&key, &value, line)?.to_string()),
+                "adaptive_cadence" => profile.adaptive_cadence = boolean(&key, &value, line)?,
+                "min_retrigger_gap_ms" => profile.min_retrigger_gap = millis(&key, &value, line)?,
+                "reverse_sprites" => profile.reverse_sprites = boolean(&key, &value, line)?,
+                "sprite_overflow" => {
+                    profile.sprite_overflow = match string(&key, &value, line)? {
+                        "error" => SpriteOverflow::Error,
+                        "clamp" => SpriteOverflow::Clamp,
+                        "wrap" => SpriteOverflow::Wrap,
+                        other => return Err(invalid(line, format!("unknown sprite_overflow `{}`", other))),
+                    };
+                }
+                "speed_read" => profile.speed_read = boolean(&key, &value, line)?,
+                "auto_terminal_intonation" => profile.auto_terminal_intonation = boolean(&key, &value, line)?,
+                "detect_question_words" => profile.detect_question_words = boolean(&key, &value, line)?,
+                "preserve_formants" => profile.preserve_formants = boolean(&key, &value, line)?,
+                "use_voice_defaults" => profile.use_voice_defaults = boolean(&key, &value, line)?,
+                "question_shape" => {
+                    profile.question_shape = match string(&key, &value, line)? {
+                        "rising" => QuestionShape::Rising,
+                        "rising-falling" => QuestionShape::RisingFalling,
+                        other => return Err(invalid(line, format!("unknown question_shape `{}`", other))),
+                    };
+                }
+                "intonation_basis" => {
+                    profile.intonation_basis = match string(&key, &value, line)? {
+                        "letter-count" => IntonationBasis::LetterCount,
+                        "time" => IntonationBasis::Time,
+                        other => return Err(invalid(line, format!("unknown intonation_basis `{}`", other))),
+                    };
+                }
+                "unmapped" => {
+                    profile.unmapped = match string(&key, &value, line)? {
+                        "skip" => UnmappedPolicy::Skip,
+                        "default-sfx" => UnmappedPolicy::DefaultSfx,
+                        "blip" => UnmappedPolicy::Blip,
+                        other => return Err(invalid(line, format!("unknown unmapped policy `{}`", other))),
+                    };
+                }
+                _ => return Err(invalid(line, format!("unknown key `{}`", key))),
+            }
+        }
+
+        Ok(profile)
+    }
+
+    /// Save the profile to a TOML file
+    ///
+    /// # Example
+    /// ```no_run
+    /// use animalese::{VoiceProfile, VoiceType};
+    ///
+    /// let profile = VoiceProfile { voice_type: VoiceType::M2, pitch_shift: -3.0, ..Default::default() };
+    /// profile.save("nook.toml").unwrap();
+    /// assert_eq!(VoiceProfile::load("nook.toml").unwrap(), profile);
+    /// ```
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_toml())?;
+        Ok(())
+    }
+
+    /// Load a profile from a TOML file (see [`VoiceProfile::from_toml`])
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let toml = std::fs::read_to_string(path)?;
+        Ok(Self::from_toml(&toml)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut profile = VoiceProfile {
+            voice_type: VoiceType::M2,
+            pitch_shift: -3.0,
+            pitch_variation: 0.4,
+            volume: 0.5,
+            tempo: 1.3,
+            sprite_trim_start: Duration::from_millis(15),
+            acronym_handling: true,
+            auto_question_intonation: None,
+            letter_gap: Duration::from_millis(80),
+            word_pause: Duration::from_millis(100),
+            line_pause: Duration::from_millis(200),
+            sprite_overflow: SpriteOverflow::Wrap,
+            speed_read: true,
+            use_voice_defaults: true,
+            question_shape: QuestionShape::RisingFalling,
+            unmapped: UnmappedPolicy::Blip,
+            intonation_range_semitones: 6.0,
+            filter: Some(FilterSettings { kind: FilterKind::Highpass, cutoff_hz: 1500.0 }),
+            auto_terminal_intonation: true,
+            intonation_basis: IntonationBasis::Time,
+            volume_variation: 0.25,
+            markdown_emphasis: true,
+            declination: 1.5,
+            min_retrigger_gap: Duration::from_millis(40),
+            pan: -0.5,
+            pitch_floor: -6.0,
+            pitch_ceiling: 9.0,
+            fade_in: Duration::ZERO,
+            adaptive_cadence: true,
+            breath_sfx: Some("tab".to_string()),
+            voice_change_fade: Duration::from_millis(30),
+            detect_question_words: true,
+            preserve_formants: true,
+            ..Default::default()
+        };
+        profile.emoji_sfx.insert('❓', "question".to_string());
+        profile.emoji_sfx.insert('"', "enter".to_string());
+        profile.emoji_sfx.insert('🔔', "ui:bell".to_string());
+
+        assert_eq!(VoiceProfile::from_toml(&profile.to_toml()), Ok(profile));
+        assert_eq!(VoiceProfile::from_toml(&VoiceProfile::default().to_toml()), Ok(VoiceProfile::default()));
+    }
+
+    #[test]
+    fn test_partial_file_and_comments() {
+        let profile = VoiceProfile::from_toml("# my preset\nvoice_type = \"F3\"  # loud\n\nvolume = 1\n").unwrap();
+        assert_eq!(profile.voice_type, VoiceType::F3);
+        assert_eq!(profile.volume, 1.0);
+        assert_eq!(profile.pitch_variation, VoiceProfile::default().pitch_variation);
+    }
+
+    #[test]
+    fn test_malformed_files() {
+        let line_of = |toml: &str| match VoiceProfile::from_toml(toml) {
+            Err(AnimaleseError::InvalidProfile { line, .. }) => line,
+            other => panic!("expected an error for {toml:?}, got {other:?}"),
+        };
+
+        assert_eq!(line_of("volume = loud"), 1);
+        assert_eq!(line_of("\nvolume = \"0.5\""), 2);
+        assert_eq!(line_of("pitch = 2.0"), 1);
+        assert_eq!(line_of("voice_type = \"x9\""), 1);
+        assert_eq!(line_of("voice_type = \"f1"), 1);
+        assert_eq!(line_of("volume 0.5"), 1);
+        assert_eq!(line_of("volume = 0.5 0.6"), 1);
+        assert_eq!(line_of("volume = 0.5\nvolume = 0.6"), 2);
+        assert_eq!(line_of("[voices]"), 1);
+        assert_eq!(line_of("[emoji_sfx]\n\"ab\" = \"enter\""), 2);
+        assert_eq!(line_of("[emoji_sfx]\n\"❓\" = 3"), 2);
+        assert_eq!(line_of("max_word_length = 2.5"), 1);
+        assert_eq!(line_of("letter_gap_ms = -5"), 1);
+        assert_eq!(line_of("\nletter_gap_ms = 1e300"), 2);
+    }
+}
@@ -0,0 +1,125 @@
+//! Extra SFX sprite sheets, addressed as `bank:sound`
+//!
+//! The bundled `sfx.ogg` is the default bank and is played with bare names.
+//! Further sheets are registered with
+//! [`Animalese::register_sfx_bank`](crate::Animalese::register_sfx_bank)
+//! along with a [`SpriteLayout`] saying where each sound sits in them.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Where each sound sits in an SFX sprite sheet
+///
+/// Every sprite in a layout has the same length.
+///
+/// # Example
+/// ```
+/// use animalese::SpriteLayout;
+/// use std::time::Duration;
+///
+/// // click at 0ms, hover at 300ms, close at 600ms
+/// let layout = SpriteLayout::sequential(["click", "hover", "close"], Duration::from_millis(300));
+/// assert_eq!(layout.offset("hover"), Some(Duration::from_millis(300)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteLayout {
+    sprites: Vec<(String, Duration)>,
+    sprite_length: Duration,
+}
+
+impl SpriteLayout {
+    /// Sounds laid end to end in the given order, each `sprite_length` long
+    pub fn sequential<S: Into<String>>(names: impl IntoIterator<Item = S>, sprite_length: Duration) -> Self {
+        let sprites = names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| (name.into(), sprite_length * i as u32))
+            .collect();
+        SpriteLayout { sprites, sprite_length }
+    }
+
+    /// Sounds at explicit offsets into the sheet, each `sprite_length` long
+    pub fn with_offsets<S: Into<String>>(sprites: impl IntoIterator<Item = (S, Duration)>, sprite_length: Duration) -> Self {
+        let sprites = sprites.into_iter().map(|(name, offset)| (name.into(), offset)).collect();
+        SpriteLayout { sprites, sprite_length }
+    }
+
+    /// The layout of the bundled `sfx.ogg`
+    pub fn builtin_sfx() -> Self {
+        Self::with_offsets(crate::builtin_sfx_map().iter().copied(), Duration::from_millis(crate::SFX_SLOT_MS))
+    }
+
+    /// Where a sound starts in the sheet
+    pub fn offset(&self, name: &str) -> Option<Duration> {
+        self.sprites.iter().find(|(sprite, _)| sprite == name).map(|(_, offset)| *offset)
+    }
+
+    /// Length of every sprite in the sheet
+    pub fn sprite_length(&self) -> Duration {
+        self.sprite_length
+    }
+
+    /// Names of the sounds in the sheet, in layout order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sprites.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// The sound that starts latest in the sheet
+    pub(crate) fn last_offset(&self) -> Option<Duration> {
+        self.sprites.iter().map(|(_, offset)| *offset).max()
+    }
+}
+
+/// A registered sheet and its layout
+#[derive(Debug, Clone)]
+pub(crate) struct SfxBank {
+    pub path: PathBuf,
+    pub layout: SpriteLayout,
+}
+
+/// Split `bank:sound` into its parts; bare names have no bank
+pub(crate) fn parse_sfx_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((bank, sound)) => (Some(bank), sound),
+        None => (None, name),
+    }
+}
+
+/// Check a bank name can be addressed with `bank:sound`
+pub(crate) fn validate_bank_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(':') || name.contains(char::is_whitespace) {
+        return Err(format!("Invalid SFX bank name {:?}: must be non-empty with no ':' or spaces", name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sprite_layout() {
+        let ms = Duration::from_millis;
+        let layout = SpriteLayout::sequential(["a", "b", "c"], ms(250));
+        assert_eq!(layout.offset("c"), Some(ms(500)));
+        assert_eq!(layout.offset("d"), None);
+        assert_eq!(layout.last_offset(), Some(ms(500)));
+        assert_eq!(layout.names().collect::<Vec<_>>(), ["a", "b", "c"]);
+
+        let builtin = SpriteLayout::builtin_sfx();
+        assert_eq!(builtin.sprite_length(), ms(600));
+        assert_eq!(builtin.offset("enter"), crate::sfx_to_sprite_time("enter"));
+    }
+
+    #[test]
+    fn test_parse_sfx_name() {
+        assert_eq!(parse_sfx_name("enter"), (None, "enter"));
+        assert_eq!(parse_sfx_name("ui:click"), (Some("ui"), "click"));
+        assert_eq!(parse_sfx_name("ui:"), (Some("ui"), ""));
+
+        assert!(validate_bank_name("ui").is_ok());
+        assert!(validate_bank_name("").is_err());
+        assert!(validate_bank_name("a:b").is_err());
+        assert!(validate_bank_name("my bank").is_err());
+    }
+}
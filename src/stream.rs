@@ -0,0 +1,108 @@
+//! Pull-based output for integrating with your own audio pipeline
+//!
+//! Instead of opening an output device, [`StreamBackend`] hands kira's renderer
+//! to a [`SampleStream`] that your mixer or audio callback reads from. This
+//! decouples synthesis from device output, so animalese can be mixed into a
+//! cpal stream, a game engine's audio graph, or a ring buffer you own.
+//!
+//! ```no_run
+//! use animalese::{Animalese, StreamBackend, StreamBackendSettings, bundled_assets_path};
+//! use kira::manager::{AudioManager, AudioManagerSettings};
+//!
+//! let manager = AudioManager::<StreamBackend>::new(AudioManagerSettings {
+//!     backend_settings: StreamBackendSettings { sample_rate: 48_000 },
+//!     ..Default::default()
+//! })?;
+//! let engine = Animalese::with_audio_manager(bundled_assets_path().to_string_lossy(), manager)?;
+//! let stream = engine.sample_stream();
+//!
+//! engine.play_letter('a')?;
+//!
+//! // Inside your audio callback: interleaved stereo f32
+//! let mut block = [0.0f32; 1024];
+//! stream.fill(&mut block);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use kira::manager::backend::{Backend, Renderer};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+/// Settings for [`StreamBackend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamBackendSettings {
+    /// Sample rate the consumer will pull at
+    pub sample_rate: u32,
+}
+
+impl Default for StreamBackendSettings {
+    fn default() -> Self {
+        Self { sample_rate: 44_100 }
+    }
+}
+
+/// A kira backend that renders on demand into caller-provided buffers
+pub struct StreamBackend {
+    renderer: Arc<Mutex<Option<Renderer>>>,
+}
+
+impl StreamBackend {
+    /// Get a handle for pulling rendered samples
+    pub fn sample_stream(&self) -> SampleStream {
+        SampleStream {
+            renderer: self.renderer.clone(),
+        }
+    }
+}
+
+impl Backend for StreamBackend {
+    type Settings = StreamBackendSettings;
+
+    type Error = Infallible;
+
+    fn setup(settings: Self::Settings) -> Result<(Self, u32), Self::Error> {
+        Ok((
+            Self {
+                renderer: Arc::new(Mutex::new(None)),
+            },
+            settings.sample_rate,
+        ))
+    }
+
+    fn start(&mut self, renderer: Renderer) -> Result<(), Self::Error> {
+        *self.renderer.lock().unwrap() = Some(renderer);
+        Ok(())
+    }
+}
+
+/// Handle for pulling samples out of a [`StreamBackend`]
+///
+/// Cheap to clone and `Send`, so it can be moved into an audio callback.
+#[derive(Clone)]
+pub struct SampleStream {
+    renderer: Arc<Mutex<Option<Renderer>>>,
+}
+
+impl SampleStream {
+    /// Render the next block of interleaved stereo samples into `buffer`
+    ///
+    /// `buffer` holds left/right pairs; a trailing odd sample is zeroed. Sounds
+    /// started since the previous call begin at the start of this block, so
+    /// smaller blocks give tighter timing.
+    pub fn fill(&self, buffer: &mut [f32]) {
+        let mut renderer = self.renderer.lock().unwrap();
+        let Some(renderer) = renderer.as_mut() else {
+            buffer.fill(0.0);
+            return;
+        };
+
+        renderer.on_start_processing();
+        let mut frames = buffer.chunks_exact_mut(2);
+        for frame in &mut frames {
+            let out = renderer.process();
+            frame[0] = out.left;
+            frame[1] = out.right;
+        }
+        frames.into_remainder().fill(0.0);
+    }
+}
@@ -0,0 +1,130 @@
+//! Exporting a planned utterance as a JSON timeline
+//!
+//! The schema is documented on [`Animalese::timeline_json`](crate::Animalese::timeline_json),
+//! the only caller. Written by hand, like the profile TOML, to keep the crate
+//! free of serialization dependencies.
+
+use crate::{PlannedSound, SoundSource, VoiceProfile, final_pitch};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Quote a string for JSON
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A duration in milliseconds, to the microsecond
+fn millis(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+/// A number for JSON, which has no NaN or infinity, so those become `null`
+fn number<T: Copy + Into<f64> + std::fmt::Debug>(value: T) -> String {
+    if value.into().is_finite() { format!("{:?}", value) } else { "null".to_string() }
+}
+
+/// One sound of the timeline as a JSON object
+fn sound_json(sound: &PlannedSound, profile: &VoiceProfile) -> String {
+    let (source, name, on_voice_sheet) = match &sound.source {
+        SoundSource::Letter(c) => ("letter", c.to_string(), true),
+        SoundSource::Special(name) => ("special", name.to_string(), true),
+        SoundSource::Sfx(name) => ("sfx", name.clone(), false),
+    };
+    let voice = if on_voice_sheet {
+        quote(sound.voice.unwrap_or(profile.voice_type).name())
+    } else {
+        "null".to_string()
+    };
+    let pitch = if sound.pitched {
+        let pitch = profile.pitch_shift + sound.pitch_offset + sound.intonation_shift;
+        final_pitch(pitch, 0.0, profile.pitch_floor, profile.pitch_ceiling)
+    } else {
+        0.0
+    };
+
+    format!(
+        "{{\"source\": {}, \"name\": {}, \"sheet\": {}, \"voice\": {}, \"start_ms\": {}, \"duration_ms\": {}, \
+         \"sprite_start_ms\": {}, \"pitch_semitones\": {}, \"volume\": {}, \"pan\": {}}}",
+        quote(source),
+        quote(&name),
+        quote(if on_voice_sheet { "voice" } else { "sfx" }),
+        voice,
+        number(millis(sound.offset)),
+        number(millis(sound.length)),
+        number(millis(sound.sprite_start)),
+        number(pitch),
+        number(profile.volume * sound.volume),
+        number((profile.pan + sound.pan).clamp(-1.0, 1.0)),
+    )
+}
+
+/// A plan as a JSON timeline, see `Animalese::timeline_json` for the schema
+pub(crate) fn to_json(plan: &[PlannedSound], profile: &VoiceProfile) -> String {
+    let duration = plan.iter().map(|sound| sound.offset + sound.length).max().unwrap_or(Duration::ZERO);
+    let mut json = format!("{{\n  \"duration_ms\": {:?},\n  \"sounds\": [", millis(duration));
+    for (index, sound) in plan.iter().enumerate() {
+        let separator = if index == 0 { "" } else { "," };
+        let _ = write!(json, "{}\n    {}", separator, sound_json(sound, profile));
+    }
+    json.push_str(if plan.is_empty() { "]\n}" } else { "\n  ]\n}" });
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan_utterance;
+
+    #[test]
+    fn test_to_json() {
+        let profile = VoiceProfile { volume: 0.5, ..Default::default() };
+        let plan = plan_utterance("<pitch=+2>h</pitch>[m2]i \u{2753}", &VoiceProfile {
+            emoji_sfx: [('\u{2753}', "question".to_string())].into(),
+            ..profile.clone()
+        }, 0.0);
+
+        assert_eq!(to_json(&plan, &profile), concat!(
+            "{\n",
+            "  \"duration_ms\": 700.0,\n",
+            "  \"sounds\": [\n",
+            "    {\"source\": \"letter\", \"name\": \"h\", \"sheet\": \"voice\", \"voice\": \"f1\", \"start_ms\": 0.0, ",
+            "\"duration_ms\": 200.0, \"sprite_start_ms\": 1400.0, \"pitch_semitones\": 2.0, \"volume\": 0.5, \"pan\": 0.0},\n",
+            "    {\"source\": \"letter\", \"name\": \"i\", \"sheet\": \"voice\", \"voice\": \"m2\", \"start_ms\": 50.0, ",
+            "\"duration_ms\": 200.0, \"sprite_start_ms\": 1600.0, \"pitch_semitones\": 0.0, \"volume\": 0.5, \"pan\": 0.0},\n",
+            "    {\"source\": \"sfx\", \"name\": \"question\", \"sheet\": \"sfx\", \"voice\": null, \"start_ms\": 100.0, ",
+            "\"duration_ms\": 600.0, \"sprite_start_ms\": 1800.0, \"pitch_semitones\": 0.0, \"volume\": 0.5, \"pan\": 0.0}\n",
+            "  ]\n",
+            "}",
+        ));
+        assert_eq!(to_json(&[], &profile), "{\n  \"duration_ms\": 0.0,\n  \"sounds\": []\n}");
+    }
+
+    #[test]
+    fn test_non_finite_numbers_are_null() {
+        let plan = plan_utterance("h", &VoiceProfile::default(), 0.0);
+        let json = to_json(&plan, &VoiceProfile { volume: f32::NAN, pan: f32::INFINITY, ..Default::default() });
+        assert!(json.contains("\"volume\": null"), "{json}");
+        assert!(json.contains("\"pan\": 1.0"), "clamped first: {json}");
+        assert_eq!(number(f32::NEG_INFINITY), "null");
+        assert_eq!(number(0.65f32), "0.65");
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+}